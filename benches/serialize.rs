@@ -0,0 +1,93 @@
+//! Rust-side counterpart of `benchmarks/benchmark.mjs`: fills a sheet with
+//! the same 100,000 x 10 grid and times writing it to memory, without the
+//! cost of crossing the JS boundary for every cell.
+//!
+//! Run with `cargo bench --bench serialize`.
+
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use excelifyjs::format::Format;
+use excelifyjs::save_options::SaveOptions;
+use excelifyjs::workbook::Workbook;
+use excelifyjs::writer::XlsxWriter;
+
+const ROWS: u32 = 100_000;
+const COLS: u32 = 10;
+const RUNS: usize = 5;
+
+fn build_workbook() -> Workbook {
+    let mut workbook = Workbook::new();
+    workbook.add_worksheet("Data").unwrap();
+    let sheet = workbook.get_worksheet_mut(0).unwrap();
+    for row in 0..ROWS {
+        for col in 0..COLS {
+            match col % 3 {
+                0 => sheet.write_string(row, col, format!("Cell {}-{}", row, col)),
+                1 => sheet.write_number(row, col, (row * col) as f64 + 0.5),
+                _ => sheet.write_boolean(row, col, row % 2 == 0),
+            }
+            .unwrap();
+        }
+    }
+    sheet
+        .set_range_format(0, 0, 0, COLS - 1, Format::new().bold())
+        .unwrap();
+    workbook
+}
+
+/// Runs `f` several times and returns the median duration.
+fn median(mut f: impl FnMut()) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort_unstable();
+    times[RUNS / 2]
+}
+
+fn report(label: &str, time: Duration) {
+    let cells = f64::from(ROWS * COLS);
+    println!(
+        "{:<8} {:>10.2} ms {:>14.0} cells/sec",
+        label,
+        time.as_secs_f64() * 1000.0,
+        cells / time.as_secs_f64()
+    );
+}
+
+fn main() {
+    println!(
+        "Benchmark: {} rows x {} columns, median of {} runs",
+        ROWS, COLS, RUNS
+    );
+    println!("{}", "=".repeat(60));
+
+    report("Write", median(|| drop(black_box(build_workbook()))));
+
+    let workbook = build_workbook();
+    let mut size = 0;
+    let save = median(|| {
+        let buffer = XlsxWriter::new(&workbook)
+            .write_to(Cursor::new(Vec::new()))
+            .unwrap();
+        size = buffer.get_ref().len();
+    });
+    report("Save", save);
+    println!("File size: {:.2} MB", size as f64 / 1024.0 / 1024.0);
+
+    let stored = median(|| {
+        let buffer = XlsxWriter::new(&workbook)
+            .options(SaveOptions::new().compression_level(0))
+            .write_to(Cursor::new(Vec::new()))
+            .unwrap();
+        size = buffer.get_ref().len();
+    });
+    report("Stored", stored);
+    println!("File size: {:.2} MB", size as f64 / 1024.0 / 1024.0);
+    println!("{}", "=".repeat(60));
+}
@@ -1,3 +1,5 @@
+// Measures the whole Node.js path. `cargo bench --bench serialize` runs the
+// same workload in Rust only, to separate serialization from binding costs.
 import { Workbook } from '../index.js';
 import { statSync, existsSync, unlinkSync } from 'fs';
 
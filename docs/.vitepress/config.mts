@@ -29,6 +29,8 @@ export default defineConfig({
         items: [
           { text: 'Workbook', link: '/workbook' },
           { text: 'Worksheet', link: '/worksheet' },
+          { text: 'Chart', link: '/chart' },
+          { text: 'ExcelError', link: '/excel-error' },
         ],
         collapsed: false,
       },
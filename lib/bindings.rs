@@ -3,13 +3,235 @@ use napi_derive::napi;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::cell::CellValue;
+use crate::cell::{format_number, CellError, CellValue, NonFinitePolicy};
+use crate::chart::{Chart as InnerChart, ChartAxis, ChartSeries, ChartType, LegendPosition};
+use crate::conditional_format::{ConditionalCriteria, ConditionalFormat, IconSetStyle};
+use crate::defined_name::DefinedName;
+use crate::error::ExcelifyError;
+use crate::format::{Color, Format};
+use crate::header_footer::HeaderFooter;
+use crate::image::Image;
+use crate::page_setup::{Orientation, PageMargins, PageSetup};
+use crate::properties::{CustomProperty, DocumentProperties, Timestamp};
+use crate::protection::{PasswordHash, SheetProtection};
+use crate::reference::{CellRange, CellRef};
+use crate::rich_text::{RichString, TextRun};
+use crate::save_options::SaveOptions as InnerSaveOptions;
+use crate::sheet_view::{SheetView, SheetVisibility};
+use crate::validation::{DataValidation, ValidationCriteria, ValidationErrorStyle, ValidationRule};
 use crate::workbook::Workbook as InnerWorkbook;
+use crate::worksheet::{
+    check_cell, range_reference, RangeBounds, Worksheet as InnerWorksheet, MAX_COLS, MAX_ROWS,
+};
 
 type CellInput = Either3<String, f64, bool>;
 
+/// A cell value as passed to `Worksheet.write`.
+type ValueInput<'env> =
+    Either5<String, f64, bool, ClassInstance<'env, ExcelError>, Vec<RichTextRun>>;
+
+/// The third argument of `Worksheet.write`: the value when the cell is given
+/// as `row, col`, or the format after an A1 address.
+type ValueOrFormat<'env> =
+    Either6<String, f64, bool, ClassInstance<'env, ExcelError>, Vec<RichTextRun>, FormatOptions>;
+
+/// A worksheet given by index, name or handle.
+type SheetInput<'env> = Either3<u32, String, ClassInstance<'env, Worksheet>>;
+
+/// A cell value as returned to JavaScript.
+type ValueOutput = Either5<String, f64, bool, ExcelError, Vec<RichTextRun>>;
+
+/// A non-empty cell as returned by `Worksheet.cells`.
+#[napi(object, object_from_js = false)]
+pub struct UsedCell {
+    pub row: u32,
+    pub col: u32,
+    pub value: ValueOutput,
+}
+
+/// The bounds of a worksheet's non-empty cells.
+#[napi(object)]
+pub struct UsedRange {
+    pub first_row: u32,
+    pub first_col: u32,
+    pub last_row: u32,
+    pub last_col: u32,
+    /// The same range in A1 notation, e.g. `B2:D10`.
+    pub range: String,
+}
+
+#[napi(object)]
+pub struct ClearOptions {
+    /// Also removes the cells' formats. Defaults to `false`.
+    pub formats: Option<bool>,
+}
+
+/// The size of a worksheet's used area, counted from A1.
+#[napi(object)]
+pub struct Dimensions {
+    pub rows: u32,
+    pub cols: u32,
+}
+
+/// A piece of rich text and its font. Runs without font options use the
+/// cell's font.
+#[napi(object)]
+pub struct RichTextRun {
+    pub text: String,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub strikeout: Option<bool>,
+    /// Hex color such as `#C00000`.
+    pub font_color: Option<String>,
+}
+
+impl RichTextRun {
+    fn from_run(run: &TextRun) -> Self {
+        let font = run.font();
+        let flag = |set: fn(&Format) -> bool| font.map(set).filter(|&on| on);
+        Self {
+            text: run.text().to_string(),
+            bold: flag(Format::is_bold),
+            italic: flag(Format::is_italic),
+            underline: flag(Format::is_underline),
+            strikeout: flag(Format::is_strikeout),
+            font_color: font
+                .and_then(Format::get_font_color)
+                .map(|color| format!("#{}", color.to_rgb_hex())),
+        }
+    }
+}
+
+fn rich_string(runs: Vec<RichTextRun>) -> Result<RichString> {
+    let mut rich = RichString::new();
+    for run in runs {
+        let mut font = Format::new();
+        if run.bold.unwrap_or(false) {
+            font = font.bold();
+        }
+        if run.italic.unwrap_or(false) {
+            font = font.italic();
+        }
+        if run.underline.unwrap_or(false) {
+            font = font.underline();
+        }
+        if run.strikeout.unwrap_or(false) {
+            font = font.strikeout();
+        }
+        if let Some(color) = run.font_color {
+            font = font.font_color(parse_color(&color)?);
+        }
+        rich = rich.formatted(run.text, font);
+    }
+    Ok(rich)
+}
+
 type SharedWorkbook = Rc<RefCell<InnerWorkbook>>;
 
+#[napi(object)]
+pub struct DefinedNameOptions {
+    /// Name or zero-based index of the worksheet the name is local to.
+    pub sheet_scope: Option<Either<u32, String>>,
+    pub hidden: Option<bool>,
+}
+
+#[napi(object)]
+pub struct DocumentPropertiesOptions {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub author: Option<String>,
+    pub manager: Option<String>,
+    pub company: Option<String>,
+    pub category: Option<String>,
+    pub keywords: Option<String>,
+    pub comments: Option<String>,
+    pub status: Option<String>,
+    pub hyperlink_base: Option<String>,
+    /// Milliseconds since the epoch (`date.getTime()`) or an ISO 8601 string.
+    pub created: Option<Either<f64, String>>,
+}
+
+impl DocumentPropertiesOptions {
+    fn into_properties(self) -> Result<DocumentProperties, &'static str> {
+        let mut properties = DocumentProperties::new();
+        let text_fields = [
+            (self.title, DocumentProperties::title as TextSetter),
+            (self.subject, DocumentProperties::subject),
+            (self.author, DocumentProperties::author),
+            (self.manager, DocumentProperties::manager),
+            (self.company, DocumentProperties::company),
+            (self.category, DocumentProperties::category),
+            (self.keywords, DocumentProperties::keywords),
+            (self.comments, DocumentProperties::comments),
+            (self.status, DocumentProperties::status),
+            (self.hyperlink_base, DocumentProperties::hyperlink_base),
+        ];
+        for (value, setter) in text_fields {
+            if let Some(value) = value {
+                properties = setter(properties, value);
+            }
+        }
+        if let Some(created) = self.created {
+            properties = properties.created(parse_timestamp(created)?);
+        }
+        Ok(properties)
+    }
+}
+
+type TextSetter = fn(DocumentProperties, String) -> DocumentProperties;
+
+fn parse_timestamp(value: Either<f64, String>) -> Result<Timestamp, &'static str> {
+    match value {
+        Either::A(millis) => Timestamp::from_unix_millis(millis),
+        Either::B(text) => Timestamp::parse(&text),
+    }
+    .map_err(coded_error)
+}
+
+#[napi(object)]
+pub struct SaveOptions {
+    /// Deflate level from 1 (fastest) to 9 (smallest), or 0 to store the
+    /// parts uncompressed. Defaults to 6.
+    pub compression_level: Option<u32>,
+    /// Write ZIP64 records so that parts can exceed 4 GiB.
+    pub zip64: Option<bool>,
+    /// A fixed save time, as milliseconds since the epoch or an ISO 8601
+    /// string, so that saving the same workbook twice gives identical files.
+    pub timestamp: Option<Either<f64, String>>,
+}
+
+impl SaveOptions {
+    fn into_options(self) -> Result<InnerSaveOptions, &'static str> {
+        let mut options = InnerSaveOptions::new().zip64(self.zip64.unwrap_or(false));
+        if let Some(level) = self.compression_level {
+            options = options.compression_level(level);
+        }
+        if let Some(timestamp) = self.timestamp {
+            let timestamp = match timestamp {
+                Either::A(millis) => Timestamp::from_unix_millis(millis),
+                Either::B(text) => Timestamp::parse(&text),
+            }
+            .map_err(|e| match e {
+                ExcelifyError::InvalidProperty(reason) => {
+                    coded_error(ExcelifyError::InvalidSaveOptions(reason))
+                }
+                e => coded_error(e),
+            })?;
+            options = options.timestamp(timestamp);
+        }
+        options.validate().map_err(coded_error)?;
+        Ok(options)
+    }
+}
+
+#[napi(object)]
+pub struct AddWorksheetOptions {
+    /// Replace invalid characters, truncate long names and add a ` (2)`
+    /// suffix to duplicates instead of throwing.
+    pub sanitize: Option<bool>,
+}
+
 #[napi]
 pub struct Workbook {
     inner: SharedWorkbook,
@@ -24,21 +246,201 @@ impl Workbook {
         }
     }
 
+    /// Adds a worksheet. Invalid or duplicate names throw unless `sanitize`
+    /// is set, in which case the name is fixed up and suffixed as needed.
     #[napi]
-    pub fn add_worksheet(&self, name: String) -> Worksheet {
-        let index = self.inner.borrow_mut().add_worksheet(name);
-        Worksheet {
-            workbook: Rc::clone(&self.inner),
-            index,
+    pub fn add_worksheet(
+        &self,
+        name: String,
+        options: Option<AddWorksheetOptions>,
+    ) -> Result<Worksheet, &'static str> {
+        let sanitize = options.and_then(|o| o.sanitize).unwrap_or(false);
+        let mut workbook = self.inner.borrow_mut();
+        let index = if sanitize {
+            workbook.add_worksheet_sanitized(&name)
+        } else {
+            workbook.add_worksheet(name).map_err(coded_error)?
+        };
+        Ok(self.handle(&workbook, index))
+    }
+
+    /// Returns the worksheet at an index or with a name, or `null` if there
+    /// is none. Names are matched case-insensitively, like in Excel.
+    #[napi]
+    pub fn get_worksheet(&self, name_or_index: Either<u32, String>) -> Option<Worksheet> {
+        let workbook = self.inner.borrow();
+        let index = match name_or_index {
+            Either::A(index) => Some(index as usize).filter(|&i| i < workbook.worksheet_count()),
+            Either::B(name) => workbook.worksheet_index_by_name(&name),
+        }?;
+        Some(self.handle(&workbook, index))
+    }
+
+    /// All worksheets, in tab order.
+    #[napi(getter)]
+    pub fn worksheets(&self) -> Vec<Worksheet> {
+        let workbook = self.inner.borrow();
+        (0..workbook.worksheet_count())
+            .map(|index| self.handle(&workbook, index))
+            .collect()
+    }
+
+    /// Removes a worksheet, given as an index, a name or a `Worksheet`.
+    /// References to it become `#REF!` and handles on it stop working.
+    #[napi]
+    pub fn remove_worksheet(&self, sheet: SheetInput) -> Result<(), &'static str> {
+        let index = self.sheet_index(&sheet)?;
+        self.inner
+            .borrow_mut()
+            .remove_worksheet(index)
+            .map(drop)
+            .map_err(coded_error)
+    }
+
+    /// Renames a worksheet and updates references to it.
+    #[napi]
+    pub fn rename_worksheet(
+        &self,
+        sheet: SheetInput,
+        new_name: String,
+    ) -> Result<(), &'static str> {
+        let index = self.sheet_index(&sheet)?;
+        self.inner
+            .borrow_mut()
+            .rename_worksheet(index, new_name)
+            .map_err(coded_error)
+    }
+
+    /// Moves a worksheet to the tab position `to`.
+    #[napi]
+    pub fn move_worksheet(&self, sheet: SheetInput, to: u32) -> Result<(), &'static str> {
+        let index = self.sheet_index(&sheet)?;
+        self.inner
+            .borrow_mut()
+            .move_worksheet(index, to as usize)
+            .map_err(coded_error)
+    }
+
+    /// Adds a copy of a worksheet, with its values, formats and settings,
+    /// as the last sheet.
+    #[napi]
+    pub fn copy_worksheet(
+        &self,
+        sheet: SheetInput,
+        new_name: String,
+    ) -> Result<Worksheet, &'static str> {
+        let index = self.sheet_index(&sheet)?;
+        let mut workbook = self.inner.borrow_mut();
+        let copy = workbook
+            .copy_worksheet(index, new_name)
+            .map_err(coded_error)?;
+        Ok(self.handle(&workbook, copy))
+    }
+
+    /// Defines a named range or constant, e.g. `defineName('Rates', '=Data!$B$2:$B$10')`.
+    #[napi]
+    pub fn define_name(
+        &self,
+        name: String,
+        refers_to: String,
+        options: Option<DefinedNameOptions>,
+    ) -> Result<(), &'static str> {
+        let mut defined_name = DefinedName::new(name, refers_to);
+        if let Some(options) = options {
+            if let Some(scope) = options.sheet_scope {
+                let index = match scope {
+                    Either::A(index) => self.sheet_index(&SheetInput::A(index))?,
+                    Either::B(sheet_name) => self.sheet_index(&SheetInput::B(sheet_name))?,
+                };
+                defined_name = defined_name.scope(index);
+            }
+            defined_name = defined_name.hidden(options.hidden.unwrap_or(false));
         }
+        self.inner
+            .borrow_mut()
+            .define_name(defined_name)
+            .map_err(coded_error)
+    }
+
+    #[napi]
+    pub fn set_properties(&self, options: DocumentPropertiesOptions) -> Result<(), &'static str> {
+        let properties = options.into_properties()?;
+        self.inner.borrow_mut().set_properties(properties);
+        Ok(())
+    }
+
+    /// Sets a custom document property. Pass `'date'` as the type to store a
+    /// timestamp or ISO 8601 string as a date.
+    #[napi]
+    pub fn set_custom_property(
+        &self,
+        name: String,
+        value: CellInput,
+        value_type: Option<String>,
+    ) -> Result<(), &'static str> {
+        let property = match (value, value_type.as_deref()) {
+            (Either3::A(text), Some("date")) => {
+                CustomProperty::Date(parse_timestamp(Either::B(text))?)
+            }
+            (Either3::B(millis), Some("date")) => {
+                CustomProperty::Date(parse_timestamp(Either::A(millis))?)
+            }
+            (_, Some("date")) => {
+                return Err(coded_arg_error(
+                    "A date property needs a timestamp or an ISO 8601 string",
+                ))
+            }
+            (_, Some(other)) => {
+                return Err(coded_arg_error(&format!(
+                    "Unknown custom property type: {}",
+                    other
+                )))
+            }
+            (Either3::A(text), None) => CustomProperty::Text(text),
+            (Either3::B(number), None) => CustomProperty::Number(number),
+            (Either3::C(flag), None) => CustomProperty::Boolean(flag),
+        };
+        self.inner
+            .borrow_mut()
+            .set_custom_property(name, property)
+            .map_err(coded_error)
+    }
+
+    /// Chooses what `write` does with `NaN` and `±Infinity`: `'error'`
+    /// (the default) throws, `'empty'` leaves the cell empty and `'numError'`
+    /// writes a `#NUM!` error cell.
+    #[napi]
+    pub fn set_non_finite_policy(&self, policy: String) -> Result<(), &'static str> {
+        let policy = NonFinitePolicy::from_name(&policy)
+            .ok_or_else(|| coded_arg_error(&format!("Unknown non-finite policy: {}", policy)))?;
+        self.inner.borrow_mut().set_non_finite_policy(policy);
+        Ok(())
+    }
+
+    /// Stops users from adding, removing, renaming or reordering sheets.
+    #[napi]
+    pub fn protect_structure(
+        &self,
+        password: Option<String>,
+        legacy_hash: Option<bool>,
+    ) -> Result<(), &'static str> {
+        let password = hash_password(password, legacy_hash.unwrap_or(false))?;
+        self.inner.borrow_mut().protect_structure(password);
+        Ok(())
     }
 
+    /// Saves the workbook as an `.xlsx` file. Invalid options throw with
+    /// `code` set to `ERR_INVALID_SAVE_OPTIONS`.
     #[napi]
-    pub fn save(&self, path: String) -> Result<()> {
+    pub fn save(&self, path: String, options: Option<SaveOptions>) -> Result<(), &'static str> {
+        let options = options
+            .map(SaveOptions::into_options)
+            .transpose()?
+            .unwrap_or_default();
         self.inner
             .borrow()
-            .save(&path)
-            .map_err(|e| Error::from_reason(e.to_string()))
+            .save_with_options(&path, options)
+            .map_err(coded_error)
     }
 
     #[napi(getter)]
@@ -47,16 +449,797 @@ impl Workbook {
     }
 }
 
+impl Workbook {
+    fn handle(&self, workbook: &InnerWorkbook, index: usize) -> Worksheet {
+        Worksheet {
+            workbook: Rc::clone(&self.inner),
+            id: workbook.worksheet_id(index).unwrap_or_default(),
+        }
+    }
+
+    fn sheet_index(&self, sheet: &SheetInput) -> Result<usize, &'static str> {
+        let workbook = self.inner.borrow();
+        match sheet {
+            Either3::A(index) => Some(*index as usize)
+                .filter(|&i| i < workbook.worksheet_count())
+                .ok_or_else(|| coded_error(ExcelifyError::SheetNotFound(*index as usize))),
+            Either3::B(name) => workbook.worksheet_index_by_name(name).ok_or_else(|| {
+                Error::new(
+                    "ERR_SHEET_NOT_FOUND",
+                    format!("Worksheet not found: {}", name),
+                )
+            }),
+            Either3::C(worksheet) => {
+                if !Rc::ptr_eq(&worksheet.workbook, &self.inner) {
+                    return Err(coded_arg_error("Worksheet belongs to another workbook"));
+                }
+                workbook
+                    .worksheet_index(worksheet.id)
+                    .ok_or_else(removed_sheet_error)
+            }
+        }
+    }
+}
+
 impl Default for Workbook {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[napi(object)]
+pub struct DataValidationOptions {
+    /// One of `list`, `whole`, `decimal`, `date`, `textLength` or `custom`.
+    #[napi(js_name = "type")]
+    pub validation_type: String,
+    /// Literal values for a `list` validation.
+    pub values: Option<Vec<String>>,
+    /// Range supplying a `list` validation, e.g. `=$A$1:$A$10`.
+    pub source: Option<String>,
+    /// Comparison for numeric, date and text-length validations. Defaults to `between`.
+    pub criteria: Option<String>,
+    pub value: Option<f64>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    /// Formula for a `custom` validation.
+    pub formula: Option<String>,
+    pub ignore_blank: Option<bool>,
+    pub show_dropdown: Option<bool>,
+    pub input_title: Option<String>,
+    pub input_message: Option<String>,
+    /// One of `stop`, `warning` or `information`.
+    pub error_style: Option<String>,
+    pub error_title: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl DataValidationOptions {
+    fn into_validation(self) -> Result<DataValidation> {
+        let rule = match self.validation_type.as_str() {
+            "list" => match (self.values, self.source) {
+                (Some(values), None) => ValidationRule::List(values),
+                (None, Some(source)) => ValidationRule::ListRange(source),
+                _ => {
+                    return Err(Error::from_reason(
+                        "List validation requires exactly one of `values` or `source`",
+                    ))
+                }
+            },
+            "whole" => ValidationRule::WholeNumber(parse_criteria(
+                self.criteria.as_deref(),
+                self.value,
+                self.minimum,
+                self.maximum,
+            )?),
+            "decimal" => ValidationRule::Decimal(parse_criteria(
+                self.criteria.as_deref(),
+                self.value,
+                self.minimum,
+                self.maximum,
+            )?),
+            "date" => ValidationRule::Date(parse_criteria(
+                self.criteria.as_deref(),
+                self.value,
+                self.minimum,
+                self.maximum,
+            )?),
+            "textLength" => ValidationRule::TextLength(parse_criteria(
+                self.criteria.as_deref(),
+                self.value,
+                self.minimum,
+                self.maximum,
+            )?),
+            "custom" => ValidationRule::Custom(
+                self.formula
+                    .ok_or_else(|| Error::from_reason("Custom validation requires `formula`"))?,
+            ),
+            other => {
+                return Err(Error::from_reason(format!(
+                    "Unknown validation type: {}",
+                    other
+                )))
+            }
+        };
+
+        let mut validation = DataValidation::new(rule);
+        if let Some(ignore) = self.ignore_blank {
+            validation = validation.ignore_blank(ignore);
+        }
+        if let Some(show) = self.show_dropdown {
+            validation = validation.show_dropdown(show);
+        }
+        if self.input_title.is_some() || self.input_message.is_some() {
+            validation = validation.input_prompt(
+                self.input_title.unwrap_or_default(),
+                self.input_message.unwrap_or_default(),
+            );
+        }
+        if self.error_style.is_some() || self.error_title.is_some() || self.error_message.is_some()
+        {
+            let style = match self.error_style.as_deref() {
+                None | Some("stop") => ValidationErrorStyle::Stop,
+                Some("warning") => ValidationErrorStyle::Warning,
+                Some("information") => ValidationErrorStyle::Information,
+                Some(other) => {
+                    return Err(Error::from_reason(format!(
+                        "Unknown error style: {}",
+                        other
+                    )))
+                }
+            };
+            validation = validation.error_alert(
+                style,
+                self.error_title.unwrap_or_default(),
+                self.error_message.unwrap_or_default(),
+            );
+        }
+        Ok(validation)
+    }
+}
+
+fn parse_criteria(
+    criteria: Option<&str>,
+    value: Option<f64>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+) -> Result<ValidationCriteria> {
+    let single = || value.ok_or_else(|| Error::from_reason("Validation requires `value`"));
+    let pair = || match (minimum, maximum) {
+        (Some(min), Some(max)) => Ok((min, max)),
+        _ => Err(Error::from_reason(
+            "Validation requires `minimum` and `maximum`",
+        )),
+    };
+
+    Ok(match criteria.unwrap_or("between") {
+        "between" => pair().map(|(min, max)| ValidationCriteria::Between(min, max))?,
+        "notBetween" => pair().map(|(min, max)| ValidationCriteria::NotBetween(min, max))?,
+        "equal" => ValidationCriteria::EqualTo(single()?),
+        "notEqual" => ValidationCriteria::NotEqualTo(single()?),
+        "greaterThan" => ValidationCriteria::GreaterThan(single()?),
+        "lessThan" => ValidationCriteria::LessThan(single()?),
+        "greaterThanOrEqual" => ValidationCriteria::GreaterThanOrEqualTo(single()?),
+        "lessThanOrEqual" => ValidationCriteria::LessThanOrEqualTo(single()?),
+        other => {
+            return Err(Error::from_reason(format!(
+                "Unknown validation criteria: {}",
+                other
+            )))
+        }
+    })
+}
+
+#[napi(object)]
+pub struct FormatOptions {
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub strikeout: Option<bool>,
+    /// Hex color such as `#9C0006`.
+    pub font_color: Option<String>,
+    pub fill_color: Option<String>,
+    /// Draws a thin border of this color around the cell.
+    pub border_color: Option<String>,
+    /// Excel number format code, e.g. `0.00%`.
+    pub num_format: Option<String>,
+    /// Whether the cell is read-only on a protected sheet. Defaults to `true`.
+    pub locked: Option<bool>,
+    /// Hides the cell's formula on a protected sheet.
+    pub hidden: Option<bool>,
+}
+
+impl FormatOptions {
+    fn into_format(self) -> Result<Format> {
+        let mut format = Format::new();
+        if self.bold.unwrap_or(false) {
+            format = format.bold();
+        }
+        if self.italic.unwrap_or(false) {
+            format = format.italic();
+        }
+        if self.underline.unwrap_or(false) {
+            format = format.underline();
+        }
+        if self.strikeout.unwrap_or(false) {
+            format = format.strikeout();
+        }
+        if let Some(color) = self.font_color {
+            format = format.font_color(parse_color(&color)?);
+        }
+        if let Some(color) = self.fill_color {
+            format = format.fill_color(parse_color(&color)?);
+        }
+        if let Some(color) = self.border_color {
+            format = format.border_color(parse_color(&color)?);
+        }
+        if let Some(code) = self.num_format {
+            format = format.num_format(code);
+        }
+        if let Some(locked) = self.locked {
+            format = format.locked(locked);
+        }
+        if let Some(hidden) = self.hidden {
+            format = format.hidden(hidden);
+        }
+        Ok(format)
+    }
+}
+
+fn parse_color(hex: &str) -> Result<Color> {
+    Color::from_hex(hex).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+#[napi(object)]
+pub struct ConditionalFormatOptions {
+    /// One of `cellValue`, `formula`, `top`, `bottom`, `aboveAverage`,
+    /// `belowAverage`, `duplicate`, `unique`, `colorScale`, `dataBar` or `iconSet`.
+    #[napi(js_name = "type")]
+    pub rule_type: String,
+    /// Comparison for `cellValue` rules. Defaults to `equal`.
+    pub criteria: Option<String>,
+    /// Operand for `cellValue` rules. Strings are treated as formulas.
+    pub value: Option<Either<f64, String>>,
+    pub minimum: Option<Either<f64, String>>,
+    pub maximum: Option<Either<f64, String>>,
+    /// Formula for `formula` rules, e.g. `=$C2>100`.
+    pub formula: Option<String>,
+    /// Number of items for `top`/`bottom` rules. Defaults to 10.
+    pub rank: Option<u32>,
+    pub percent: Option<bool>,
+    /// Format applied when the rule matches.
+    pub format: Option<FormatOptions>,
+    pub min_color: Option<String>,
+    /// Midpoint color; turns a color scale into a 3-color scale.
+    pub mid_color: Option<String>,
+    pub max_color: Option<String>,
+    pub bar_color: Option<String>,
+    /// Icon set name such as `3Arrows` or `5Rating`. Defaults to `3TrafficLights1`.
+    pub icon_style: Option<String>,
+    pub reverse_icons: Option<bool>,
+    pub show_value: Option<bool>,
+}
+
+impl ConditionalFormatOptions {
+    fn into_rule(self) -> Result<ConditionalFormat> {
+        let format = self
+            .format
+            .map(FormatOptions::into_format)
+            .transpose()?
+            .unwrap_or_default();
+        let color = |hex: Option<String>, default: u32| match hex {
+            Some(hex) => parse_color(&hex),
+            None => Ok(Color::rgb(default)),
+        };
+
+        Ok(match self.rule_type.as_str() {
+            "cellValue" => ConditionalFormat::CellValue {
+                criteria: parse_conditional_criteria(
+                    self.criteria.as_deref(),
+                    self.value,
+                    self.minimum,
+                    self.maximum,
+                )?,
+                format,
+            },
+            "formula" => ConditionalFormat::Formula {
+                formula: self
+                    .formula
+                    .ok_or_else(|| Error::from_reason("Formula rule requires `formula`"))?,
+                format,
+            },
+            "top" | "bottom" => ConditionalFormat::TopBottom {
+                rank: self.rank.unwrap_or(10),
+                percent: self.percent.unwrap_or(false),
+                bottom: self.rule_type == "bottom",
+                format,
+            },
+            "aboveAverage" | "belowAverage" => ConditionalFormat::Average {
+                below: self.rule_type == "belowAverage",
+                format,
+            },
+            "duplicate" | "unique" => ConditionalFormat::Duplicates {
+                unique: self.rule_type == "unique",
+                format,
+            },
+            "colorScale" => match self.mid_color {
+                Some(mid) => ConditionalFormat::ColorScale3 {
+                    min_color: color(self.min_color, 0xF8696B)?,
+                    mid_color: parse_color(&mid)?,
+                    max_color: color(self.max_color, 0x63BE7B)?,
+                },
+                None => ConditionalFormat::ColorScale2 {
+                    min_color: color(self.min_color, 0xFFEF9C)?,
+                    max_color: color(self.max_color, 0x63BE7B)?,
+                },
+            },
+            "dataBar" => ConditionalFormat::DataBar {
+                color: color(self.bar_color, 0x638EC6)?,
+            },
+            "iconSet" => ConditionalFormat::IconSet {
+                style: match self.icon_style.as_deref() {
+                    None => IconSetStyle::ThreeTrafficLights,
+                    Some(name) => IconSetStyle::from_name(name).ok_or_else(|| {
+                        Error::from_reason(format!("Unknown icon style: {}", name))
+                    })?,
+                },
+                reverse: self.reverse_icons.unwrap_or(false),
+                show_value: self.show_value.unwrap_or(true),
+            },
+            other => {
+                return Err(Error::from_reason(format!(
+                    "Unknown conditional format type: {}",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+fn parse_conditional_criteria(
+    criteria: Option<&str>,
+    value: Option<Either<f64, String>>,
+    minimum: Option<Either<f64, String>>,
+    maximum: Option<Either<f64, String>>,
+) -> Result<ConditionalCriteria> {
+    let operand = |v: Either<f64, String>| match v {
+        Either::A(n) => format_number(n),
+        Either::B(s) => s,
+    };
+    let single = || {
+        value
+            .map(operand)
+            .ok_or_else(|| Error::from_reason("Cell value rule requires `value`"))
+    };
+    let pair = || match (minimum, maximum) {
+        (Some(min), Some(max)) => Ok((operand(min), operand(max))),
+        _ => Err(Error::from_reason(
+            "Cell value rule requires `minimum` and `maximum`",
+        )),
+    };
+
+    Ok(match criteria.unwrap_or("equal") {
+        "between" => pair().map(|(min, max)| ConditionalCriteria::Between(min, max))?,
+        "notBetween" => pair().map(|(min, max)| ConditionalCriteria::NotBetween(min, max))?,
+        "equal" => ConditionalCriteria::EqualTo(single()?),
+        "notEqual" => ConditionalCriteria::NotEqualTo(single()?),
+        "greaterThan" => ConditionalCriteria::GreaterThan(single()?),
+        "lessThan" => ConditionalCriteria::LessThan(single()?),
+        "greaterThanOrEqual" => ConditionalCriteria::GreaterThanOrEqualTo(single()?),
+        "lessThanOrEqual" => ConditionalCriteria::LessThanOrEqualTo(single()?),
+        other => {
+            return Err(Error::from_reason(format!(
+                "Unknown conditional format criteria: {}",
+                other
+            )))
+        }
+    })
+}
+
+#[napi(object)]
+pub struct ChartSeriesOptions {
+    /// Sheet-qualified range of values, e.g. `Sheet1!$B$2:$B$13`.
+    pub values: String,
+    /// Range of category labels, or x values for scatter charts.
+    pub categories: Option<String>,
+    /// Legend name. Use a leading `=` to reference a cell, e.g. `=Sheet1!$B$1`.
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[napi(object)]
+pub struct ChartAxisOptions {
+    pub title: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub major_gridlines: Option<bool>,
+}
+
+#[napi]
+pub struct Chart {
+    inner: InnerChart,
+}
+
+#[napi]
+impl Chart {
+    /// Creates a chart of type `column`, `bar`, `line`, `pie`, `scatter` or `area`.
+    #[napi(constructor)]
+    pub fn new(chart_type: String) -> Result<Self> {
+        let chart_type = ChartType::from_name(&chart_type)
+            .ok_or_else(|| Error::from_reason(format!("Unknown chart type: {}", chart_type)))?;
+        Ok(Self {
+            inner: InnerChart::new(chart_type),
+        })
+    }
+
+    #[napi]
+    pub fn add_series(&mut self, options: ChartSeriesOptions) -> Result<()> {
+        let mut series = ChartSeries::new(options.values);
+        if let Some(categories) = options.categories {
+            series = series.categories(categories);
+        }
+        if let Some(name) = options.name {
+            series = series.name(name);
+        }
+        if let Some(color) = options.color {
+            series = series.color(parse_color(&color)?);
+        }
+        self.update(|chart| chart.add_series(series));
+        Ok(())
+    }
+
+    #[napi]
+    pub fn set_title(&mut self, title: String) {
+        self.update(|chart| chart.title(title));
+    }
+
+    #[napi]
+    pub fn set_x_axis(&mut self, options: ChartAxisOptions) {
+        let axis = options.into_axis(false);
+        self.update(|chart| chart.x_axis(axis));
+    }
+
+    #[napi]
+    pub fn set_y_axis(&mut self, options: ChartAxisOptions) {
+        let axis = options.into_axis(true);
+        self.update(|chart| chart.y_axis(axis));
+    }
+
+    /// Sets the legend position: `right`, `left`, `top`, `bottom` or `none`.
+    #[napi]
+    pub fn set_legend(&mut self, position: String) -> Result<()> {
+        let position = LegendPosition::from_name(&position)
+            .ok_or_else(|| Error::from_reason(format!("Unknown legend position: {}", position)))?;
+        self.update(|chart| chart.legend(position));
+        Ok(())
+    }
+
+    /// Sets the chart size in pixels.
+    #[napi]
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.update(|chart| chart.size(width, height));
+    }
+}
+
+impl Chart {
+    fn update(&mut self, f: impl FnOnce(InnerChart) -> InnerChart) {
+        let placeholder = InnerChart::new(self.inner.chart_type());
+        let chart = std::mem::replace(&mut self.inner, placeholder);
+        self.inner = f(chart);
+    }
+}
+
+impl ChartAxisOptions {
+    fn into_axis(self, default_gridlines: bool) -> ChartAxis {
+        let mut axis =
+            ChartAxis::new().major_gridlines(self.major_gridlines.unwrap_or(default_gridlines));
+        if let Some(title) = self.title {
+            axis = axis.title(title);
+        }
+        if let Some(min) = self.min {
+            axis = axis.min(min);
+        }
+        if let Some(max) = self.max {
+            axis = axis.max(max);
+        }
+        axis
+    }
+}
+
+#[napi(object)]
+pub struct ImageOptions {
+    pub scale_x: Option<f64>,
+    pub scale_y: Option<f64>,
+    /// Horizontal offset from the anchor cell, in pixels.
+    pub x_offset: Option<u32>,
+    /// Vertical offset from the anchor cell, in pixels.
+    pub y_offset: Option<u32>,
+    /// Description read by screen readers.
+    pub alt_text: Option<String>,
+}
+
+#[napi(object)]
+pub struct PageMarginsOptions {
+    pub left: Option<f64>,
+    pub right: Option<f64>,
+    pub top: Option<f64>,
+    pub bottom: Option<f64>,
+    pub header: Option<f64>,
+    pub footer: Option<f64>,
+}
+
+#[napi(object)]
+pub struct PageSetupOptions {
+    /// Either `portrait` or `landscape`.
+    pub orientation: Option<String>,
+    /// Excel paper size code, e.g. 1 for Letter or 9 for A4.
+    pub paper_size: Option<u32>,
+    /// Print scale in percent (10-400).
+    pub scale: Option<u32>,
+    /// Pages across to fit the printout on. 0 leaves the width unconstrained.
+    pub fit_to_width: Option<u32>,
+    /// Pages down to fit the printout on. 0 leaves the height unconstrained.
+    pub fit_to_height: Option<u32>,
+    /// Margins in inches.
+    pub margins: Option<PageMarginsOptions>,
+    pub center_horizontally: Option<bool>,
+    pub center_vertically: Option<bool>,
+    pub print_gridlines: Option<bool>,
+    pub print_headings: Option<bool>,
+    /// `[firstRow, firstCol, lastRow, lastCol]`.
+    pub print_area: Option<Vec<u32>>,
+    /// `[firstRow, lastRow]` repeated at the top of each page.
+    pub repeat_rows: Option<Vec<u32>>,
+    /// `[firstCol, lastCol]` repeated at the left of each page.
+    pub repeat_cols: Option<Vec<u32>>,
+}
+
+impl PageSetupOptions {
+    fn into_page_setup(self) -> Result<PageSetup> {
+        let mut setup = PageSetup::new();
+        if let Some(name) = self.orientation {
+            let orientation = Orientation::from_name(&name)
+                .ok_or_else(|| Error::from_reason(format!("Unknown orientation: {}", name)))?;
+            setup = setup.orientation(orientation);
+        }
+        if let Some(paper_size) = self.paper_size {
+            setup = setup.paper_size(paper_size);
+        }
+        if let Some(scale) = self.scale {
+            setup = setup.scale(scale);
+        }
+        if self.fit_to_width.is_some() || self.fit_to_height.is_some() {
+            setup = setup.fit_to_pages(
+                self.fit_to_width.unwrap_or(1),
+                self.fit_to_height.unwrap_or(1),
+            );
+        }
+        if let Some(margins) = self.margins {
+            let defaults = PageMargins::default();
+            setup = setup.margins(PageMargins {
+                left: margins.left.unwrap_or(defaults.left),
+                right: margins.right.unwrap_or(defaults.right),
+                top: margins.top.unwrap_or(defaults.top),
+                bottom: margins.bottom.unwrap_or(defaults.bottom),
+                header: margins.header.unwrap_or(defaults.header),
+                footer: margins.footer.unwrap_or(defaults.footer),
+            });
+        }
+        setup = setup
+            .center_horizontally(self.center_horizontally.unwrap_or(false))
+            .center_vertically(self.center_vertically.unwrap_or(false))
+            .print_gridlines(self.print_gridlines.unwrap_or(false))
+            .print_headings(self.print_headings.unwrap_or(false));
+        if let Some(area) = self.print_area {
+            match area[..] {
+                [first_row, first_col, last_row, last_col] => {
+                    setup = setup.print_area(first_row, first_col, last_row, last_col);
+                }
+                _ => {
+                    return Err(Error::from_reason(
+                        "printArea must be [firstRow, firstCol, lastRow, lastCol]",
+                    ))
+                }
+            }
+        }
+        if let Some(rows) = self.repeat_rows {
+            let (first, last) = parse_span(&rows, "repeatRows")?;
+            setup = setup.repeat_rows(first, last);
+        }
+        if let Some(cols) = self.repeat_cols {
+            let (first, last) = parse_span(&cols, "repeatCols")?;
+            setup = setup.repeat_cols(first, last);
+        }
+        Ok(setup)
+    }
+}
+
+/// Accepts `[index]` or `[first, last]`.
+fn parse_span(span: &[u32], option: &str) -> Result<(u32, u32)> {
+    match *span {
+        [index] => Ok((index, index)),
+        [first, last] => Ok((first, last)),
+        _ => Err(Error::from_reason(format!(
+            "{} must be [first] or [first, last]",
+            option
+        ))),
+    }
+}
+
+#[napi(object)]
+pub struct HeaderFooterOptions {
+    /// Text for the first printed page.
+    pub first_page: Option<String>,
+    /// Text for even pages. The main text is then used on odd pages.
+    pub even_pages: Option<String>,
+}
+
+fn header_footer(text: String, options: Option<HeaderFooterOptions>) -> HeaderFooter {
+    let mut header_footer = HeaderFooter::new(text);
+    if let Some(options) = options {
+        if let Some(first_page) = options.first_page {
+            header_footer = header_footer.first_page(first_page);
+        }
+        if let Some(even_pages) = options.even_pages {
+            header_footer = header_footer.even_pages(even_pages);
+        }
+    }
+    header_footer
+}
+
+/// Actions users may still perform on a protected worksheet. Everything not
+/// listed here is locked.
+#[napi(object)]
+#[derive(Default)]
+pub struct ProtectionOptions {
+    /// Store the password with the legacy 16-bit hash instead of SHA-512.
+    /// Needed only for very old spreadsheet applications.
+    pub legacy_hash: Option<bool>,
+    pub select_locked_cells: Option<bool>,
+    pub select_unlocked_cells: Option<bool>,
+    pub format_cells: Option<bool>,
+    pub format_columns: Option<bool>,
+    pub format_rows: Option<bool>,
+    pub insert_columns: Option<bool>,
+    pub insert_rows: Option<bool>,
+    pub insert_hyperlinks: Option<bool>,
+    pub delete_columns: Option<bool>,
+    pub delete_rows: Option<bool>,
+    pub sort: Option<bool>,
+    pub autofilter: Option<bool>,
+    pub pivot_tables: Option<bool>,
+    pub edit_objects: Option<bool>,
+    pub edit_scenarios: Option<bool>,
+}
+
+fn hash_password(
+    password: Option<String>,
+    legacy: bool,
+) -> Result<Option<PasswordHash>, &'static str> {
+    password
+        .map(|password| {
+            if legacy {
+                Ok(PasswordHash::legacy(&password))
+            } else {
+                PasswordHash::sha512(&password).map_err(coded_error)
+            }
+        })
+        .transpose()
+}
+
+type ProtectionSetter = fn(SheetProtection, bool) -> SheetProtection;
+
+impl ProtectionOptions {
+    fn into_protection(self, password: Option<String>) -> Result<SheetProtection, &'static str> {
+        let mut protection = SheetProtection::new();
+        if let Some(password) = hash_password(password, self.legacy_hash.unwrap_or(false))? {
+            protection = protection.password(password);
+        }
+        let allow: [(Option<bool>, ProtectionSetter); 15] = [
+            (
+                self.select_locked_cells,
+                SheetProtection::select_locked_cells,
+            ),
+            (
+                self.select_unlocked_cells,
+                SheetProtection::select_unlocked_cells,
+            ),
+            (self.format_cells, SheetProtection::format_cells),
+            (self.format_columns, SheetProtection::format_columns),
+            (self.format_rows, SheetProtection::format_rows),
+            (self.insert_columns, SheetProtection::insert_columns),
+            (self.insert_rows, SheetProtection::insert_rows),
+            (self.insert_hyperlinks, SheetProtection::insert_hyperlinks),
+            (self.delete_columns, SheetProtection::delete_columns),
+            (self.delete_rows, SheetProtection::delete_rows),
+            (self.sort, SheetProtection::sort),
+            (self.autofilter, SheetProtection::autofilter),
+            (self.pivot_tables, SheetProtection::pivot_tables),
+            (self.edit_objects, SheetProtection::edit_objects),
+            (self.edit_scenarios, SheetProtection::edit_scenarios),
+        ];
+        for (value, setter) in allow {
+            if let Some(value) = value {
+                protection = setter(protection, value);
+            }
+        }
+        Ok(protection)
+    }
+}
+
+#[napi(object)]
+pub struct SheetViewOptions {
+    /// Zoom level in percent (10-400).
+    pub zoom: Option<u32>,
+    pub show_gridlines: Option<bool>,
+    /// Show row numbers and column letters.
+    pub show_headings: Option<bool>,
+    pub right_to_left: Option<bool>,
+    /// `[row, col]` or `[firstRow, firstCol, lastRow, lastCol]`.
+    pub selection: Option<Vec<u32>>,
+}
+
+impl SheetViewOptions {
+    fn into_view(self) -> Result<SheetView> {
+        let mut view = SheetView::new()
+            .show_gridlines(self.show_gridlines.unwrap_or(true))
+            .show_headings(self.show_headings.unwrap_or(true))
+            .right_to_left(self.right_to_left.unwrap_or(false));
+        if let Some(zoom) = self.zoom {
+            view = view.zoom(zoom);
+        }
+        if let Some(selection) = self.selection {
+            view =
+                match selection[..] {
+                    [row, col] => view.selection(row, col, row, col),
+                    [first_row, first_col, last_row, last_col] => {
+                        view.selection(first_row, first_col, last_row, last_col)
+                    }
+                    _ => return Err(Error::from_reason(
+                        "selection must be [row, col] or [firstRow, firstCol, lastRow, lastCol]",
+                    )),
+                };
+        }
+        Ok(view)
+    }
+}
+
+/// An Excel error value such as `#N/A`, for writing and reading error cells:
+/// `sheet.write('A1', new ExcelError('#N/A'))`.
+#[napi]
+#[derive(Clone, Copy)]
+pub struct ExcelError {
+    inner: CellError,
+}
+
+#[napi]
+impl ExcelError {
+    /// Creates an error value from the text Excel shows: `#NULL!`, `#DIV/0!`,
+    /// `#VALUE!`, `#REF!`, `#NAME?`, `#NUM!` or `#N/A`.
+    #[napi(constructor)]
+    pub fn new(value: String) -> Result<Self> {
+        let inner = CellError::from_name(&value)
+            .ok_or_else(|| Error::from_reason(format!("Unknown Excel error: {}", value)))?;
+        Ok(Self { inner })
+    }
+
+    #[napi(getter)]
+    pub fn value(&self) -> &'static str {
+        self.inner.as_str()
+    }
+
+    #[napi]
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> &'static str {
+        self.inner.as_str()
+    }
+}
+
+/// A handle on one sheet of a workbook. It refers to the sheet by its
+/// stable id, so it stays valid when other sheets are removed or moved.
 #[napi]
 pub struct Worksheet {
     workbook: SharedWorkbook,
-    index: usize,
+    id: u32,
 }
 
 #[napi]
@@ -68,25 +1251,623 @@ impl Worksheet {
         ))
     }
 
+    /// Writes a value to a cell, addressed either as `write(row, col, value,
+    /// format?)` or in A1 notation as `write('B3', value, format?)`. Cells
+    /// outside Excel's grid and strings over 32,767 characters throw with
+    /// `code` set to `ERR_ROW_OUT_OF_RANGE`, `ERR_COLUMN_OUT_OF_RANGE` or
+    /// `ERR_STRING_TOO_LONG`.
+    #[napi]
+    pub fn write(
+        &self,
+        row_or_cell: Either<u32, String>,
+        col_or_value: ValueInput,
+        value_or_format: Option<ValueOrFormat>,
+        format: Option<FormatOptions>,
+    ) -> Result<(), &'static str> {
+        let (row, col, value, format) = match row_or_cell {
+            Either::A(row) => {
+                let col = match col_or_value {
+                    Either5::B(col) if col >= 0.0 && col.fract() == 0.0 => col as u32,
+                    _ => return Err(coded_arg_error("`col` must be a column index")),
+                };
+                let value = match value_or_format {
+                    Some(Either6::A(s)) => Either5::A(s),
+                    Some(Either6::B(n)) => Either5::B(n),
+                    Some(Either6::C(b)) => Either5::C(b),
+                    Some(Either6::D(error)) => Either5::D(error),
+                    Some(Either6::E(runs)) => Either5::E(runs),
+                    Some(Either6::F(_)) | None => {
+                        return Err(coded_arg_error("`value` is required"))
+                    }
+                };
+                (row, col, value, format)
+            }
+            Either::B(cell) => {
+                let (row, col) = self.resolve_cell(&cell)?;
+                let format = match value_or_format {
+                    Some(Either6::F(format)) => Some(format),
+                    None => None,
+                    Some(_) => {
+                        return Err(coded_arg_error(
+                            "expected a format after the value of an A1 address",
+                        ))
+                    }
+                };
+                (row, col, col_or_value, format)
+            }
+        };
+        let value = cell_value(value).map_err(invalid_arg)?;
+        let format = format
+            .map(FormatOptions::into_format)
+            .transpose()
+            .map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.write(row, col, value)?;
+            match format {
+                Some(format) => ws.set_format(row, col, format),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Returns the value of a cell, addressed as `getCell(row, col)` or
+    /// `getCell('B3')`, or `null` if the cell is empty.
+    #[napi]
+    pub fn get_cell(
+        &self,
+        row_or_cell: Either<u32, String>,
+        col: Option<u32>,
+    ) -> Result<Option<ValueOutput>, &'static str> {
+        let (row, col) = match row_or_cell {
+            Either::A(row) => {
+                let col = col.ok_or_else(|| coded_arg_error("`col` is required"))?;
+                check_cell(row, col).map_err(coded_error)?;
+                (row, col)
+            }
+            Either::B(cell) => self.resolve_cell(&cell)?,
+        };
+        self.with_worksheet(|ws| ws.get(row, col).and_then(js_value))
+    }
+
+    /// Returns a row's values from column A up to its last non-empty cell,
+    /// with `null` for empty cells in between.
+    #[napi]
+    pub fn get_row(&self, row: u32) -> Result<Vec<Option<ValueOutput>>, &'static str> {
+        check_cell(row, 0).map_err(coded_error)?;
+        self.with_worksheet(|ws| {
+            let last_col = ws
+                .sheet_data()
+                .row(row)
+                .iter()
+                .rfind(|(_, cell)| cell.has_value())
+                .map(|&(col, _)| col);
+            last_col.map_or_else(Vec::new, |last_col| {
+                (0..=last_col)
+                    .map(|col| ws.get(row, col).and_then(js_value))
+                    .collect()
+            })
+        })
+    }
+
+    /// Returns a range's values as an array of rows, addressed as
+    /// `getRange(firstRow, firstCol, lastRow, lastCol)` or
+    /// `getRange('A1:C10')`. Whole rows and columns (`'A:C'`) stop at the
+    /// last used row or column.
+    #[napi]
+    pub fn get_range(
+        &self,
+        first_row_or_range: Either<u32, String>,
+        first_col: Option<u32>,
+        last_row: Option<u32>,
+        last_col: Option<u32>,
+    ) -> Result<Vec<Vec<Option<ValueOutput>>>, &'static str> {
+        let (first_row, first_col, last_row, last_col) = match first_row_or_range {
+            Either::A(first_row) => {
+                let (Some(first_col), Some(last_row), Some(last_col)) =
+                    (first_col, last_row, last_col)
+                else {
+                    return Err(coded_arg_error(
+                        "`firstCol`, `lastRow` and `lastCol` are required",
+                    ));
+                };
+                check_cell(first_row, first_col).map_err(coded_error)?;
+                check_cell(last_row, last_col).map_err(coded_error)?;
+                (
+                    first_row.min(last_row),
+                    first_col.min(last_col),
+                    first_row.max(last_row),
+                    first_col.max(last_col),
+                )
+            }
+            Either::B(range) => {
+                let (first_row, first_col, last_row, last_col) = self.resolve_range(&range)?;
+                let whole = last_row == MAX_ROWS - 1 || last_col == MAX_COLS - 1;
+                let used = self.with_worksheet(InnerWorksheet::dimensions)?;
+                let (_, _, used_last_row, used_last_col) = used.unwrap_or_default();
+                let last_row = if last_row == MAX_ROWS - 1 {
+                    used_last_row
+                } else {
+                    last_row
+                };
+                let last_col = if last_col == MAX_COLS - 1 {
+                    used_last_col
+                } else {
+                    last_col
+                };
+                // Whole rows or columns past the used area hold nothing.
+                if whole && (used.is_none() || last_row < first_row || last_col < first_col) {
+                    return Ok(Vec::new());
+                }
+                (first_row, first_col, last_row, last_col)
+            }
+        };
+        self.with_worksheet(|ws| {
+            (first_row..=last_row)
+                .map(|row| {
+                    (first_col..=last_col)
+                        .map(|col| ws.get(row, col).and_then(js_value))
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
+    /// The number of rows and columns from A1 to the last used cell.
+    #[napi]
+    pub fn dimensions(&self) -> Result<Dimensions, &'static str> {
+        self.with_worksheet(|ws| match ws.dimensions() {
+            Some((_, _, last_row, last_col)) => Dimensions {
+                rows: last_row + 1,
+                cols: last_col + 1,
+            },
+            None => Dimensions { rows: 0, cols: 0 },
+        })
+    }
+
+    /// The smallest range holding every non-empty cell, or `null` if the
+    /// sheet has no values.
+    #[napi]
+    pub fn used_range(&self) -> Result<Option<UsedRange>, &'static str> {
+        self.with_worksheet(|ws| {
+            ws.dimensions().map(
+                |bounds @ (first_row, first_col, last_row, last_col)| UsedRange {
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                    range: range_reference(bounds),
+                },
+            )
+        })
+    }
+
+    /// Empties a cell, addressed as `clearCell(row, col)` or
+    /// `clearCell('B3')`. The cell keeps its format unless `formats` is set.
+    #[napi]
+    pub fn clear_cell(
+        &self,
+        row_or_cell: Either<u32, String>,
+        col_or_options: Option<Either<u32, ClearOptions>>,
+        options: Option<ClearOptions>,
+    ) -> Result<(), &'static str> {
+        let ((row, col), options) = match (row_or_cell, col_or_options) {
+            (Either::A(row), Some(Either::A(col))) => ((row, col), options),
+            (Either::A(_), _) => return Err(coded_arg_error("`col` is required")),
+            (Either::B(cell), Some(Either::B(options))) => {
+                (self.resolve_cell(&cell)?, Some(options))
+            }
+            (Either::B(cell), _) => (self.resolve_cell(&cell)?, options),
+        };
+        self.clear((row, col, row, col), options)
+    }
+
+    /// Empties a range, addressed as
+    /// `clearRange(firstRow, firstCol, lastRow, lastCol)` or
+    /// `clearRange('A1:C10')`. Cells keep their formats unless `formats` is
+    /// set.
+    #[napi]
+    pub fn clear_range(
+        &self,
+        first_row_or_range: Either<u32, String>,
+        first_col_or_options: Option<Either<u32, ClearOptions>>,
+        last_row: Option<u32>,
+        last_col: Option<u32>,
+        options: Option<ClearOptions>,
+    ) -> Result<(), &'static str> {
+        let (bounds, options) = match (first_row_or_range, first_col_or_options) {
+            (Either::A(first_row), Some(Either::A(first_col))) => {
+                let (Some(last_row), Some(last_col)) = (last_row, last_col) else {
+                    return Err(coded_arg_error(
+                        "`firstCol`, `lastRow` and `lastCol` are required",
+                    ));
+                };
+                ((first_row, first_col, last_row, last_col), options)
+            }
+            (Either::A(_), _) => {
+                return Err(coded_arg_error(
+                    "`firstCol`, `lastRow` and `lastCol` are required",
+                ))
+            }
+            (Either::B(range), Some(Either::B(options))) => {
+                (self.resolve_range(&range)?, Some(options))
+            }
+            (Either::B(range), _) => (self.resolve_range(&range)?, options),
+        };
+        self.clear(bounds, options)
+    }
+
+    /// Whether no cell holds a value. Formats alone don't count.
+    #[napi]
+    pub fn is_empty(&self) -> Result<bool, &'static str> {
+        self.with_worksheet(InnerWorksheet::is_empty)
+    }
+
+    /// The cells that hold values, in row-major order.
+    #[napi]
+    pub fn cells(&self) -> Result<Vec<UsedCell>, &'static str> {
+        self.with_worksheet(|ws| {
+            ws.cells()
+                .filter_map(|((row, col), value)| {
+                    js_value(value).map(|value| UsedCell { row, col, value })
+                })
+                .collect()
+        })
+    }
+
+    /// Inserts `count` empty rows (default 1) above `row`. Cells, formats,
+    /// ranges and formulas across the workbook follow the moved rows.
+    #[napi]
+    pub fn insert_rows(&self, row: u32, count: Option<u32>) -> Result<(), &'static str> {
+        let index = self.current_index()?;
+        self.workbook
+            .borrow_mut()
+            .insert_rows(index, row, count.unwrap_or(1))
+            .map_err(coded_error)
+    }
+
+    /// Deletes `count` rows (default 1) starting at `row`. References to
+    /// deleted cells become `#REF!`.
+    #[napi]
+    pub fn delete_rows(&self, row: u32, count: Option<u32>) -> Result<(), &'static str> {
+        let index = self.current_index()?;
+        self.workbook
+            .borrow_mut()
+            .delete_rows(index, row, count.unwrap_or(1))
+            .map_err(coded_error)
+    }
+
+    #[napi]
+    pub fn insert_columns(&self, col: u32, count: Option<u32>) -> Result<(), &'static str> {
+        let index = self.current_index()?;
+        self.workbook
+            .borrow_mut()
+            .insert_cols(index, col, count.unwrap_or(1))
+            .map_err(coded_error)
+    }
+
+    #[napi]
+    pub fn delete_columns(&self, col: u32, count: Option<u32>) -> Result<(), &'static str> {
+        let index = self.current_index()?;
+        self.workbook
+            .borrow_mut()
+            .delete_cols(index, col, count.unwrap_or(1))
+            .map_err(coded_error)
+    }
+
+    /// Formats a range of cells, whether or not they hold values.
+    #[napi]
+    pub fn set_format(
+        &self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        format: FormatOptions,
+    ) -> Result<(), &'static str> {
+        let format = format.into_format().map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.set_range_format(first_row, first_col, last_row, last_col, format)
+        })
+    }
+
+    /// Protects the worksheet so that locked cells can't be edited.
+    #[napi]
+    pub fn protect(
+        &self,
+        password: Option<String>,
+        options: Option<ProtectionOptions>,
+    ) -> Result<(), &'static str> {
+        let protection = options.unwrap_or_default().into_protection(password)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.protect(protection);
+            Ok(())
+        })
+    }
+
+    #[napi]
+    pub fn add_data_validation(
+        &self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        options: DataValidationOptions,
+    ) -> Result<(), &'static str> {
+        let validation = options.into_validation().map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.add_data_validation(first_row, first_col, last_row, last_col, validation)
+        })
+    }
+
+    #[napi]
+    pub fn add_conditional_format(
+        &self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        options: ConditionalFormatOptions,
+    ) -> Result<(), &'static str> {
+        let rule = options.into_rule().map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.add_conditional_format(first_row, first_col, last_row, last_col, rule)
+        })
+    }
+
     #[napi]
-    pub fn write(&self, row: u32, col: u32, value: CellInput) -> Result<()> {
-        let cell_value = match value {
-            Either3::A(s) => CellValue::String(s),
-            Either3::B(n) => CellValue::Number(n),
-            Either3::C(b) => CellValue::Boolean(b),
+    pub fn insert_chart(&self, row: u32, col: u32, chart: &Chart) -> Result<(), &'static str> {
+        let chart = chart.inner.clone();
+        self.try_with_worksheet_mut(|ws| ws.insert_chart(row, col, chart))
+    }
+
+    /// Inserts a PNG, JPEG or GIF image from a file path or a Buffer.
+    #[napi]
+    pub fn insert_image(
+        &self,
+        row: u32,
+        col: u32,
+        source: Either<String, Buffer>,
+        options: Option<ImageOptions>,
+    ) -> Result<(), &'static str> {
+        let image = match source {
+            Either::A(path) => Image::from_path(path),
+            Either::B(buffer) => Image::from_buffer(buffer.to_vec()),
+        }
+        .map_err(coded_error)?;
+
+        let image = match options {
+            Some(options) => {
+                let mut image = image.scale(
+                    options.scale_x.unwrap_or(1.0),
+                    options.scale_y.unwrap_or(1.0),
+                );
+                image = image.offset(options.x_offset.unwrap_or(0), options.y_offset.unwrap_or(0));
+                match options.alt_text {
+                    Some(text) => image.alt_text(text),
+                    None => image,
+                }
+            }
+            None => image,
         };
+        self.try_with_worksheet_mut(|ws| ws.insert_image(row, col, image))
+    }
+
+    #[napi]
+    pub fn set_page_setup(&self, options: PageSetupOptions) -> Result<(), &'static str> {
+        let page_setup = options.into_page_setup().map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| ws.set_page_setup(page_setup))
+    }
+
+    /// Sets the page header, e.g. `setHeader('&LConfidential&RPage &P of &N')`.
+    #[napi]
+    pub fn set_header(
+        &self,
+        text: String,
+        options: Option<HeaderFooterOptions>,
+    ) -> Result<(), &'static str> {
+        let header = header_footer(text, options);
+        self.try_with_worksheet_mut(|ws| ws.set_header(header))
+    }
+
+    #[napi]
+    pub fn set_footer(
+        &self,
+        text: String,
+        options: Option<HeaderFooterOptions>,
+    ) -> Result<(), &'static str> {
+        let footer = header_footer(text, options);
+        self.try_with_worksheet_mut(|ws| ws.set_footer(footer))
+    }
+
+    /// Starts a new printed page above each of the given zero-based rows.
+    #[napi]
+    pub fn set_h_page_breaks(&self, rows: Vec<u32>) -> Result<(), &'static str> {
+        self.try_with_worksheet_mut(|ws| ws.set_h_page_breaks(&rows))
+    }
+
+    /// Starts a new printed page left of each of the given zero-based columns.
+    #[napi]
+    pub fn set_v_page_breaks(&self, cols: Vec<u32>) -> Result<(), &'static str> {
+        self.try_with_worksheet_mut(|ws| ws.set_v_page_breaks(&cols))
+    }
+
+    /// Sets whether the sheet is `visible`, `hidden` or `veryHidden`.
+    #[napi]
+    pub fn set_visibility(&self, state: String) -> Result<(), &'static str> {
+        let visibility = SheetVisibility::from_name(&state)
+            .ok_or_else(|| coded_arg_error(&format!("Unknown sheet visibility: {}", state)))?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.set_visibility(visibility);
+            Ok(())
+        })
+    }
+
+    #[napi]
+    pub fn set_tab_color(&self, color: String) -> Result<(), &'static str> {
+        let color = parse_color(&color).map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| {
+            ws.set_tab_color(color);
+            Ok(())
+        })
+    }
+
+    /// Makes this the sheet shown when the file is opened.
+    #[napi]
+    pub fn activate(&self) -> Result<(), &'static str> {
+        let index = self.current_index()?;
         self.workbook
             .borrow_mut()
-            .write(self.index, row, col, cell_value)
-            .map_err(|e| Error::from_reason(e.to_string()))
+            .set_active_sheet(index)
+            .map_err(coded_error)
+    }
+
+    /// Selects the sheet's tab together with the active sheet.
+    #[napi]
+    pub fn select(&self) -> Result<(), &'static str> {
+        self.try_with_worksheet_mut(|ws| {
+            ws.set_selected(true);
+            Ok(())
+        })
+    }
+
+    #[napi]
+    pub fn set_view(&self, options: SheetViewOptions) -> Result<(), &'static str> {
+        let view = options.into_view().map_err(invalid_arg)?;
+        self.try_with_worksheet_mut(|ws| ws.set_view(view))
+    }
+
+    #[napi(getter)]
+    pub fn name(&self) -> Result<String, &'static str> {
+        self.with_worksheet(|ws| ws.name().to_string())
     }
 
+    /// The sheet's current tab position.
     #[napi(getter)]
-    pub fn name(&self) -> Result<String> {
+    pub fn index(&self) -> Result<u32, &'static str> {
+        self.current_index().map(|index| index as u32)
+    }
+}
+
+impl Worksheet {
+    fn clear(
+        &self,
+        bounds: RangeBounds,
+        options: Option<ClearOptions>,
+    ) -> Result<(), &'static str> {
+        let formats = options.and_then(|o| o.formats).unwrap_or(false);
+        let (first_row, first_col, last_row, last_col) = bounds;
+        self.try_with_worksheet_mut(|ws| {
+            ws.clear_range(first_row, first_col, last_row, last_col)?;
+            if formats {
+                ws.clear_range_formats(first_row, first_col, last_row, last_col)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn current_index(&self) -> Result<usize, &'static str> {
+        self.workbook
+            .borrow()
+            .worksheet_index(self.id)
+            .ok_or_else(removed_sheet_error)
+    }
+
+    fn with_worksheet<T>(&self, f: impl FnOnce(&InnerWorksheet) -> T) -> Result<T, &'static str> {
         let workbook = self.workbook.borrow();
         let worksheet = workbook
-            .get_worksheet(self.index)
-            .ok_or_else(|| Error::from_reason("Worksheet not found"))?;
-        Ok(worksheet.name().to_string())
+            .worksheet_index(self.id)
+            .and_then(|index| workbook.get_worksheet(index))
+            .ok_or_else(removed_sheet_error)?;
+        Ok(f(worksheet))
     }
+
+    /// Runs `f` on this worksheet. Its errors reach JavaScript with the
+    /// library's error code.
+    fn try_with_worksheet_mut<T>(
+        &self,
+        f: impl FnOnce(&mut InnerWorksheet) -> crate::error::Result<T>,
+    ) -> Result<T, &'static str> {
+        let mut workbook = self.workbook.borrow_mut();
+        let index = workbook
+            .worksheet_index(self.id)
+            .ok_or_else(removed_sheet_error)?;
+        let worksheet = workbook
+            .get_worksheet_mut(index)
+            .ok_or(ExcelifyError::SheetNotFound(index))
+            .map_err(coded_error)?;
+        f(worksheet).map_err(coded_error)
+    }
+
+    /// Parses an A1 address such as `B3` or `'Q1 Sales'!B3`. A sheet prefix
+    /// must name this worksheet.
+    fn resolve_cell(&self, reference: &str) -> Result<(u32, u32), &'static str> {
+        let cell = CellRef::parse(reference).map_err(coded_error)?;
+        self.check_sheet_prefix(cell.sheet.as_deref(), reference)?;
+        Ok((cell.row, cell.col))
+    }
+
+    /// Parses an A1 range such as `A1:C10` or `A:C`. A sheet prefix must name
+    /// this worksheet.
+    fn resolve_range(&self, reference: &str) -> Result<RangeBounds, &'static str> {
+        let range = CellRange::parse(reference).map_err(coded_error)?;
+        self.check_sheet_prefix(range.sheet.as_deref(), reference)?;
+        Ok(range.bounds())
+    }
+
+    fn check_sheet_prefix(&self, sheet: Option<&str>, reference: &str) -> Result<(), &'static str> {
+        let Some(sheet) = sheet else {
+            return Ok(());
+        };
+        let name = self.with_worksheet(|ws| ws.name().to_lowercase())?;
+        if name != sheet.to_lowercase() {
+            return Err(coded_error(ExcelifyError::InvalidCellReference(format!(
+                "{:?} refers to another worksheet",
+                reference
+            ))));
+        }
+        Ok(())
+    }
+}
+
+fn cell_value(input: ValueInput) -> Result<CellValue> {
+    Ok(match input {
+        Either5::A(s) => CellValue::String(s),
+        Either5::B(n) => CellValue::Number(n),
+        Either5::C(b) => CellValue::Boolean(b),
+        Either5::D(error) => CellValue::Error(error.inner),
+        Either5::E(runs) => CellValue::RichString(rich_string(runs)?),
+    })
+}
+
+fn js_value(value: &CellValue) -> Option<ValueOutput> {
+    match value {
+        CellValue::String(s) => Some(Either5::A(s.clone())),
+        CellValue::RichString(rich) => Some(Either5::E(
+            rich.runs().iter().map(RichTextRun::from_run).collect(),
+        )),
+        CellValue::Number(n) => Some(Either5::B(*n)),
+        CellValue::Boolean(b) => Some(Either5::C(*b)),
+        CellValue::Error(error) => Some(Either5::D(ExcelError { inner: *error })),
+        CellValue::Empty => None,
+    }
+}
+
+/// Converts a library error into a JS error whose `code` names its kind,
+/// e.g. `ERR_ROW_OUT_OF_RANGE`.
+fn coded_error(error: ExcelifyError) -> Error<&'static str> {
+    Error::new(error.code(), error.to_string())
+}
+
+fn removed_sheet_error() -> Error<&'static str> {
+    Error::new("ERR_SHEET_NOT_FOUND", "Worksheet has been removed")
+}
+
+fn coded_arg_error(reason: &str) -> Error<&'static str> {
+    Error::new("InvalidArg", reason)
+}
+
+/// Re-codes an argument conversion error for a method that throws coded
+/// errors.
+fn invalid_arg(error: Error) -> Error<&'static str> {
+    Error::new("InvalidArg", error.reason.clone())
 }
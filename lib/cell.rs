@@ -1,35 +1,220 @@
-#[derive(Debug, Clone)]
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::rich_text::RichString;
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum CellValue {
     String(String),
+    RichString(RichString),
     Number(f64),
     Boolean(bool),
+    Error(CellError),
+    #[default]
     Empty,
 }
 
+/// The error values a cell can hold, as shown by Excel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellError {
+    /// `#NULL!`: ranges that don't intersect.
+    Null,
+    /// `#DIV/0!`
+    Div0,
+    /// `#VALUE!`: an argument of the wrong type.
+    Value,
+    /// `#REF!`: a reference to a deleted cell.
+    Ref,
+    /// `#NAME?`: an unknown function or name.
+    Name,
+    /// `#NUM!`: a number that can't be represented.
+    Num,
+    /// `#N/A`: a value that isn't available.
+    NA,
+}
+
+impl CellError {
+    pub const ALL: [CellError; 7] = [
+        CellError::Null,
+        CellError::Div0,
+        CellError::Value,
+        CellError::Ref,
+        CellError::Name,
+        CellError::Num,
+        CellError::NA,
+    ];
+
+    /// Parses the text Excel displays, e.g. `#N/A`. Case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|error| error.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CellError::Null => "#NULL!",
+            CellError::Div0 => "#DIV/0!",
+            CellError::Value => "#VALUE!",
+            CellError::Ref => "#REF!",
+            CellError::Name => "#NAME?",
+            CellError::Num => "#NUM!",
+            CellError::NA => "#N/A",
+        }
+    }
+}
+
 impl CellValue {
     pub fn to_xlsx_value(&self) -> String {
         match self {
             CellValue::String(s) => s.clone(),
-            CellValue::Number(n) => n.to_string(),
-            CellValue::Boolean(b) => if *b { "1".to_string() } else { "0".to_string() },
+            CellValue::RichString(rich) => rich.to_plain_text(),
+            CellValue::Number(n) if !n.is_finite() => CellError::Num.as_str().to_string(),
+            CellValue::Number(n) => format_number(*n),
+            CellValue::Error(e) => e.as_str().to_string(),
+            CellValue::Boolean(b) => {
+                if *b {
+                    "1".to_string()
+                } else {
+                    "0".to_string()
+                }
+            }
             CellValue::Empty => String::new(),
         }
     }
 
     pub fn xlsx_type(&self) -> Option<&'static str> {
         match self {
-            CellValue::String(_) => Some("inlineStr"),
+            CellValue::String(_) | CellValue::RichString(_) => Some("inlineStr"),
+            CellValue::Number(n) if !n.is_finite() => Some("e"),
             CellValue::Number(_) => None,
+            CellValue::Error(_) => Some("e"),
             CellValue::Boolean(_) => Some("b"),
             CellValue::Empty => None,
         }
     }
 }
 
-impl Default for CellValue {
-    fn default() -> Self {
-        CellValue::Empty
+/// What to do when a NaN or infinite number is written to a cell. Excel
+/// has no representation for these, so they can't be stored as numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Fail the write with `ExcelifyError::NonFiniteNumber`.
+    #[default]
+    Error,
+    /// Leave the cell empty.
+    Empty,
+    /// Write a `#NUM!` error cell, as Excel does for overflowing formulas.
+    NumError,
+}
+
+impl NonFinitePolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(NonFinitePolicy::Error),
+            "empty" => Some(NonFinitePolicy::Empty),
+            "numError" => Some(NonFinitePolicy::NumError),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a finite number with the fewest digits that parse back to the
+/// same `f64`. Very large and very small magnitudes use exponent notation
+/// (`1E+308`) rather than hundreds of digits. Negative zero is written as
+/// `0`, since Excel has no signed zero. Non-finite values have no
+/// representation in the file and must be rejected by the caller.
+pub fn format_number(n: f64) -> String {
+    let mut formatted = String::new();
+    write_number(&mut formatted, n).expect("writing to a String can't fail");
+    formatted
+}
+
+/// Like `format_number`, but appends to `out`, so that writing many numbers
+/// can reuse one buffer.
+pub fn write_number(out: &mut impl fmt::Write, n: f64) -> fmt::Result {
+    if n == 0.0 {
+        return out.write_str("0");
+    }
+    if (1e-7..1e21).contains(&n.abs()) {
+        return write!(out, "{}", n);
+    }
+    // Only very large and very small magnitudes get here.
+    let formatted = format!("{:E}", n);
+    match formatted.split_once('E') {
+        Some((mantissa, exponent)) if !exponent.starts_with('-') => {
+            write!(out, "{}E+{}", mantissa, exponent)
+        }
+        _ => out.write_str(&formatted),
+    }
+}
+
+/// Encodes characters that XML 1.0 can't carry as `_xHHHH_`, the escape
+/// SpreadsheetML defines for string content (ECMA-376 Part 1, 22.9.2.19).
+/// Carriage returns are escaped too, since XML parsers turn a raw `\r` into
+/// `\n`. An underscore that would otherwise start such a sequence is itself
+/// escaped as `_x005F_`, so every string survives the round trip.
+pub fn escape_xstring(text: &str) -> Cow<'_, str> {
+    let needs_escape =
+        |c: char| matches!(c, '\u{0}'..='\u{8}' | '\u{B}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}');
+    if !text.chars().any(needs_escape) && !text.contains("_x") {
+        return Cow::Borrowed(text);
     }
+
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for (i, c) in text.char_indices() {
+        if needs_escape(c) || (c == '_' && escape_sequence_at(text, i).is_some()) {
+            escaped.push_str(&format!("_x{:04X}_", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Reverses `escape_xstring`, decoding every `_xHHHH_` sequence.
+pub fn unescape_xstring(text: &str) -> Cow<'_, str> {
+    if !text.contains("_x") {
+        return Cow::Borrowed(text);
+    }
+
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("_x") {
+        unescaped.push_str(&rest[..start]);
+        match escape_sequence_at(rest, start) {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[start + 7..];
+            }
+            None => {
+                unescaped.push('_');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+/// Decodes the `_xHHHH_` sequence starting at byte `pos`, if there is one.
+fn escape_sequence_at(text: &str, pos: usize) -> Option<char> {
+    let sequence = text.get(pos..pos + 7)?.as_bytes();
+    if !sequence.starts_with(b"_x") || sequence[6] != b'_' {
+        return None;
+    }
+    let hex = std::str::from_utf8(&sequence[2..6]).ok()?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+}
+
+/// Whether a string needs `xml:space="preserve"` to keep leading or
+/// trailing whitespace, which XML consumers may otherwise trim.
+pub fn needs_space_preserve(text: &str) -> bool {
+    let is_space = |c: char| matches!(c, ' ' | '\t' | '\n' | '\r');
+    text.starts_with(is_space) || text.ends_with(is_space)
 }
 
 impl From<String> for CellValue {
@@ -62,6 +247,18 @@ impl From<i32> for CellValue {
     }
 }
 
+impl From<RichString> for CellValue {
+    fn from(rich: RichString) -> Self {
+        CellValue::RichString(rich)
+    }
+}
+
+impl From<CellError> for CellValue {
+    fn from(e: CellError) -> Self {
+        CellValue::Error(e)
+    }
+}
+
 impl From<bool> for CellValue {
     fn from(b: bool) -> Self {
         CellValue::Boolean(b)
@@ -86,6 +283,144 @@ mod tests {
         assert_eq!(cell.xlsx_type(), None);
     }
 
+    #[test]
+    fn test_error_value() {
+        let cell = CellValue::Error(CellError::Div0);
+        assert_eq!(cell.to_xlsx_value(), "#DIV/0!");
+        assert_eq!(cell.xlsx_type(), Some("e"));
+
+        for error in CellError::ALL {
+            assert_eq!(CellError::from_name(error.as_str()), Some(error));
+        }
+        assert_eq!(CellError::from_name("#n/a"), Some(CellError::NA));
+        assert_eq!(CellError::from_name("#ERROR!"), None);
+    }
+
+    #[test]
+    fn test_non_finite_value() {
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let cell = CellValue::Number(n);
+            assert_eq!(cell.to_xlsx_value(), "#NUM!");
+            assert_eq!(cell.xlsx_type(), Some("e"));
+        }
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0.0), "0");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(42.0), "42");
+        assert_eq!(format_number(-1.5), "-1.5");
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_number(123456789012345680.0), "123456789012345680");
+        assert_eq!(format_number(1e21), "1E+21");
+        assert_eq!(format_number(1e308), "1E+308");
+        assert_eq!(format_number(f64::MAX), "1.7976931348623157E+308");
+        assert_eq!(format_number(1e-7), "0.0000001");
+        assert_eq!(format_number(1.5e-8), "1.5E-8");
+        assert_eq!(format_number(f64::MIN_POSITIVE), "2.2250738585072014E-308");
+        assert_eq!(format_number(5e-324), "5E-324");
+    }
+
+    #[test]
+    fn test_write_number_appends() {
+        let mut out = String::from("A1:");
+        write_number(&mut out, -1.5).unwrap();
+        write_number(&mut out, 1e21).unwrap();
+        assert_eq!(out, "A1:-1.51E+21");
+    }
+
+    #[test]
+    fn test_format_number_round_trips() {
+        let samples = [
+            1.0 / 3.0,
+            -2.0 / 3.0,
+            std::f64::consts::PI,
+            1e15 + 0.3,
+            9007199254740993.0,
+            f64::from_bits(0x000F_FFFF_FFFF_FFFF),
+            2.225073858507201e-308,
+            1.7976931348623157e308,
+            -1e-300,
+            12345.678901234567,
+        ];
+        for n in samples {
+            let formatted = format_number(n);
+            assert_eq!(formatted.parse::<f64>().unwrap(), n, "{}", formatted);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_policy_names() {
+        assert_eq!(
+            NonFinitePolicy::from_name("numError"),
+            Some(NonFinitePolicy::NumError)
+        );
+        assert_eq!(NonFinitePolicy::from_name("zero"), None);
+    }
+
+    #[test]
+    fn test_escape_xstring() {
+        assert!(matches!(escape_xstring("plain text"), Cow::Borrowed(_)));
+        assert_eq!(escape_xstring("a\u{0}b\u{8}"), "a_x0000_b_x0008_");
+        assert_eq!(
+            escape_xstring("line\r\nnext\ttab"),
+            "line_x000D_\nnext\ttab"
+        );
+        assert_eq!(escape_xstring("\u{B}\u{C}\u{1F}"), "_x000B__x000C__x001F_");
+        assert_eq!(escape_xstring("\u{FFFE}\u{FFFF}"), "_xFFFE__xFFFF_");
+        assert_eq!(escape_xstring("_x0041_"), "_x005F_x0041_");
+        assert_eq!(
+            escape_xstring("snake_x_case _x12 _xZZZZ_"),
+            "snake_x_case _x12 _xZZZZ_"
+        );
+    }
+
+    #[test]
+    fn test_unescape_xstring() {
+        assert_eq!(unescape_xstring("_x0041__x0009_"), "A\t");
+        assert_eq!(unescape_xstring("_x005F_x0041_"), "_x0041_");
+        assert_eq!(unescape_xstring("trailing _x004"), "trailing _x004");
+        assert_eq!(unescape_xstring("_xD800_"), "_xD800_");
+    }
+
+    #[test]
+    fn test_xstring_round_trip() {
+        let adversarial = [
+            "\u{0}\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}",
+            "scraped\u{1B}[31mred\u{1B}[0m",
+            "_x0000_",
+            "__x0041__",
+            "_x005F_x0041_",
+            "_x_x0041_",
+            "_x004_x0041_",
+            "\r\n\r",
+            "\u{FFFE}mixed 😀 emoji\u{0}",
+            "ends with _x",
+            "_",
+            "",
+        ];
+        for text in adversarial {
+            let escaped = escape_xstring(text);
+            assert!(
+                !escaped
+                    .chars()
+                    .any(|c| c.is_control() && !matches!(c, '\t' | '\n')),
+                "{:?}",
+                escaped
+            );
+            assert_eq!(unescape_xstring(&escaped), text, "{:?}", escaped);
+        }
+    }
+
+    #[test]
+    fn test_needs_space_preserve() {
+        assert!(needs_space_preserve(" leading"));
+        assert!(needs_space_preserve("trailing\n"));
+        assert!(!needs_space_preserve("inner  spaces"));
+        assert!(!needs_space_preserve(""));
+    }
+
     #[test]
     fn test_boolean_value() {
         let cell_true = CellValue::Boolean(true);
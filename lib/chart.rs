@@ -0,0 +1,340 @@
+use crate::error::{ExcelifyError, Result};
+use crate::format::Color;
+
+/// Excel's default chart size in pixels.
+const DEFAULT_WIDTH: u32 = 480;
+const DEFAULT_HEIGHT: u32 = 288;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartType {
+    Column,
+    Bar,
+    Line,
+    Pie,
+    Scatter,
+    Area,
+}
+
+impl ChartType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "column" => Some(ChartType::Column),
+            "bar" => Some(ChartType::Bar),
+            "line" => Some(ChartType::Line),
+            "pie" => Some(ChartType::Pie),
+            "scatter" => Some(ChartType::Scatter),
+            "area" => Some(ChartType::Area),
+            _ => None,
+        }
+    }
+
+    pub fn has_axes(&self) -> bool {
+        !matches!(self, ChartType::Pie)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LegendPosition {
+    #[default]
+    Right,
+    Left,
+    Top,
+    Bottom,
+    None,
+}
+
+impl LegendPosition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "right" => Some(LegendPosition::Right),
+            "left" => Some(LegendPosition::Left),
+            "top" => Some(LegendPosition::Top),
+            "bottom" => Some(LegendPosition::Bottom),
+            "none" => Some(LegendPosition::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            LegendPosition::Right => Some("r"),
+            LegendPosition::Left => Some("l"),
+            LegendPosition::Top => Some("t"),
+            LegendPosition::Bottom => Some("b"),
+            LegendPosition::None => None,
+        }
+    }
+}
+
+/// A data series. Ranges are sheet-qualified references such as
+/// `Sheet1!$B$2:$B$13`; a leading `=` is accepted and ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSeries {
+    values: String,
+    categories: Option<String>,
+    name: Option<String>,
+    color: Option<Color>,
+}
+
+impl ChartSeries {
+    pub fn new(values: impl Into<String>) -> Self {
+        Self {
+            values: values.into(),
+            categories: None,
+            name: None,
+            color: None,
+        }
+    }
+
+    pub fn categories(mut self, range: impl Into<String>) -> Self {
+        self.categories = Some(range.into());
+        self
+    }
+
+    /// Sets the series name shown in the legend. Names starting with `=` are
+    /// treated as a reference to the cell holding the name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn get_values(&self) -> &str {
+        strip_formula_prefix(&self.values)
+    }
+
+    pub fn get_categories(&self) -> Option<&str> {
+        self.categories.as_deref().map(strip_formula_prefix)
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Replaces the series' ranges and its name reference, if any.
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        self.values = rewrite(&self.values);
+        if let Some(categories) = &mut self.categories {
+            *categories = rewrite(categories);
+        }
+        if let Some(name) = self.name.as_mut().filter(|name| name.starts_with('=')) {
+            *name = rewrite(name);
+        }
+    }
+}
+
+fn strip_formula_prefix(formula: &str) -> &str {
+    formula.strip_prefix('=').unwrap_or(formula)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartAxis {
+    title: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    major_gridlines: bool,
+}
+
+impl ChartAxis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn major_gridlines(mut self, show: bool) -> Self {
+        self.major_gridlines = show;
+        self
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn get_min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn get_max(&self) -> Option<f64> {
+        self.max
+    }
+
+    pub fn has_major_gridlines(&self) -> bool {
+        self.major_gridlines
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    chart_type: ChartType,
+    title: Option<String>,
+    series: Vec<ChartSeries>,
+    x_axis: ChartAxis,
+    y_axis: ChartAxis,
+    legend: LegendPosition,
+    width: u32,
+    height: u32,
+}
+
+impl Chart {
+    pub fn new(chart_type: ChartType) -> Self {
+        Self {
+            chart_type,
+            title: None,
+            series: Vec::new(),
+            x_axis: ChartAxis::new(),
+            y_axis: ChartAxis::new().major_gridlines(true),
+            legend: LegendPosition::default(),
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+        }
+    }
+
+    pub fn add_series(mut self, series: ChartSeries) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// The category axis, or the horizontal value axis of a scatter chart.
+    pub fn x_axis(mut self, axis: ChartAxis) -> Self {
+        self.x_axis = axis;
+        self
+    }
+
+    pub fn y_axis(mut self, axis: ChartAxis) -> Self {
+        self.y_axis = axis;
+        self
+    }
+
+    pub fn legend(mut self, position: LegendPosition) -> Self {
+        self.legend = position;
+        self
+    }
+
+    /// Sets the chart size in pixels.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn chart_type(&self) -> ChartType {
+        self.chart_type
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn series(&self) -> &[ChartSeries] {
+        &self.series
+    }
+
+    pub fn get_x_axis(&self) -> &ChartAxis {
+        &self.x_axis
+    }
+
+    pub fn get_y_axis(&self) -> &ChartAxis {
+        &self.y_axis
+    }
+
+    pub fn get_legend(&self) -> LegendPosition {
+        self.legend
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        for series in &mut self.series {
+            series.rewrite_formulas(rewrite);
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.series.is_empty() {
+            return Err(invalid("chart must have at least one series"));
+        }
+        if self.series.iter().any(|s| s.get_values().trim().is_empty()) {
+            return Err(invalid("series values range must not be empty"));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(invalid("chart size must be greater than zero"));
+        }
+        for axis in [&self.x_axis, &self.y_axis] {
+            if let (Some(min), Some(max)) = (axis.min, axis.max) {
+                if min >= max {
+                    return Err(invalid(format!(
+                        "axis minimum {} must be less than maximum {}",
+                        min, max
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> ExcelifyError {
+    ExcelifyError::InvalidChart(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_ranges() {
+        let series = ChartSeries::new("=Sheet1!$B$2:$B$5").categories("Sheet1!$A$2:$A$5");
+        assert_eq!(series.get_values(), "Sheet1!$B$2:$B$5");
+        assert_eq!(series.get_categories(), Some("Sheet1!$A$2:$A$5"));
+    }
+
+    #[test]
+    fn test_chart_defaults() {
+        let chart = Chart::new(ChartType::Column);
+        assert_eq!(chart.get_size(), (480, 288));
+        assert_eq!(chart.get_legend(), LegendPosition::Right);
+        assert!(chart.get_y_axis().has_major_gridlines());
+        assert!(!ChartType::Pie.has_axes());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Chart::new(ChartType::Line).validate().is_err());
+
+        let chart = Chart::new(ChartType::Line).add_series(ChartSeries::new("Sheet1!$B$2:$B$5"));
+        assert!(chart.validate().is_ok());
+
+        let inverted = chart.clone().y_axis(ChartAxis::new().min(10.0).max(0.0));
+        assert!(inverted.validate().is_err());
+        assert!(chart.size(0, 100).validate().is_err());
+    }
+}
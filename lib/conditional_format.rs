@@ -0,0 +1,303 @@
+use crate::error::{ExcelifyError, Result};
+use crate::format::{Color, Format};
+
+/// Comparison for cell-value rules. Operands are formulas, so text must be
+/// quoted (`"\"Closed\""`) while numbers and references are written as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalCriteria {
+    Between(String, String),
+    NotBetween(String, String),
+    EqualTo(String),
+    NotEqualTo(String),
+    GreaterThan(String),
+    LessThan(String),
+    GreaterThanOrEqualTo(String),
+    LessThanOrEqualTo(String),
+}
+
+impl ConditionalCriteria {
+    pub fn operator(&self) -> &'static str {
+        match self {
+            ConditionalCriteria::Between(_, _) => "between",
+            ConditionalCriteria::NotBetween(_, _) => "notBetween",
+            ConditionalCriteria::EqualTo(_) => "equal",
+            ConditionalCriteria::NotEqualTo(_) => "notEqual",
+            ConditionalCriteria::GreaterThan(_) => "greaterThan",
+            ConditionalCriteria::LessThan(_) => "lessThan",
+            ConditionalCriteria::GreaterThanOrEqualTo(_) => "greaterThanOrEqual",
+            ConditionalCriteria::LessThanOrEqualTo(_) => "lessThanOrEqual",
+        }
+    }
+
+    pub fn formulas(&self) -> Vec<&str> {
+        match self {
+            ConditionalCriteria::Between(min, max) | ConditionalCriteria::NotBetween(min, max) => {
+                vec![strip_formula_prefix(min), strip_formula_prefix(max)]
+            }
+            ConditionalCriteria::EqualTo(v)
+            | ConditionalCriteria::NotEqualTo(v)
+            | ConditionalCriteria::GreaterThan(v)
+            | ConditionalCriteria::LessThan(v)
+            | ConditionalCriteria::GreaterThanOrEqualTo(v)
+            | ConditionalCriteria::LessThanOrEqualTo(v) => vec![strip_formula_prefix(v)],
+        }
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            ConditionalCriteria::Between(min, max) | ConditionalCriteria::NotBetween(min, max) => {
+                vec![min, max]
+            }
+            ConditionalCriteria::EqualTo(v)
+            | ConditionalCriteria::NotEqualTo(v)
+            | ConditionalCriteria::GreaterThan(v)
+            | ConditionalCriteria::LessThan(v)
+            | ConditionalCriteria::GreaterThanOrEqualTo(v)
+            | ConditionalCriteria::LessThanOrEqualTo(v) => vec![v],
+        }
+    }
+}
+
+fn strip_formula_prefix(formula: &str) -> &str {
+    formula.strip_prefix('=').unwrap_or(formula)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSetStyle {
+    ThreeArrows,
+    ThreeArrowsGray,
+    ThreeFlags,
+    ThreeTrafficLights,
+    ThreeTrafficLightsRimmed,
+    ThreeSigns,
+    ThreeSymbols,
+    ThreeSymbolsUncircled,
+    FourArrows,
+    FourArrowsGray,
+    FourRedToBlack,
+    FourRatings,
+    FourTrafficLights,
+    FiveArrows,
+    FiveArrowsGray,
+    FiveRatings,
+    FiveQuarters,
+}
+
+impl IconSetStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IconSetStyle::ThreeArrows => "3Arrows",
+            IconSetStyle::ThreeArrowsGray => "3ArrowsGray",
+            IconSetStyle::ThreeFlags => "3Flags",
+            IconSetStyle::ThreeTrafficLights => "3TrafficLights1",
+            IconSetStyle::ThreeTrafficLightsRimmed => "3TrafficLights2",
+            IconSetStyle::ThreeSigns => "3Signs",
+            IconSetStyle::ThreeSymbols => "3Symbols",
+            IconSetStyle::ThreeSymbolsUncircled => "3Symbols2",
+            IconSetStyle::FourArrows => "4Arrows",
+            IconSetStyle::FourArrowsGray => "4ArrowsGray",
+            IconSetStyle::FourRedToBlack => "4RedToBlack",
+            IconSetStyle::FourRatings => "4Rating",
+            IconSetStyle::FourTrafficLights => "4TrafficLights",
+            IconSetStyle::FiveArrows => "5Arrows",
+            IconSetStyle::FiveArrowsGray => "5ArrowsGray",
+            IconSetStyle::FiveRatings => "5Rating",
+            IconSetStyle::FiveQuarters => "5Quarters",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL_ICON_SETS
+            .iter()
+            .copied()
+            .find(|style| style.as_str() == name)
+    }
+
+    pub fn icon_count(&self) -> u32 {
+        // The leading digit of the OOXML name is the number of icons.
+        u32::from(self.as_str().as_bytes()[0] - b'0')
+    }
+}
+
+const ALL_ICON_SETS: [IconSetStyle; 17] = [
+    IconSetStyle::ThreeArrows,
+    IconSetStyle::ThreeArrowsGray,
+    IconSetStyle::ThreeFlags,
+    IconSetStyle::ThreeTrafficLights,
+    IconSetStyle::ThreeTrafficLightsRimmed,
+    IconSetStyle::ThreeSigns,
+    IconSetStyle::ThreeSymbols,
+    IconSetStyle::ThreeSymbolsUncircled,
+    IconSetStyle::FourArrows,
+    IconSetStyle::FourArrowsGray,
+    IconSetStyle::FourRedToBlack,
+    IconSetStyle::FourRatings,
+    IconSetStyle::FourTrafficLights,
+    IconSetStyle::FiveArrows,
+    IconSetStyle::FiveArrowsGray,
+    IconSetStyle::FiveRatings,
+    IconSetStyle::FiveQuarters,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalFormat {
+    CellValue {
+        criteria: ConditionalCriteria,
+        format: Format,
+    },
+    Formula {
+        formula: String,
+        format: Format,
+    },
+    /// Highlights the top (or bottom) `rank` values, or `rank` percent of
+    /// values when `percent` is set.
+    TopBottom {
+        rank: u32,
+        percent: bool,
+        bottom: bool,
+        format: Format,
+    },
+    Average {
+        below: bool,
+        format: Format,
+    },
+    Duplicates {
+        unique: bool,
+        format: Format,
+    },
+    ColorScale2 {
+        min_color: Color,
+        max_color: Color,
+    },
+    ColorScale3 {
+        min_color: Color,
+        mid_color: Color,
+        max_color: Color,
+    },
+    DataBar {
+        color: Color,
+    },
+    IconSet {
+        style: IconSetStyle,
+        reverse: bool,
+        show_value: bool,
+    },
+}
+
+impl ConditionalFormat {
+    pub fn rule_type(&self) -> &'static str {
+        match self {
+            ConditionalFormat::CellValue { .. } => "cellIs",
+            ConditionalFormat::Formula { .. } => "expression",
+            ConditionalFormat::TopBottom { .. } => "top10",
+            ConditionalFormat::Average { .. } => "aboveAverage",
+            ConditionalFormat::Duplicates { unique: false, .. } => "duplicateValues",
+            ConditionalFormat::Duplicates { unique: true, .. } => "uniqueValues",
+            ConditionalFormat::ColorScale2 { .. } | ConditionalFormat::ColorScale3 { .. } => {
+                "colorScale"
+            }
+            ConditionalFormat::DataBar { .. } => "dataBar",
+            ConditionalFormat::IconSet { .. } => "iconSet",
+        }
+    }
+
+    /// The differential format applied when the rule matches. Scales, bars
+    /// and icon sets render their own visuals and have none.
+    pub fn format(&self) -> Option<&Format> {
+        match self {
+            ConditionalFormat::CellValue { format, .. }
+            | ConditionalFormat::Formula { format, .. }
+            | ConditionalFormat::TopBottom { format, .. }
+            | ConditionalFormat::Average { format, .. }
+            | ConditionalFormat::Duplicates { format, .. } => Some(format),
+            _ => None,
+        }
+    }
+
+    /// Replaces the rule's formulas, e.g. to move references after rows
+    /// or columns were inserted.
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        match self {
+            ConditionalFormat::CellValue { criteria, .. } => {
+                for operand in criteria.operands_mut() {
+                    *operand = rewrite(operand);
+                }
+            }
+            ConditionalFormat::Formula { formula, .. } => *formula = rewrite(formula),
+            _ => {}
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ConditionalFormat::TopBottom { rank, percent, .. } => {
+                let max = if *percent { 100 } else { 1000 };
+                if *rank == 0 || *rank > max {
+                    return Err(ExcelifyError::InvalidConditionalFormat(format!(
+                        "rank must be between 1 and {}",
+                        max
+                    )));
+                }
+            }
+            ConditionalFormat::Formula { formula, .. } if formula.trim().is_empty() => {
+                return Err(ExcelifyError::InvalidConditionalFormat(
+                    "formula must not be empty".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_criteria_formulas() {
+        let criteria = ConditionalCriteria::Between("=1".into(), "10".into());
+        assert_eq!(criteria.operator(), "between");
+        assert_eq!(criteria.formulas(), vec!["1", "10"]);
+    }
+
+    #[test]
+    fn test_icon_set_style() {
+        assert_eq!(
+            IconSetStyle::from_name("3Arrows"),
+            Some(IconSetStyle::ThreeArrows)
+        );
+        assert_eq!(IconSetStyle::from_name("6Stars"), None);
+        assert_eq!(IconSetStyle::FourRatings.icon_count(), 4);
+        assert_eq!(IconSetStyle::FiveQuarters.icon_count(), 5);
+    }
+
+    #[test]
+    fn test_rule_type_and_format() {
+        let dup = ConditionalFormat::Duplicates {
+            unique: true,
+            format: Format::new().bold(),
+        };
+        assert_eq!(dup.rule_type(), "uniqueValues");
+        assert!(dup.format().is_some());
+
+        let bar = ConditionalFormat::DataBar {
+            color: Color::rgb(0x638EC6),
+        };
+        assert_eq!(bar.rule_type(), "dataBar");
+        assert!(bar.format().is_none());
+    }
+
+    #[test]
+    fn test_validate_rank() {
+        let rule = |rank, percent| ConditionalFormat::TopBottom {
+            rank,
+            percent,
+            bottom: false,
+            format: Format::new(),
+        };
+        assert!(rule(10, false).validate().is_ok());
+        assert!(rule(0, false).validate().is_err());
+        assert!(rule(101, true).validate().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::{ExcelifyError, Result};
+use crate::reference::CellRef;
+
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Prefix Excel reserves for built-in names such as `_xlnm.Print_Area`.
+pub const BUILTIN_PREFIX: &str = "_xlnm.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinedName {
+    name: String,
+    refers_to: String,
+    sheet_scope: Option<usize>,
+    hidden: bool,
+}
+
+impl DefinedName {
+    /// Creates a workbook-scoped name. A leading `=` on `refers_to` is
+    /// accepted and ignored.
+    pub fn new(name: impl Into<String>, refers_to: impl Into<String>) -> Self {
+        let refers_to = refers_to.into();
+        let refers_to = match refers_to.strip_prefix('=') {
+            Some(stripped) => stripped.to_string(),
+            None => refers_to,
+        };
+        Self {
+            name: name.into(),
+            refers_to,
+            sheet_scope: None,
+            hidden: false,
+        }
+    }
+
+    /// Limits the name to the worksheet at `sheet_index`.
+    pub fn scope(mut self, sheet_index: usize) -> Self {
+        self.sheet_scope = Some(sheet_index);
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn refers_to(&self) -> &str {
+        &self.refers_to
+    }
+
+    pub fn sheet_scope(&self) -> Option<usize> {
+        self.sheet_scope
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Whether two names would clash: Excel compares names
+    /// case-insensitively within the same scope.
+    pub fn conflicts_with(&self, other: &DefinedName) -> bool {
+        self.sheet_scope == other.sheet_scope && self.name.eq_ignore_ascii_case(&other.name)
+    }
+
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        self.refers_to = rewrite(&self.refers_to);
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        if self.refers_to.trim().is_empty() {
+            return Err(ExcelifyError::InvalidDefinedName(format!(
+                "{}: reference must not be empty",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Checks a user-supplied name against Excel's naming rules.
+pub fn validate_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        Err(ExcelifyError::InvalidDefinedName(format!(
+            "{}: {}",
+            name, reason
+        )))
+    };
+
+    let mut chars = name.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return invalid("name must not be empty"),
+    };
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return invalid("name exceeds 255 characters");
+    }
+    if is_builtin(name) {
+        return invalid("the _xlnm. prefix is reserved for built-in names");
+    }
+    if !(first.is_alphabetic() || first == '_' || first == '\\') {
+        return invalid("name must start with a letter, underscore or backslash");
+    }
+    if !chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '\\')) {
+        return invalid(
+            "name may only contain letters, digits, underscores, periods and backslashes",
+        );
+    }
+    if CellRef::parse(name).is_ok() || is_r1c1_reference(name) {
+        return invalid("name must not look like a cell reference");
+    }
+    Ok(())
+}
+
+pub fn is_builtin(name: &str) -> bool {
+    name.get(..BUILTIN_PREFIX.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(BUILTIN_PREFIX))
+}
+
+/// Matches `R1C1`-style references such as `R2`, `C3`, `RC` or `R1C1`.
+pub fn is_r1c1_reference(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    let skip_digits = |mut pos: usize| {
+        while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+            pos += 1;
+        }
+        pos
+    };
+
+    let mut pos = 0;
+    let mut matched = false;
+    if bytes.first().map(u8::to_ascii_uppercase) == Some(b'R') {
+        pos = skip_digits(1);
+        matched = true;
+    }
+    if bytes.get(pos).map(u8::to_ascii_uppercase) == Some(b'C') {
+        pos = skip_digits(pos + 1);
+        matched = true;
+    }
+    matched && pos == bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_names() {
+        for name in [
+            "Sales",
+            "_total",
+            "\\path",
+            "Tax.Rate",
+            "Region_2024",
+            "ABCD1",
+            "Ümsatz",
+            "Rate",
+            "CR",
+        ] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        for name in [
+            "",
+            "1st",
+            "has space",
+            "a-b",
+            "R",
+            "c",
+            "A1",
+            "xfd1048576",
+            "R1C1",
+            "R2",
+            "C10",
+            "rc",
+            "_xlnm.Print_Area",
+        ] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+        assert!(validate_name(&"n".repeat(256)).is_err());
+        assert!(validate_name("aÜÜÜ").is_ok());
+    }
+
+    #[test]
+    fn test_refers_to_prefix() {
+        let name = DefinedName::new("Sales", "=Sheet1!$A$1:$A$10");
+        assert_eq!(name.refers_to(), "Sheet1!$A$1:$A$10");
+        assert!(DefinedName::new("Empty", "=").validate().is_err());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let global = DefinedName::new("Sales", "Sheet1!$A$1");
+        assert!(global.conflicts_with(&DefinedName::new("SALES", "Sheet1!$B$1")));
+        assert!(!global.conflicts_with(&DefinedName::new("Sales", "Sheet1!$B$1").scope(0)));
+    }
+}
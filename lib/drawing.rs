@@ -0,0 +1,86 @@
+/// Column width and row height Excel uses when none are set, in pixels.
+pub const DEFAULT_COL_WIDTH_PIXELS: u32 = 64;
+pub const DEFAULT_ROW_HEIGHT_PIXELS: u32 = 20;
+
+/// The same default column width as `<col width>` stores it, in characters
+/// of the 11pt Calibri default font. A `<col>` element without a width
+/// would hide its columns.
+pub const DEFAULT_COL_WIDTH_CHARS: f64 = 9.140625;
+
+/// DrawingML measures positions in English Metric Units.
+pub const EMU_PER_PIXEL: u64 = 9525;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchorPoint {
+    pub row: u32,
+    pub col: u32,
+    pub row_offset: u64,
+    pub col_offset: u64,
+}
+
+/// Where an object sits on the sheet, both as cell-relative corners for the
+/// two-cell anchor and as absolute EMU values for the shape transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Anchor {
+    pub from: AnchorPoint,
+    pub to: AnchorPoint,
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Anchor {
+    /// Anchors an object of `width` x `height` pixels whose top-left corner
+    /// is `x_offset`/`y_offset` pixels into the cell at `row`/`col`.
+    pub fn new(row: u32, col: u32, x_offset: u32, y_offset: u32, width: u32, height: u32) -> Self {
+        let left = u64::from(col) * u64::from(DEFAULT_COL_WIDTH_PIXELS) + u64::from(x_offset);
+        let top = u64::from(row) * u64::from(DEFAULT_ROW_HEIGHT_PIXELS) + u64::from(y_offset);
+
+        Self {
+            from: point_at(left, top),
+            to: point_at(left + u64::from(width), top + u64::from(height)),
+            x: left * EMU_PER_PIXEL,
+            y: top * EMU_PER_PIXEL,
+            width: u64::from(width) * EMU_PER_PIXEL,
+            height: u64::from(height) * EMU_PER_PIXEL,
+        }
+    }
+}
+
+fn point_at(left: u64, top: u64) -> AnchorPoint {
+    let col_width = u64::from(DEFAULT_COL_WIDTH_PIXELS);
+    let row_height = u64::from(DEFAULT_ROW_HEIGHT_PIXELS);
+    AnchorPoint {
+        row: (top / row_height) as u32,
+        col: (left / col_width) as u32,
+        row_offset: (top % row_height) * EMU_PER_PIXEL,
+        col_offset: (left % col_width) * EMU_PER_PIXEL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_at_cell_origin() {
+        let anchor = Anchor::new(2, 1, 0, 0, 128, 40);
+        assert_eq!(anchor.from, point_at(64, 40));
+        assert_eq!(anchor.from.row, 2);
+        assert_eq!(anchor.from.col, 1);
+        assert_eq!(anchor.to.row, 4);
+        assert_eq!(anchor.to.col, 3);
+        assert_eq!(anchor.width, 128 * EMU_PER_PIXEL);
+    }
+
+    #[test]
+    fn test_anchor_with_offsets() {
+        let anchor = Anchor::new(0, 0, 70, 5, 10, 10);
+        assert_eq!(anchor.from.col, 1);
+        assert_eq!(anchor.from.col_offset, 6 * EMU_PER_PIXEL);
+        assert_eq!(anchor.from.row_offset, 5 * EMU_PER_PIXEL);
+        assert_eq!(anchor.to.col_offset, 16 * EMU_PER_PIXEL);
+        assert_eq!(anchor.x, 70 * EMU_PER_PIXEL);
+    }
+}
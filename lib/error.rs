@@ -14,8 +14,96 @@ pub enum ExcelifyError {
     #[error("XML error: {0}")]
     Xml(#[from] quick_xml::Error),
 
+    #[error("Invalid sheet name: {0}")]
+    InvalidSheetName(String),
+
     #[error("Invalid cell reference: {0}")]
     InvalidCellReference(String),
+
+    #[error("Invalid defined name: {0}")]
+    InvalidDefinedName(String),
+
+    #[error("Invalid data validation: {0}")]
+    InvalidDataValidation(String),
+
+    #[error("Invalid conditional format: {0}")]
+    InvalidConditionalFormat(String),
+
+    #[error("Invalid chart: {0}")]
+    InvalidChart(String),
+
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+
+    #[error("Invalid page setup: {0}")]
+    InvalidPageSetup(String),
+
+    #[error("Invalid header or footer: {0}")]
+    InvalidHeaderFooter(String),
+
+    #[error("Invalid protection: {0}")]
+    InvalidProtection(String),
+
+    #[error("Invalid document property: {0}")]
+    InvalidProperty(String),
+
+    #[error("Invalid sheet view: {0}")]
+    InvalidSheetView(String),
+
+    #[error("Invalid color: {0}")]
+    InvalidColor(String),
+
+    #[error("Invalid save options: {0}")]
+    InvalidSaveOptions(String),
+
+    #[error("Row {0} is out of range; Excel supports rows 0 to 1048575")]
+    RowOutOfRange(u32),
+
+    #[error("Column {0} is out of range; Excel supports columns 0 to 16383")]
+    ColumnOutOfRange(u32),
+
+    #[error("String of {0} characters exceeds Excel's limit of 32767")]
+    StringTooLong(usize),
+
+    #[error("Cannot write {0}: Excel cells can't hold NaN or infinite numbers")]
+    NonFiniteNumber(f64),
+
+    #[error(
+        "Range of {0} cells is too large to format cell by cell; format whole columns instead"
+    )]
+    RangeTooLarge(u64),
+}
+
+impl ExcelifyError {
+    /// A stable identifier for the kind of error, exposed to JavaScript as
+    /// `error.code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExcelifyError::SheetNotFound(_) => "ERR_SHEET_NOT_FOUND",
+            ExcelifyError::Io(_) => "ERR_IO",
+            ExcelifyError::Zip(_) => "ERR_ZIP",
+            ExcelifyError::Xml(_) => "ERR_XML",
+            ExcelifyError::InvalidSheetName(_) => "ERR_INVALID_SHEET_NAME",
+            ExcelifyError::InvalidCellReference(_) => "ERR_INVALID_CELL_REFERENCE",
+            ExcelifyError::InvalidDefinedName(_) => "ERR_INVALID_DEFINED_NAME",
+            ExcelifyError::InvalidDataValidation(_) => "ERR_INVALID_DATA_VALIDATION",
+            ExcelifyError::InvalidConditionalFormat(_) => "ERR_INVALID_CONDITIONAL_FORMAT",
+            ExcelifyError::InvalidChart(_) => "ERR_INVALID_CHART",
+            ExcelifyError::InvalidImage(_) => "ERR_INVALID_IMAGE",
+            ExcelifyError::InvalidPageSetup(_) => "ERR_INVALID_PAGE_SETUP",
+            ExcelifyError::InvalidHeaderFooter(_) => "ERR_INVALID_HEADER_FOOTER",
+            ExcelifyError::InvalidProtection(_) => "ERR_INVALID_PROTECTION",
+            ExcelifyError::InvalidProperty(_) => "ERR_INVALID_PROPERTY",
+            ExcelifyError::InvalidSheetView(_) => "ERR_INVALID_SHEET_VIEW",
+            ExcelifyError::InvalidColor(_) => "ERR_INVALID_COLOR",
+            ExcelifyError::InvalidSaveOptions(_) => "ERR_INVALID_SAVE_OPTIONS",
+            ExcelifyError::RowOutOfRange(_) => "ERR_ROW_OUT_OF_RANGE",
+            ExcelifyError::ColumnOutOfRange(_) => "ERR_COLUMN_OUT_OF_RANGE",
+            ExcelifyError::StringTooLong(_) => "ERR_STRING_TOO_LONG",
+            ExcelifyError::NonFiniteNumber(_) => "ERR_NON_FINITE_NUMBER",
+            ExcelifyError::RangeTooLarge(_) => "ERR_RANGE_TOO_LARGE",
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ExcelifyError>;
@@ -0,0 +1,218 @@
+use crate::error::{ExcelifyError, Result};
+
+/// An opaque RGB color, written to XML as `FFRRGGBB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color(u32);
+
+impl Color {
+    pub const BLACK: Color = Color(0x000000);
+    pub const WHITE: Color = Color(0xFFFFFF);
+
+    pub fn rgb(rgb: u32) -> Self {
+        Color(rgb & 0xFF_FFFF)
+    }
+
+    /// Parses `#RRGGBB`, `RRGGBB` or `AARRGGBB`. The alpha channel is ignored
+    /// because Excel only renders opaque colors.
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ExcelifyError::InvalidColor(hex.to_string()));
+        }
+        let digits = match digits.len() {
+            6 => digits,
+            8 => &digits[2..],
+            _ => return Err(ExcelifyError::InvalidColor(hex.to_string())),
+        };
+        u32::from_str_radix(digits, 16)
+            .map(Color)
+            .map_err(|_| ExcelifyError::InvalidColor(hex.to_string()))
+    }
+
+    pub fn to_rgb_hex(&self) -> String {
+        format!("{:06X}", self.0)
+    }
+
+    pub fn to_argb_hex(&self) -> String {
+        format!("FF{:06X}", self.0)
+    }
+}
+
+/// Font, fill, border, number format and protection properties applied to
+/// cells or conditional format rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Format {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikeout: bool,
+    font_color: Option<Color>,
+    fill_color: Option<Color>,
+    border_color: Option<Color>,
+    num_format: Option<String>,
+    unlocked: bool,
+    hidden: bool,
+}
+
+impl Format {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikeout(mut self) -> Self {
+        self.strikeout = true;
+        self
+    }
+
+    pub fn font_color(mut self, color: Color) -> Self {
+        self.font_color = Some(color);
+        self
+    }
+
+    pub fn fill_color(mut self, color: Color) -> Self {
+        self.fill_color = Some(color);
+        self
+    }
+
+    /// Draws a thin border of the given color on all four sides.
+    pub fn border_color(mut self, color: Color) -> Self {
+        self.border_color = Some(color);
+        self
+    }
+
+    pub fn num_format(mut self, code: impl Into<String>) -> Self {
+        self.num_format = Some(code.into());
+        self
+    }
+
+    /// Whether the cell can be edited once the worksheet is protected.
+    /// Cells are locked by default.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.unlocked = !locked;
+        self
+    }
+
+    /// Hides the cell's formula in the formula bar once the worksheet is
+    /// protected.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.italic
+    }
+
+    pub fn is_underline(&self) -> bool {
+        self.underline
+    }
+
+    pub fn is_strikeout(&self) -> bool {
+        self.strikeout
+    }
+
+    pub fn get_font_color(&self) -> Option<Color> {
+        self.font_color
+    }
+
+    pub fn get_fill_color(&self) -> Option<Color> {
+        self.fill_color
+    }
+
+    pub fn get_border_color(&self) -> Option<Color> {
+        self.border_color
+    }
+
+    pub fn get_num_format(&self) -> Option<&str> {
+        self.num_format.as_deref()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        !self.unlocked
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn has_protection(&self) -> bool {
+        self.unlocked || self.hidden
+    }
+
+    pub fn has_font(&self) -> bool {
+        self.bold || self.italic || self.underline || self.strikeout || self.font_color.is_some()
+    }
+
+    /// A copy holding only the font properties.
+    pub fn font(&self) -> Format {
+        Format {
+            bold: self.bold,
+            italic: self.italic,
+            underline: self.underline,
+            strikeout: self.strikeout,
+            font_color: self.font_color,
+            ..Format::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_from_hex() {
+        assert_eq!(Color::from_hex("#FF0000").unwrap(), Color::rgb(0xFF0000));
+        assert_eq!(Color::from_hex("00ff00").unwrap().to_rgb_hex(), "00FF00");
+        assert_eq!(
+            Color::from_hex("80123456").unwrap().to_argb_hex(),
+            "FF123456"
+        );
+        assert!(Color::from_hex("#FFF").is_err());
+        assert!(Color::from_hex("GGGGGG").is_err());
+        assert!(Color::from_hex("+12345").is_err());
+        assert!(Color::from_hex("aé12345").is_err());
+    }
+
+    #[test]
+    fn test_format_builder() {
+        let format = Format::new()
+            .bold()
+            .font_color(Color::rgb(0x9C0006))
+            .fill_color(Color::rgb(0xFFC7CE));
+        assert!(format.is_bold());
+        assert!(format.has_font());
+        assert_eq!(format.get_fill_color(), Some(Color::rgb(0xFFC7CE)));
+        assert!(!Format::new().fill_color(Color::WHITE).has_font());
+    }
+
+    #[test]
+    fn test_format_protection() {
+        assert!(Format::new().is_locked());
+        assert!(!Format::new().has_protection());
+
+        let input = Format::new().locked(false);
+        assert!(!input.is_locked());
+        assert!(input.has_protection());
+        assert!(Format::new().hidden(true).has_protection());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::{ExcelifyError, Result};
+
+/// Excel rejects header and footer strings longer than this, control codes
+/// included.
+const MAX_LENGTH: usize = 255;
+
+/// Text for a page header or footer, written with Excel's control codes:
+/// `&L`, `&C` and `&R` start the left, center and right sections, `&P` is the
+/// page number, `&N` the page count, `&D`/`&T` the date and time, `&F` the
+/// file name and `&A` the sheet name. A literal ampersand is written `&&`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderFooter {
+    text: String,
+    first_page: Option<String>,
+    even_pages: Option<String>,
+}
+
+impl HeaderFooter {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            first_page: None,
+            even_pages: None,
+        }
+    }
+
+    /// Uses different text on the first printed page.
+    pub fn first_page(mut self, text: impl Into<String>) -> Self {
+        self.first_page = Some(text.into());
+        self
+    }
+
+    /// Uses different text on even pages; `text` is then used on odd pages.
+    pub fn even_pages(mut self, text: impl Into<String>) -> Self {
+        self.even_pages = Some(text.into());
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_first_page(&self) -> Option<&str> {
+        self.first_page.as_deref()
+    }
+
+    pub fn get_even_pages(&self) -> Option<&str> {
+        self.even_pages.as_deref()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate_text(&self.text)?;
+        for text in [&self.first_page, &self.even_pages].into_iter().flatten() {
+            validate_text(text)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_text(text: &str) -> Result<()> {
+    let invalid = |reason: String| Err(ExcelifyError::InvalidHeaderFooter(reason));
+
+    if text.chars().count() > MAX_LENGTH {
+        return invalid(format!("text exceeds {} characters", MAX_LENGTH));
+    }
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            continue;
+        }
+        match chars.next() {
+            // Section, field and formatting codes.
+            Some(
+                'L' | 'C' | 'R' | 'P' | 'N' | 'D' | 'T' | 'F' | 'A' | 'Z' | 'B' | 'I' | 'U' | 'E'
+                | 'S' | 'X' | 'Y' | 'O' | 'H' | '&',
+            ) => {}
+            Some('G') => return invalid("images in headers and footers are not supported".into()),
+            // Font color, e.g. &KFF0000.
+            Some('K') => {
+                let color: String = chars.by_ref().take(6).collect();
+                if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return invalid(format!("invalid font color code &K{}", color));
+                }
+            }
+            // Font name and style, e.g. &"Arial,Bold".
+            Some('"') => {
+                if !chars.by_ref().any(|c| c == '"') {
+                    return invalid("unterminated font name".into());
+                }
+            }
+            // Font size in points, e.g. &14.
+            Some(d) if d.is_ascii_digit() => {
+                while chars.next_if(|c| c.is_ascii_digit()).is_some() {}
+            }
+            Some(other) => return invalid(format!("unknown control code &{}", other)),
+            None => return invalid("text ends with an incomplete control code".into()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_codes() {
+        for text in [
+            "&LConfidential&CPage &P of &N&R&D &T",
+            "&F - &A",
+            "&\"Arial,Bold\"&14Report",
+            "&KFF0000Warning",
+            "Smith && Sons",
+            "",
+        ] {
+            assert!(HeaderFooter::new(text).validate().is_ok(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_invalid_codes() {
+        for text in ["&Q", "Trailing &", "&\"Arial", "&K12", "&G"] {
+            assert!(HeaderFooter::new(text).validate().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_length_limit() {
+        assert!(HeaderFooter::new("x".repeat(255)).validate().is_ok());
+        assert!(HeaderFooter::new("x".repeat(256)).validate().is_err());
+        assert!(HeaderFooter::new("ok")
+            .first_page("x".repeat(256))
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use std::path::Path;
+
+use crate::error::{ExcelifyError, Result};
+
+/// Excel lays images out at 96 DPI, so higher-DPI images appear smaller.
+const DEFAULT_DPI: f64 = 96.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageType {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl ImageType {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageType::Png => "png",
+            ImageType::Jpeg => "jpeg",
+            ImageType::Gif => "gif",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageType::Png => "image/png",
+            ImageType::Jpeg => "image/jpeg",
+            ImageType::Gif => "image/gif",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    data: Vec<u8>,
+    image_type: ImageType,
+    width: u32,
+    height: u32,
+    x_dpi: f64,
+    y_dpi: f64,
+    scale_x: f64,
+    scale_y: f64,
+    x_offset: u32,
+    y_offset: u32,
+    alt_text: Option<String>,
+}
+
+impl Image {
+    /// Reads a PNG, JPEG or GIF image, detecting the format from its header.
+    pub fn from_buffer(data: Vec<u8>) -> Result<Self> {
+        let header = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            parse_png(&data)?
+        } else if data.starts_with(&[0xFF, 0xD8]) {
+            parse_jpeg(&data)?
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            parse_gif(&data)?
+        } else {
+            return Err(invalid("unsupported format; expected PNG, JPEG or GIF"));
+        };
+
+        if header.width == 0 || header.height == 0 {
+            return Err(invalid("image has zero width or height"));
+        }
+
+        Ok(Self {
+            data,
+            image_type: header.image_type,
+            width: header.width,
+            height: header.height,
+            x_dpi: header.x_dpi,
+            y_dpi: header.y_dpi,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            x_offset: 0,
+            y_offset: 0,
+            alt_text: None,
+        })
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_buffer(std::fs::read(path)?)
+    }
+
+    pub fn scale(mut self, scale_x: f64, scale_y: f64) -> Self {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+        self
+    }
+
+    /// Shifts the image right and down from the anchor cell, in pixels.
+    pub fn offset(mut self, x_offset: u32, y_offset: u32) -> Self {
+        self.x_offset = x_offset;
+        self.y_offset = y_offset;
+        self
+    }
+
+    pub fn alt_text(mut self, text: impl Into<String>) -> Self {
+        self.alt_text = Some(text.into());
+        self
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn image_type(&self) -> ImageType {
+        self.image_type
+    }
+
+    /// Pixel dimensions as stored in the file.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn dpi(&self) -> (f64, f64) {
+        (self.x_dpi, self.y_dpi)
+    }
+
+    pub fn get_offset(&self) -> (u32, u32) {
+        (self.x_offset, self.y_offset)
+    }
+
+    pub fn get_alt_text(&self) -> Option<&str> {
+        self.alt_text.as_deref()
+    }
+
+    /// Size in pixels as rendered by Excel, after DPI and scale adjustments.
+    pub fn display_size(&self) -> (u32, u32) {
+        let width = f64::from(self.width) * self.scale_x * DEFAULT_DPI / self.x_dpi;
+        let height = f64::from(self.height) * self.scale_y * DEFAULT_DPI / self.y_dpi;
+        (width.round() as u32, height.round() as u32)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let valid = |scale: f64| scale.is_finite() && scale > 0.0;
+        if !valid(self.scale_x) || !valid(self.scale_y) {
+            return Err(invalid("scale must be a positive number"));
+        }
+        Ok(())
+    }
+}
+
+struct ImageHeader {
+    image_type: ImageType,
+    width: u32,
+    height: u32,
+    x_dpi: f64,
+    y_dpi: f64,
+}
+
+fn parse_png(data: &[u8]) -> Result<ImageHeader> {
+    let mut header = ImageHeader {
+        image_type: ImageType::Png,
+        width: 0,
+        height: 0,
+        x_dpi: DEFAULT_DPI,
+        y_dpi: DEFAULT_DPI,
+    };
+
+    // Chunks are length (4), type (4), data, CRC (4) after the 8 byte signature.
+    let mut pos = 8;
+    while pos + 8 <= data.len() {
+        let length = read_u32_be(data, pos)? as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let body = pos + 8;
+
+        match chunk_type {
+            b"IHDR" => {
+                header.width = read_u32_be(data, body)?;
+                header.height = read_u32_be(data, body + 4)?;
+            }
+            b"pHYs" => {
+                let x_ppu = read_u32_be(data, body)?;
+                let y_ppu = read_u32_be(data, body + 4)?;
+                // Unit 1 is pixels per metre; 0 only gives an aspect ratio.
+                if data.get(body + 8) == Some(&1) && x_ppu > 0 && y_ppu > 0 {
+                    header.x_dpi = f64::from(x_ppu) * 0.0254;
+                    header.y_dpi = f64::from(y_ppu) * 0.0254;
+                }
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+
+        pos = body + length + 4;
+    }
+
+    if header.width == 0 {
+        return Err(invalid("PNG is missing its IHDR chunk"));
+    }
+    Ok(header)
+}
+
+fn parse_jpeg(data: &[u8]) -> Result<ImageHeader> {
+    let mut header = ImageHeader {
+        image_type: ImageType::Jpeg,
+        width: 0,
+        height: 0,
+        x_dpi: DEFAULT_DPI,
+        y_dpi: DEFAULT_DPI,
+    };
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err(invalid("malformed JPEG marker"));
+        }
+        let marker = data[pos + 1];
+        // Markers may be preceded by any number of 0xFF fill bytes.
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        let length = usize::from(read_u16_be(data, pos + 2)?);
+        if length < 2 {
+            return Err(invalid("malformed JPEG segment length"));
+        }
+        let body = pos + 4;
+
+        match marker {
+            // APP0 carries the JFIF density fields.
+            0xE0 if data.get(body..body + 5) == Some(b"JFIF\0") => {
+                let units = data.get(body + 7).copied().unwrap_or(0);
+                let x_density = f64::from(read_u16_be(data, body + 8)?);
+                let y_density = f64::from(read_u16_be(data, body + 10)?);
+                let factor = match units {
+                    1 => Some(1.0),
+                    2 => Some(2.54),
+                    _ => None,
+                };
+                if let Some(factor) = factor.filter(|_| x_density > 0.0 && y_density > 0.0) {
+                    header.x_dpi = x_density * factor;
+                    header.y_dpi = y_density * factor;
+                }
+            }
+            // Start-of-frame markers, excluding DHT (C4), JPG (C8) and DAC (CC).
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                header.height = u32::from(read_u16_be(data, body + 1)?);
+                header.width = u32::from(read_u16_be(data, body + 3)?);
+                return Ok(header);
+            }
+            // Start of scan: frame header should have come first.
+            0xDA => break,
+            _ => {}
+        }
+
+        pos = body + length - 2;
+    }
+
+    Err(invalid("JPEG is missing its frame header"))
+}
+
+fn parse_gif(data: &[u8]) -> Result<ImageHeader> {
+    Ok(ImageHeader {
+        image_type: ImageType::Gif,
+        width: u32::from(read_u16_le(data, 6)?),
+        height: u32::from(read_u16_le(data, 8)?),
+        x_dpi: DEFAULT_DPI,
+        y_dpi: DEFAULT_DPI,
+    })
+}
+
+fn read_u32_be(data: &[u8], pos: usize) -> Result<u32> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(truncated)
+}
+
+fn read_u16_be(data: &[u8], pos: usize) -> Result<u16> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(truncated)
+}
+
+fn read_u16_le(data: &[u8], pos: usize) -> Result<u16> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(truncated)
+}
+
+fn truncated() -> ExcelifyError {
+    invalid("image data is truncated")
+}
+
+fn invalid(reason: impl Into<String>) -> ExcelifyError {
+    ExcelifyError::InvalidImage(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32, ppm: Option<u32>) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend_from_slice(&13u32.to_be_bytes());
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        if let Some(ppm) = ppm {
+            data.extend_from_slice(&9u32.to_be_bytes());
+            data.extend_from_slice(b"pHYs");
+            data.extend_from_slice(&ppm.to_be_bytes());
+            data.extend_from_slice(&ppm.to_be_bytes());
+            data.extend_from_slice(&[1, 0, 0, 0, 0]);
+        }
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(b"IEND");
+        data
+    }
+
+    #[test]
+    fn test_png_header() {
+        let image = Image::from_buffer(png(200, 100, None)).unwrap();
+        assert_eq!(image.image_type(), ImageType::Png);
+        assert_eq!(image.dimensions(), (200, 100));
+        assert_eq!(image.display_size(), (200, 100));
+    }
+
+    #[test]
+    fn test_png_dpi_and_scale() {
+        // 7874 pixels per metre is 200 DPI.
+        let image = Image::from_buffer(png(400, 200, Some(7874))).unwrap();
+        let (x_dpi, _) = image.dpi();
+        assert!((x_dpi - 200.0).abs() < 0.01);
+        assert_eq!(image.display_size(), (192, 96));
+        assert_eq!(image.scale(0.5, 2.0).display_size(), (96, 192));
+    }
+
+    #[test]
+    fn test_jpeg_header() {
+        let mut data = vec![0xFF, 0xD8];
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10]);
+        data.extend_from_slice(b"JFIF\0");
+        data.extend_from_slice(&[1, 1, 1, 0, 72, 0, 72, 0, 0]);
+        data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x11, 8, 0x01, 0x2C, 0x02, 0x58]);
+        data.extend_from_slice(&[0; 12]);
+
+        let image = Image::from_buffer(data).unwrap();
+        assert_eq!(image.image_type(), ImageType::Jpeg);
+        assert_eq!(image.dimensions(), (600, 300));
+        assert_eq!(image.dpi(), (72.0, 72.0));
+        assert_eq!(image.display_size(), (800, 400));
+    }
+
+    #[test]
+    fn test_gif_header() {
+        let mut data = b"GIF89a".to_vec();
+        data.extend_from_slice(&[0x40, 0x01, 0xF0, 0x00]);
+        let image = Image::from_buffer(data).unwrap();
+        assert_eq!(image.image_type(), ImageType::Gif);
+        assert_eq!(image.dimensions(), (320, 240));
+    }
+
+    #[test]
+    fn test_invalid_images() {
+        assert!(Image::from_buffer(b"BM not supported".to_vec()).is_err());
+        assert!(Image::from_buffer(b"\x89PNG\r\n\x1a\n".to_vec()).is_err());
+        assert!(Image::from_buffer(vec![0xFF, 0xD8, 0xFF]).is_err());
+
+        let image = Image::from_buffer(png(1, 1, None)).unwrap();
+        assert!(image.scale(0.0, 1.0).validate().is_err());
+    }
+}
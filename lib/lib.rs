@@ -1,11 +1,31 @@
 pub mod bindings;
 pub mod cell;
+pub mod chart;
+pub mod conditional_format;
+pub mod defined_name;
+pub mod drawing;
 pub mod error;
+pub mod format;
+pub mod header_footer;
+pub mod image;
+pub mod page_setup;
+pub mod properties;
+pub mod protection;
+pub mod reference;
+pub mod rich_text;
+pub mod save_options;
+pub mod sheet_data;
+pub mod sheet_view;
+pub mod styles;
+pub mod validation;
 pub mod workbook;
 pub mod worksheet;
 pub mod writer;
 
 // Re-export napi bindings as the public API
-pub use bindings::{Workbook, Worksheet};
-pub use cell::CellValue;
+pub use bindings::{Chart, ExcelError, Workbook, Worksheet};
+pub use cell::{CellError, CellValue};
 pub use error::{ExcelifyError, Result};
+pub use format::{Color, Format};
+pub use reference::{CellRange, CellRef};
+pub use rich_text::{RichString, TextRun};
@@ -0,0 +1,341 @@
+use crate::error::{ExcelifyError, Result};
+use crate::reference::Shift;
+use crate::worksheet::{check_cell, RangeBounds};
+
+/// Highest paper size code defined by SpreadsheetML.
+const MAX_PAPER_SIZE: u32 = 118;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "portrait" => Some(Orientation::Portrait),
+            "landscape" => Some(Orientation::Landscape),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        }
+    }
+}
+
+/// Page margins in inches. The defaults match Excel's "Normal" preset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageMargins {
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub header: f64,
+    pub footer: f64,
+}
+
+impl Default for PageMargins {
+    fn default() -> Self {
+        Self {
+            left: 0.7,
+            right: 0.7,
+            top: 0.75,
+            bottom: 0.75,
+            header: 0.3,
+            footer: 0.3,
+        }
+    }
+}
+
+impl PageMargins {
+    fn values(&self) -> [f64; 6] {
+        [
+            self.left,
+            self.right,
+            self.top,
+            self.bottom,
+            self.header,
+            self.footer,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageSetup {
+    orientation: Option<Orientation>,
+    paper_size: Option<u32>,
+    scale: Option<u32>,
+    fit_to_pages: Option<(u32, u32)>,
+    margins: PageMargins,
+    center_horizontally: bool,
+    center_vertically: bool,
+    print_gridlines: bool,
+    print_headings: bool,
+    print_area: Option<RangeBounds>,
+    repeat_rows: Option<(u32, u32)>,
+    repeat_cols: Option<(u32, u32)>,
+}
+
+impl PageSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = Some(orientation);
+        self
+    }
+
+    /// Sets the paper size using Excel's codes, e.g. 1 for Letter or 9 for A4.
+    pub fn paper_size(mut self, paper_size: u32) -> Self {
+        self.paper_size = Some(paper_size);
+        self
+    }
+
+    /// Prints at `scale` percent of normal size (10-400).
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Shrinks the printout to fit `width` pages across and `height` pages
+    /// down. A value of 0 leaves that direction unconstrained. Takes
+    /// precedence over `scale`.
+    pub fn fit_to_pages(mut self, width: u32, height: u32) -> Self {
+        self.fit_to_pages = Some((width, height));
+        self
+    }
+
+    pub fn margins(mut self, margins: PageMargins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn center_horizontally(mut self, center: bool) -> Self {
+        self.center_horizontally = center;
+        self
+    }
+
+    pub fn center_vertically(mut self, center: bool) -> Self {
+        self.center_vertically = center;
+        self
+    }
+
+    pub fn print_gridlines(mut self, print: bool) -> Self {
+        self.print_gridlines = print;
+        self
+    }
+
+    /// Prints the row numbers and column letters.
+    pub fn print_headings(mut self, print: bool) -> Self {
+        self.print_headings = print;
+        self
+    }
+
+    pub fn print_area(
+        mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+    ) -> Self {
+        self.print_area = Some((
+            first_row.min(last_row),
+            first_col.min(last_col),
+            first_row.max(last_row),
+            first_col.max(last_col),
+        ));
+        self
+    }
+
+    /// Repeats rows `first..=last` at the top of every printed page.
+    pub fn repeat_rows(mut self, first: u32, last: u32) -> Self {
+        self.repeat_rows = Some((first.min(last), first.max(last)));
+        self
+    }
+
+    /// Repeats columns `first..=last` at the left of every printed page.
+    pub fn repeat_cols(mut self, first: u32, last: u32) -> Self {
+        self.repeat_cols = Some((first.min(last), first.max(last)));
+        self
+    }
+
+    /// Moves the print area and repeated rows and columns after rows or
+    /// columns were inserted or deleted. Parts that were deleted entirely
+    /// are cleared.
+    pub fn apply_shift(&mut self, shift: Shift) {
+        self.print_area = self.print_area.and_then(|bounds| shift.bounds(bounds));
+        self.repeat_rows = self.repeat_rows.and_then(|(first, last)| {
+            let (first, _, last, _) = shift.bounds((first, 0, last, 0))?;
+            Some((first, last))
+        });
+        self.repeat_cols = self.repeat_cols.and_then(|(first, last)| {
+            let (_, first, _, last) = shift.bounds((0, first, 0, last))?;
+            Some((first, last))
+        });
+    }
+
+    pub fn get_orientation(&self) -> Option<Orientation> {
+        self.orientation
+    }
+
+    pub fn get_paper_size(&self) -> Option<u32> {
+        self.paper_size
+    }
+
+    pub fn get_scale(&self) -> Option<u32> {
+        self.scale
+    }
+
+    pub fn get_fit_to_pages(&self) -> Option<(u32, u32)> {
+        self.fit_to_pages
+    }
+
+    pub fn get_margins(&self) -> &PageMargins {
+        &self.margins
+    }
+
+    pub fn is_centered_horizontally(&self) -> bool {
+        self.center_horizontally
+    }
+
+    pub fn is_centered_vertically(&self) -> bool {
+        self.center_vertically
+    }
+
+    pub fn has_print_gridlines(&self) -> bool {
+        self.print_gridlines
+    }
+
+    pub fn has_print_headings(&self) -> bool {
+        self.print_headings
+    }
+
+    pub fn get_print_area(&self) -> Option<RangeBounds> {
+        self.print_area
+    }
+
+    pub fn get_repeat_rows(&self) -> Option<(u32, u32)> {
+        self.repeat_rows
+    }
+
+    pub fn get_repeat_cols(&self) -> Option<(u32, u32)> {
+        self.repeat_cols
+    }
+
+    /// Whether any `<printOptions>` attribute differs from Excel's default.
+    pub fn has_print_options(&self) -> bool {
+        self.center_horizontally
+            || self.center_vertically
+            || self.print_gridlines
+            || self.print_headings
+    }
+
+    /// Whether any `<pageSetup>` attribute differs from Excel's default.
+    pub fn has_page_setup(&self) -> bool {
+        self.orientation.is_some()
+            || self.paper_size.is_some()
+            || self.scale.is_some()
+            || self.fit_to_pages.is_some()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(paper_size) = self.paper_size {
+            if paper_size == 0 || paper_size > MAX_PAPER_SIZE {
+                return Err(invalid(format!(
+                    "paper size must be between 1 and {}",
+                    MAX_PAPER_SIZE
+                )));
+            }
+        }
+        if let Some(scale) = self.scale {
+            if !(10..=400).contains(&scale) {
+                return Err(invalid("scale must be between 10 and 400 percent"));
+            }
+        }
+        if self
+            .margins
+            .values()
+            .iter()
+            .any(|m| !m.is_finite() || *m < 0.0)
+        {
+            return Err(invalid("margins must be non-negative numbers"));
+        }
+        // Ranges are normalized, so checking their last row and column
+        // covers the whole range.
+        if let Some((_, _, last_row, last_col)) = self.print_area {
+            check_cell(last_row, last_col)?;
+        }
+        if let Some((_, last_row)) = self.repeat_rows {
+            check_cell(last_row, 0)?;
+        }
+        if let Some((_, last_col)) = self.repeat_cols {
+            check_cell(0, last_col)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> ExcelifyError {
+    ExcelifyError::InvalidPageSetup(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let setup = PageSetup::new();
+        assert!(!setup.has_page_setup());
+        assert!(!setup.has_print_options());
+        assert_eq!(setup.get_margins().top, 0.75);
+        assert!(setup.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ranges_are_normalized() {
+        let setup = PageSetup::new()
+            .print_area(10, 3, 0, 0)
+            .repeat_rows(1, 0)
+            .repeat_cols(2, 2);
+        assert_eq!(setup.get_print_area(), Some((0, 0, 10, 3)));
+        assert_eq!(setup.get_repeat_rows(), Some((0, 1)));
+        assert_eq!(setup.get_repeat_cols(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(PageSetup::new().scale(5).validate().is_err());
+        assert!(PageSetup::new().scale(400).validate().is_ok());
+        assert!(PageSetup::new().paper_size(0).validate().is_err());
+        assert!(PageSetup::new().paper_size(9).validate().is_ok());
+
+        let margins = PageMargins {
+            left: -1.0,
+            ..PageMargins::default()
+        };
+        assert!(PageSetup::new().margins(margins).validate().is_err());
+
+        let err = PageSetup::new()
+            .print_area(0, 0, 2_000_000, 0)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_ROW_OUT_OF_RANGE");
+        let err = PageSetup::new()
+            .repeat_cols(0, 20_000)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_COLUMN_OUT_OF_RANGE");
+        assert!(PageSetup::new()
+            .repeat_rows(0, 1_048_575)
+            .validate()
+            .is_ok());
+    }
+}
@@ -0,0 +1,398 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cell::format_number;
+use crate::error::{ExcelifyError, Result};
+
+/// Longest custom property name Excel accepts.
+const MAX_CUSTOM_NAME_LENGTH: usize = 255;
+
+/// A UTC point in time with one-second precision, written to document
+/// properties in W3CDTF form (`2024-03-01T09:30:00Z`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        Timestamp(seconds)
+    }
+
+    /// Converts a JavaScript-style millisecond timestamp.
+    pub fn from_unix_millis(millis: f64) -> Result<Self> {
+        if !millis.is_finite() {
+            return Err(invalid(format!("invalid timestamp {}", millis)));
+        }
+        Ok(Timestamp((millis / 1000.0).floor() as i64))
+    }
+
+    pub fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        Timestamp(seconds)
+    }
+
+    /// Parses an ISO 8601 date (`2024-03-01`) or date-time
+    /// (`2024-03-01T09:30:00`, optionally with fractional seconds and a `Z`
+    /// or `±HH:MM` offset). Date-times without an offset are taken as UTC.
+    pub fn parse(text: &str) -> Result<Self> {
+        let error = || invalid(format!("expected an ISO 8601 date, got {:?}", text));
+        let number = |s: &str| -> Result<i64> {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(error());
+            }
+            s.parse().map_err(|_| error())
+        };
+
+        let (date, time) = match text.split_once(['T', ' ']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (text, None),
+        };
+
+        let mut parts = date.splitn(3, '-');
+        let (year, month, day) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(y), Some(m), Some(d)) if y.len() == 4 && m.len() == 2 && d.len() == 2 => {
+                (number(y)?, number(m)?, number(d)?)
+            }
+            _ => return Err(error()),
+        };
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(error());
+        }
+        let mut seconds = days_from_civil(year, month, day) * 86_400;
+
+        if let Some(time) = time {
+            let (clock, offset) = if let Some(clock) = time.strip_suffix('Z') {
+                (clock, 0)
+            } else if let Some(pos) = time.rfind(['+', '-']) {
+                let (clock, offset) = time.split_at(pos);
+                let sign = if offset.starts_with('-') { -1 } else { 1 };
+                let (hours, minutes) = offset[1..].split_once(':').ok_or_else(error)?;
+                (
+                    clock,
+                    sign * (number(hours)? * 3600 + number(minutes)? * 60),
+                )
+            } else {
+                (time, 0)
+            };
+
+            let clock = clock.split('.').next().unwrap_or_default();
+            let fields: Vec<&str> = clock.split(':').collect();
+            let (hour, minute, second) = match fields[..] {
+                [h, m] => (number(h)?, number(m)?, 0),
+                [h, m, s] => (number(h)?, number(m)?, number(s)?),
+                _ => return Err(error()),
+            };
+            if hour > 23 || minute > 59 || second > 59 {
+                return Err(error());
+            }
+            seconds += hour * 3600 + minute * 60 + second - offset;
+        }
+
+        Ok(Timestamp(seconds))
+    }
+
+    pub fn unix_seconds(&self) -> i64 {
+        self.0
+    }
+
+    /// The UTC calendar date and time as `(year, month, day, hour, minute,
+    /// second)`.
+    pub fn to_civil(&self) -> (i64, i64, i64, i64, i64, i64) {
+        let days = self.0.div_euclid(86_400);
+        let secs = self.0.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        (year, month, day, secs / 3600, secs % 3600 / 60, secs % 60)
+    }
+
+    pub fn to_w3cdtf(&self) -> String {
+        let (year, month, day, hour, minute, second) = self.to_civil();
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year, month, day, hour, minute, second
+        )
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Built-in document properties shown in Excel's File > Info pane.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentProperties {
+    title: Option<String>,
+    subject: Option<String>,
+    author: Option<String>,
+    manager: Option<String>,
+    company: Option<String>,
+    category: Option<String>,
+    keywords: Option<String>,
+    comments: Option<String>,
+    status: Option<String>,
+    hyperlink_base: Option<String>,
+    created: Option<Timestamp>,
+}
+
+impl DocumentProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn manager(mut self, manager: impl Into<String>) -> Self {
+        self.manager = Some(manager.into());
+        self
+    }
+
+    pub fn company(mut self, company: impl Into<String>) -> Self {
+        self.company = Some(company.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn keywords(mut self, keywords: impl Into<String>) -> Self {
+        self.keywords = Some(keywords.into());
+        self
+    }
+
+    pub fn comments(mut self, comments: impl Into<String>) -> Self {
+        self.comments = Some(comments.into());
+        self
+    }
+
+    pub fn status(mut self, status: impl Into<String>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    pub fn hyperlink_base(mut self, base: impl Into<String>) -> Self {
+        self.hyperlink_base = Some(base.into());
+        self
+    }
+
+    /// Sets the creation time. Defaults to the time the file is saved.
+    pub fn created(mut self, created: Timestamp) -> Self {
+        self.created = Some(created);
+        self
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn get_subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    pub fn get_author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn get_manager(&self) -> Option<&str> {
+        self.manager.as_deref()
+    }
+
+    pub fn get_company(&self) -> Option<&str> {
+        self.company.as_deref()
+    }
+
+    pub fn get_category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn get_keywords(&self) -> Option<&str> {
+        self.keywords.as_deref()
+    }
+
+    pub fn get_comments(&self) -> Option<&str> {
+        self.comments.as_deref()
+    }
+
+    pub fn get_status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    pub fn get_hyperlink_base(&self) -> Option<&str> {
+        self.hyperlink_base.as_deref()
+    }
+
+    pub fn get_created(&self) -> Option<Timestamp> {
+        self.created
+    }
+}
+
+/// The value of a custom document property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomProperty {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+    Date(Timestamp),
+}
+
+impl CustomProperty {
+    /// The `docPropsVTypes` element holding the value. Whole numbers that fit
+    /// are written as integers so Excel shows them without decimals.
+    pub fn vt_type(&self) -> &'static str {
+        match self {
+            CustomProperty::Text(_) => "vt:lpwstr",
+            CustomProperty::Number(n) if is_i4(*n) => "vt:i4",
+            CustomProperty::Number(_) => "vt:r8",
+            CustomProperty::Boolean(_) => "vt:bool",
+            CustomProperty::Date(_) => "vt:filetime",
+        }
+    }
+
+    pub fn vt_value(&self) -> String {
+        match self {
+            CustomProperty::Text(text) => text.clone(),
+            CustomProperty::Number(n) if is_i4(*n) => (*n as i32).to_string(),
+            CustomProperty::Number(n) => format_number(*n),
+            CustomProperty::Boolean(b) => b.to_string(),
+            CustomProperty::Date(timestamp) => timestamp.to_w3cdtf(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            CustomProperty::Number(n) if !n.is_finite() => Err(invalid(format!(
+                "custom property value {} is not finite",
+                n
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn is_i4(n: f64) -> bool {
+    n.fract() == 0.0 && n >= f64::from(i32::MIN) && n <= f64::from(i32::MAX)
+}
+
+pub fn validate_custom_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(invalid("custom property name must not be empty".into()));
+    }
+    if name.chars().count() > MAX_CUSTOM_NAME_LENGTH {
+        return Err(invalid(format!(
+            "custom property name exceeds {} characters",
+            MAX_CUSTOM_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn invalid(reason: String) -> ExcelifyError {
+    ExcelifyError::InvalidProperty(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        assert_eq!(
+            Timestamp::from_unix_seconds(0).to_w3cdtf(),
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix_seconds(951_782_400).to_w3cdtf(),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix_seconds(-1).to_w3cdtf(),
+            "1969-12-31T23:59:59Z"
+        );
+        let parsed = Timestamp::parse("2024-03-01T09:30:15Z").unwrap();
+        assert_eq!(parsed.to_w3cdtf(), "2024-03-01T09:30:15Z");
+    }
+
+    #[test]
+    fn test_timestamp_parse_forms() {
+        let utc = Timestamp::parse("2024-03-01T09:30:00Z").unwrap();
+        assert_eq!(Timestamp::parse("2024-03-01T09:30:00.123Z").unwrap(), utc);
+        assert_eq!(Timestamp::parse("2024-03-01T10:30:00+01:00").unwrap(), utc);
+        assert_eq!(Timestamp::parse("2024-03-01 09:30").unwrap(), utc);
+        assert_eq!(
+            Timestamp::parse("2024-03-01").unwrap().to_w3cdtf(),
+            "2024-03-01T00:00:00Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix_millis(1_709_285_400_000.0).unwrap(),
+            utc
+        );
+
+        for text in [
+            "",
+            "2024-3-1",
+            "2023-02-29",
+            "2024-13-01",
+            "2024-03-01T25:00",
+            "yesterday",
+        ] {
+            assert!(Timestamp::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_custom_property_types() {
+        assert_eq!(CustomProperty::Number(42.0).vt_type(), "vt:i4");
+        assert_eq!(CustomProperty::Number(42.0).vt_value(), "42");
+        assert_eq!(CustomProperty::Number(1.5).vt_type(), "vt:r8");
+        assert_eq!(CustomProperty::Number(1e10).vt_type(), "vt:r8");
+        assert_eq!(CustomProperty::Boolean(true).vt_value(), "true");
+        assert!(CustomProperty::Number(f64::NAN).validate().is_err());
+        assert!(validate_custom_name(" ").is_err());
+    }
+}
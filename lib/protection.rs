@@ -0,0 +1,240 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::{Digest, Sha512};
+
+use crate::error::{ExcelifyError, Result};
+
+/// Iterations Excel uses when hashing protection passwords with SHA-512.
+pub const DEFAULT_SPIN_COUNT: u32 = 100_000;
+const SALT_LENGTH: usize = 16;
+
+/// A protection password as stored in the file. Excel never stores the
+/// password itself, only one of these hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordHash {
+    /// The 16-bit hash understood by every spreadsheet application. Easy to
+    /// brute-force, so it only deters casual edits.
+    Legacy(u16),
+    /// The salted, iterated SHA-512 hash written by Excel 2013 and later.
+    Sha512 {
+        hash: String,
+        salt: String,
+        spin_count: u32,
+    },
+}
+
+impl PasswordHash {
+    pub fn legacy(password: &str) -> Self {
+        PasswordHash::Legacy(legacy_hash(password))
+    }
+
+    /// Hashes with SHA-512 using a random salt.
+    pub fn sha512(password: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LENGTH];
+        getrandom::getrandom(&mut salt).map_err(|e| {
+            ExcelifyError::InvalidProtection(format!("failed to generate salt: {}", e))
+        })?;
+        Ok(Self::sha512_with_salt(password, &salt, DEFAULT_SPIN_COUNT))
+    }
+
+    /// Hashes with SHA-512 using a caller-supplied salt, for reproducible
+    /// output.
+    pub fn sha512_with_salt(password: &str, salt: &[u8], spin_count: u32) -> Self {
+        let utf16: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut hash = Sha512::new()
+            .chain_update(salt)
+            .chain_update(&utf16)
+            .finalize();
+        for iteration in 0..spin_count {
+            hash = Sha512::new()
+                .chain_update(hash)
+                .chain_update(iteration.to_le_bytes())
+                .finalize();
+        }
+        PasswordHash::Sha512 {
+            hash: BASE64.encode(hash),
+            salt: BASE64.encode(salt),
+            spin_count,
+        }
+    }
+}
+
+/// The legacy password verifier from ECMA-376 Part 4, 14.7.1.
+fn legacy_hash(password: &str) -> u16 {
+    let bytes: Vec<u16> = password.chars().map(|c| c as u16).collect();
+    let mut hash: u16 = 0;
+    for &byte in bytes.iter().rev() {
+        hash = ((hash >> 14) & 0x01) | ((hash << 1) & 0x7FFF);
+        hash ^= byte;
+    }
+    hash = ((hash >> 14) & 0x01) | ((hash << 1) & 0x7FFF);
+    hash ^= bytes.len() as u16;
+    hash ^ 0xCE4B
+}
+
+/// Which actions remain available to users on a protected worksheet.
+/// Selecting cells is allowed by default; everything else is locked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetProtection {
+    password: Option<PasswordHash>,
+    select_locked_cells: bool,
+    select_unlocked_cells: bool,
+    format_cells: bool,
+    format_columns: bool,
+    format_rows: bool,
+    insert_columns: bool,
+    insert_rows: bool,
+    insert_hyperlinks: bool,
+    delete_columns: bool,
+    delete_rows: bool,
+    sort: bool,
+    autofilter: bool,
+    pivot_tables: bool,
+    edit_objects: bool,
+    edit_scenarios: bool,
+}
+
+impl Default for SheetProtection {
+    fn default() -> Self {
+        Self {
+            password: None,
+            select_locked_cells: true,
+            select_unlocked_cells: true,
+            format_cells: false,
+            format_columns: false,
+            format_rows: false,
+            insert_columns: false,
+            insert_rows: false,
+            insert_hyperlinks: false,
+            delete_columns: false,
+            delete_rows: false,
+            sort: false,
+            autofilter: false,
+            pivot_tables: false,
+            edit_objects: false,
+            edit_scenarios: false,
+        }
+    }
+}
+
+macro_rules! allow_setters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            pub fn $field(mut self, allow: bool) -> Self {
+                self.$field = allow;
+                self
+            }
+        )*
+    };
+}
+
+impl SheetProtection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn password(mut self, password: PasswordHash) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    allow_setters!(
+        select_locked_cells,
+        select_unlocked_cells,
+        format_cells,
+        format_columns,
+        format_rows,
+        insert_columns,
+        insert_rows,
+        insert_hyperlinks,
+        delete_columns,
+        delete_rows,
+        sort,
+        autofilter,
+        pivot_tables,
+        edit_objects,
+        edit_scenarios,
+    );
+
+    pub fn get_password(&self) -> Option<&PasswordHash> {
+        self.password.as_ref()
+    }
+
+    /// The `<sheetProtection>` flags that differ from their schema default,
+    /// as attribute name and value. In the file a flag set to `1` means the
+    /// action is *locked*.
+    pub fn attributes(&self) -> Vec<(&'static str, &'static str)> {
+        let flag = |locked: bool| if locked { "1" } else { "0" };
+        // (attribute, locked, schema default)
+        let flags = [
+            ("objects", !self.edit_objects, false),
+            ("scenarios", !self.edit_scenarios, false),
+            ("formatCells", !self.format_cells, true),
+            ("formatColumns", !self.format_columns, true),
+            ("formatRows", !self.format_rows, true),
+            ("insertColumns", !self.insert_columns, true),
+            ("insertRows", !self.insert_rows, true),
+            ("insertHyperlinks", !self.insert_hyperlinks, true),
+            ("deleteColumns", !self.delete_columns, true),
+            ("deleteRows", !self.delete_rows, true),
+            ("selectLockedCells", !self.select_locked_cells, false),
+            ("sort", !self.sort, true),
+            ("autoFilter", !self.autofilter, true),
+            ("pivotTables", !self.pivot_tables, true),
+            ("selectUnlockedCells", !self.select_unlocked_cells, false),
+        ];
+        flags
+            .into_iter()
+            .filter(|(_, locked, default)| locked != default)
+            .map(|(name, locked, _)| (name, flag(locked)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_hash() {
+        assert_eq!(legacy_hash("password"), 0x83AF);
+        assert_eq!(legacy_hash(""), 0xCE4B);
+    }
+
+    #[test]
+    fn test_sha512_hash() {
+        let PasswordHash::Sha512 {
+            hash,
+            salt,
+            spin_count,
+        } = PasswordHash::sha512_with_salt("secret", &[0; 16], 1)
+        else {
+            panic!("expected a SHA-512 hash");
+        };
+        assert_eq!(salt, "AAAAAAAAAAAAAAAAAAAAAA==");
+        assert_eq!(spin_count, 1);
+        assert_eq!(BASE64.decode(hash).unwrap().len(), 64);
+
+        assert_ne!(
+            PasswordHash::sha512("secret").unwrap(),
+            PasswordHash::sha512("secret").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        assert_eq!(
+            SheetProtection::new().attributes(),
+            vec![("objects", "1"), ("scenarios", "1")]
+        );
+        let protection = SheetProtection::new()
+            .sort(true)
+            .edit_objects(true)
+            .edit_scenarios(true)
+            .select_locked_cells(false);
+        assert_eq!(
+            protection.attributes(),
+            vec![("selectLockedCells", "1"), ("sort", "0")]
+        );
+    }
+}
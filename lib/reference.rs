@@ -0,0 +1,758 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{ExcelifyError, Result};
+use crate::worksheet::{col_to_letter, quote_sheet_name, RangeBounds, MAX_COLS, MAX_ROWS};
+
+/// A single cell in A1 notation, e.g. `B3`, `$B$3` or `'Q1 Sales'!B3`.
+/// Rows and columns are zero-based, like everywhere else in the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRef {
+    pub sheet: Option<String>,
+    pub row: u32,
+    pub col: u32,
+    /// The row is pinned with `$`.
+    pub row_absolute: bool,
+    /// The column is pinned with `$`.
+    pub col_absolute: bool,
+}
+
+impl CellRef {
+    pub fn new(row: u32, col: u32) -> Self {
+        Self {
+            sheet: None,
+            row,
+            col,
+            row_absolute: false,
+            col_absolute: false,
+        }
+    }
+
+    pub fn parse(reference: &str) -> Result<Self> {
+        let (sheet, cell) = split_sheet(reference)?;
+        let mut cell_ref = parse_cell(cell).ok_or_else(|| invalid(reference))?;
+        cell_ref.sheet = sheet;
+        Ok(cell_ref)
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = ExcelifyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            write!(f, "{}!", quote_sheet_name(sheet))?;
+        }
+        let dollar = |absolute: bool| if absolute { "$" } else { "" };
+        write!(
+            f,
+            "{}{}{}{}",
+            dollar(self.col_absolute),
+            col_to_letter(self.col),
+            dollar(self.row_absolute),
+            self.row + 1
+        )
+    }
+}
+
+/// A rectangular range such as `A1:C10`, a whole column `A:A` or a whole
+/// row `3:3`, optionally on a named sheet. `first` is always the top-left
+/// corner and `last` the bottom-right; the sheet is only kept on the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRange {
+    pub sheet: Option<String>,
+    pub first: CellRef,
+    pub last: CellRef,
+}
+
+impl CellRange {
+    pub fn new(first_row: u32, first_col: u32, last_row: u32, last_col: u32) -> Self {
+        Self {
+            sheet: None,
+            first: CellRef::new(first_row.min(last_row), first_col.min(last_col)),
+            last: CellRef::new(first_row.max(last_row), first_col.max(last_col)),
+        }
+    }
+
+    /// Parses a range. A single cell such as `B3` is accepted as a one-cell
+    /// range.
+    pub fn parse(reference: &str) -> Result<Self> {
+        let (sheet, range) = split_sheet(reference)?;
+        let (first, last) = match range.split_once(':') {
+            Some((first, last)) => parse_cell(first)
+                .zip(parse_cell(last))
+                .or_else(|| whole_columns(first, last))
+                .or_else(|| whole_rows(first, last)),
+            None => parse_cell(range).map(|cell| (cell.clone(), cell)),
+        }
+        .ok_or_else(|| invalid(reference))?;
+
+        // Normalize so that `C10:A1` means the same as `A1:C10`.
+        let (first_col, last_col) = if first.col <= last.col {
+            (
+                (first.col, first.col_absolute),
+                (last.col, last.col_absolute),
+            )
+        } else {
+            (
+                (last.col, last.col_absolute),
+                (first.col, first.col_absolute),
+            )
+        };
+        let (first_row, last_row) = if first.row <= last.row {
+            (
+                (first.row, first.row_absolute),
+                (last.row, last.row_absolute),
+            )
+        } else {
+            (
+                (last.row, last.row_absolute),
+                (first.row, first.row_absolute),
+            )
+        };
+        let corner = |(row, row_absolute), (col, col_absolute)| CellRef {
+            sheet: None,
+            row,
+            col,
+            row_absolute,
+            col_absolute,
+        };
+        Ok(Self {
+            sheet,
+            first: corner(first_row, first_col),
+            last: corner(last_row, last_col),
+        })
+    }
+
+    pub fn bounds(&self) -> RangeBounds {
+        (self.first.row, self.first.col, self.last.row, self.last.col)
+    }
+
+    pub fn contains(&self, row: u32, col: u32) -> bool {
+        (self.first.row..=self.last.row).contains(&row)
+            && (self.first.col..=self.last.col).contains(&col)
+    }
+
+    pub fn is_single_cell(&self) -> bool {
+        self.first.row == self.last.row && self.first.col == self.last.col
+    }
+}
+
+impl FromStr for CellRange {
+    type Err = ExcelifyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            write!(f, "{}!", quote_sheet_name(sheet))?;
+        }
+        if self.is_single_cell() {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}:{}", self.first, self.last)
+        }
+    }
+}
+
+/// Rows or columns inserted into or deleted from a worksheet. Moves cell
+/// positions and ranges, and rewrites the references in formulas that point
+/// at the edited sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    InsertRows { at: u32, count: u32 },
+    DeleteRows { at: u32, count: u32 },
+    InsertCols { at: u32, count: u32 },
+    DeleteCols { at: u32, count: u32 },
+}
+
+impl Shift {
+    /// Where a row ends up, or `None` if it was deleted or pushed off the
+    /// sheet.
+    pub fn row(&self, row: u32) -> Option<u32> {
+        match *self {
+            Shift::InsertRows { at, count } => insert_index(row, at, count, MAX_ROWS),
+            Shift::DeleteRows { at, count } => delete_index(row, at, count),
+            _ => Some(row),
+        }
+    }
+
+    /// Where a column ends up, or `None` if it was deleted or pushed off the
+    /// sheet.
+    pub fn col(&self, col: u32) -> Option<u32> {
+        match *self {
+            Shift::InsertCols { at, count } => insert_index(col, at, count, MAX_COLS),
+            Shift::DeleteCols { at, count } => delete_index(col, at, count),
+            _ => Some(col),
+        }
+    }
+
+    pub fn cell(&self, row: u32, col: u32) -> Option<(u32, u32)> {
+        Some((self.row(row)?, self.col(col)?))
+    }
+
+    /// Where a range ends up. Like in Excel, a range grows when rows or
+    /// columns are inserted inside it and shrinks when part of it is
+    /// deleted. Returns `None` if the whole range was deleted.
+    pub fn bounds(&self, bounds: RangeBounds) -> Option<RangeBounds> {
+        let (first_row, first_col, last_row, last_col) = bounds;
+        let (first_row, last_row) = self.row_span(first_row, last_row)?;
+        let (first_col, last_col) = self.col_span(first_col, last_col)?;
+        Some((first_row, first_col, last_row, last_col))
+    }
+
+    fn row_span(&self, first: u32, last: u32) -> Option<(u32, u32)> {
+        match *self {
+            Shift::InsertRows { at, count } => insert_span(first, last, at, count, MAX_ROWS),
+            Shift::DeleteRows { at, count } => delete_span(first, last, at, count, MAX_ROWS),
+            _ => Some((first, last)),
+        }
+    }
+
+    fn col_span(&self, first: u32, last: u32) -> Option<(u32, u32)> {
+        match *self {
+            Shift::InsertCols { at, count } => insert_span(first, last, at, count, MAX_COLS),
+            Shift::DeleteCols { at, count } => delete_span(first, last, at, count, MAX_COLS),
+            _ => Some((first, last)),
+        }
+    }
+
+    /// Rewrites the references to `sheet` in a formula. Unqualified
+    /// references belong to `home_sheet`, the sheet the formula lives on.
+    /// References to deleted cells become `#REF!`, as in Excel.
+    pub fn formula(&self, formula: &str, sheet: &str, home_sheet: Option<&str>) -> String {
+        rewrite_references(formula, |prefix, ref_sheet, shape| {
+            if !ref_sheet
+                .or(home_sheet)
+                .is_some_and(|target| same_sheet(target, sheet))
+            {
+                return None;
+            }
+            Some(match self.shift_shape(shape) {
+                Some(shape) => format!("{}{}", prefix, shape),
+                None => "#REF!".to_string(),
+            })
+        })
+    }
+
+    fn shift_shape(&self, shape: RefShape) -> Option<RefShape> {
+        Some(match shape {
+            RefShape::Cells(first, last) => {
+                let (first_row, first_col, last_row, last_col) =
+                    self.bounds((first.row, first.col, last.row, last.col))?;
+                RefShape::Cells(
+                    CellRef {
+                        row: first_row,
+                        col: first_col,
+                        ..first
+                    },
+                    CellRef {
+                        row: last_row,
+                        col: last_col,
+                        ..last
+                    },
+                )
+            }
+            RefShape::Cell(cell) => {
+                let (row, col) = self.cell(cell.row, cell.col)?;
+                RefShape::Cell(CellRef { row, col, ..cell })
+            }
+            RefShape::Cols((first, first_abs), (last, last_abs)) => {
+                let (first, last) = self.col_span(first, last)?;
+                RefShape::Cols((first, first_abs), (last, last_abs))
+            }
+            RefShape::Rows((first, first_abs), (last, last_abs)) => {
+                let (first, last) = self.row_span(first, last)?;
+                RefShape::Rows((first, first_abs), (last, last_abs))
+            }
+        })
+    }
+}
+
+/// Points the references to sheet `old` in a formula at `new`, or turns
+/// them into `#REF!` when the sheet was removed. Unqualified references are
+/// left alone.
+pub fn rename_sheet_references(formula: &str, old: &str, new: Option<&str>) -> String {
+    rewrite_references(formula, |_, sheet, shape| {
+        if !same_sheet(sheet?, old) {
+            return None;
+        }
+        Some(match new {
+            Some(new) => format!("{}!{}", quote_sheet_name(new), shape),
+            None => "#REF!".to_string(),
+        })
+    })
+}
+
+/// Excel compares sheet names case-insensitively.
+fn same_sheet(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Finds the cell references in a formula, skipping string literals and
+/// names, and replaces each one for which `replace` returns new text.
+/// `replace` gets the sheet prefix as written, the unquoted sheet name, if
+/// any, and the reference itself.
+fn rewrite_references(
+    formula: &str,
+    mut replace: impl FnMut(&str, Option<&str>, RefShape) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(formula.len());
+    let mut rest = formula;
+    let mut after_name = false;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '"' {
+            let len = string_literal_len(rest);
+            out.push_str(&rest[..len]);
+            after_name = false;
+            len
+        } else if !after_name && (c == '\'' || c == '$' || is_name_char(c)) {
+            let len = match reference_at(rest) {
+                Some((len, prefix_len, sheet, shape)) => {
+                    match replace(&rest[..prefix_len], sheet.as_deref(), shape) {
+                        Some(replacement) => out.push_str(&replacement),
+                        None => out.push_str(&rest[..len]),
+                    }
+                    len
+                }
+                None => {
+                    // Skip the whole name so that references aren't found
+                    // inside it.
+                    let len = rest
+                        .find(|c: char| c != '$' && !is_name_char(c))
+                        .unwrap_or(rest.len())
+                        .max(c.len_utf8());
+                    out.push_str(&rest[..len]);
+                    len
+                }
+            };
+            after_name = true;
+            len
+        } else {
+            out.push(c);
+            after_name = is_name_char(c);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    out
+}
+
+/// Parses a possibly sheet-qualified reference at the start of `text`,
+/// returning its length, the length of its sheet prefix, the sheet name and
+/// the reference.
+fn reference_at(text: &str) -> Option<(usize, usize, Option<String>, RefShape)> {
+    let (prefix_len, sheet) = sheet_prefix(text)?;
+    let body = &text[prefix_len..];
+    let body_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '$' || c == ':'))
+        .unwrap_or(body.len());
+    let next = body[body_len..].chars().next();
+    if next.is_some_and(|c| c == '(' || is_name_char(c)) {
+        return None;
+    }
+    let shape = RefShape::parse(&body[..body_len])?;
+    Some((prefix_len + body_len, prefix_len, sheet, shape))
+}
+
+fn insert_index(index: u32, at: u32, count: u32, limit: u32) -> Option<u32> {
+    if index < at {
+        return Some(index);
+    }
+    index.checked_add(count).filter(|&index| index < limit)
+}
+
+fn delete_index(index: u32, at: u32, count: u32) -> Option<u32> {
+    if index < at {
+        Some(index)
+    } else if index - at < count {
+        None
+    } else {
+        Some(index - count)
+    }
+}
+
+fn insert_span(first: u32, last: u32, at: u32, count: u32, limit: u32) -> Option<(u32, u32)> {
+    // Whole rows and columns stay whole.
+    if (first, last) == (0, limit - 1) {
+        return Some((first, last));
+    }
+    let first = insert_index(first, at, count, limit)?;
+    let last = if last < at {
+        last
+    } else {
+        last.saturating_add(count).min(limit - 1)
+    };
+    Some((first, last))
+}
+
+fn delete_span(first: u32, last: u32, at: u32, count: u32, limit: u32) -> Option<(u32, u32)> {
+    if (first, last) == (0, limit - 1) {
+        return Some((first, last));
+    }
+    let end = at.saturating_add(count);
+    let first = if first < at {
+        first
+    } else if first < end {
+        at
+    } else {
+        first - count
+    };
+    let last = if last < at {
+        last
+    } else if last < end {
+        at.checked_sub(1)?
+    } else {
+        last - count
+    };
+    (first <= last).then_some((first, last))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '\\'
+}
+
+/// The length of the string literal at the start of `text`, including its
+/// quotes. `""` inside the literal is an escaped quote.
+fn string_literal_len(text: &str) -> usize {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            if chars.peek().is_some_and(|&(_, c)| c == '"') {
+                chars.next();
+            } else {
+                return i + 1;
+            }
+        }
+    }
+    text.len()
+}
+
+/// Reads a `Sheet!` or `'Sheet Name'!` prefix, returning its length and the
+/// unquoted sheet name. A missing prefix has length 0.
+fn sheet_prefix(text: &str) -> Option<(usize, Option<String>)> {
+    if let Some(quoted) = text.strip_prefix('\'') {
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c != '\'' {
+                continue;
+            }
+            if chars.peek().is_some_and(|&(_, c)| c == '\'') {
+                chars.next();
+                continue;
+            }
+            let end = i + 1;
+            return quoted[end..]
+                .starts_with('!')
+                .then(|| (end + 2, Some(quoted[..i].replace("''", "'"))));
+        }
+        return None;
+    }
+    let name_len = text.find(|c: char| !is_name_char(c)).unwrap_or(text.len());
+    if name_len > 0 && text[name_len..].starts_with('!') {
+        Some((name_len + 1, Some(text[..name_len].to_string())))
+    } else {
+        Some((0, None))
+    }
+}
+
+/// A reference as written in a formula, keeping its shape so that it can be
+/// written back the same way.
+enum RefShape {
+    Cell(CellRef),
+    Cells(CellRef, CellRef),
+    Cols((u32, bool), (u32, bool)),
+    Rows((u32, bool), (u32, bool)),
+}
+
+impl RefShape {
+    fn parse(text: &str) -> Option<Self> {
+        match text.split_once(':') {
+            None => parse_cell(text).map(RefShape::Cell),
+            Some((first, last)) => {
+                if let (Some(first), Some(last)) = (parse_cell(first), parse_cell(last)) {
+                    Some(RefShape::Cells(first, last))
+                } else if let (Some(first), Some(last)) = (parse_col(first), parse_col(last)) {
+                    Some(RefShape::Cols(first, last))
+                } else {
+                    Some(RefShape::Rows(parse_row(first)?, parse_row(last)?))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for RefShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dollar = |absolute: bool| if absolute { "$" } else { "" };
+        match self {
+            RefShape::Cell(cell) => write!(f, "{}", cell),
+            RefShape::Cells(first, last) => write!(f, "{}:{}", first, last),
+            RefShape::Cols((first, first_abs), (last, last_abs)) => write!(
+                f,
+                "{}{}:{}{}",
+                dollar(*first_abs),
+                col_to_letter(*first),
+                dollar(*last_abs),
+                col_to_letter(*last)
+            ),
+            RefShape::Rows((first, first_abs), (last, last_abs)) => write!(
+                f,
+                "{}{}:{}{}",
+                dollar(*first_abs),
+                first + 1,
+                dollar(*last_abs),
+                last + 1
+            ),
+        }
+    }
+}
+
+fn invalid(reference: &str) -> ExcelifyError {
+    ExcelifyError::InvalidCellReference(format!("{:?}", reference))
+}
+
+/// Splits `Sheet!A1` or `'Sheet Name'!A1` into the unquoted sheet name and
+/// the reference.
+fn split_sheet(reference: &str) -> Result<(Option<String>, &str)> {
+    let Some((sheet, rest)) = reference.rsplit_once('!') else {
+        return Ok((None, reference));
+    };
+    let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(quoted) => quoted.replace("''", "'"),
+        None => sheet.to_string(),
+    };
+    if sheet.is_empty() {
+        return Err(invalid(reference));
+    }
+    Ok((Some(sheet), rest))
+}
+
+fn parse_cell(cell: &str) -> Option<CellRef> {
+    let (col, col_absolute, rest) = parse_col_part(cell)?;
+    let (row, row_absolute) = parse_row(rest)?;
+    Some(CellRef {
+        sheet: None,
+        row,
+        col,
+        row_absolute,
+        col_absolute,
+    })
+}
+
+/// Parses the leading `$?LETTERS` of a reference, returning the remainder.
+fn parse_col_part(text: &str) -> Option<(u32, bool, &str)> {
+    let (absolute, text) = match text.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let letters = text.bytes().take_while(u8::is_ascii_alphabetic).count();
+    if letters == 0 || letters > 3 {
+        return None;
+    }
+    let col = text[..letters].bytes().fold(0u32, |acc, b| {
+        acc * 26 + u32::from(b.to_ascii_uppercase() - b'A' + 1)
+    });
+    (col <= MAX_COLS).then(|| (col - 1, absolute, &text[letters..]))
+}
+
+fn parse_col(text: &str) -> Option<(u32, bool)> {
+    match parse_col_part(text)? {
+        (col, absolute, "") => Some((col, absolute)),
+        _ => None,
+    }
+}
+
+fn parse_row(text: &str) -> Option<(u32, bool)> {
+    let (absolute, digits) = match text.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let row = digits.parse::<u32>().ok()?;
+    (1..=MAX_ROWS).contains(&row).then(|| (row - 1, absolute))
+}
+
+fn whole_columns(first: &str, last: &str) -> Option<(CellRef, CellRef)> {
+    let (first_col, first_absolute) = parse_col(first)?;
+    let (last_col, last_absolute) = parse_col(last)?;
+    let corner = |row, col, col_absolute| CellRef {
+        col_absolute,
+        ..CellRef::new(row, col)
+    };
+    Some((
+        corner(0, first_col, first_absolute),
+        corner(MAX_ROWS - 1, last_col, last_absolute),
+    ))
+}
+
+fn whole_rows(first: &str, last: &str) -> Option<(CellRef, CellRef)> {
+    let (first_row, first_absolute) = parse_row(first)?;
+    let (last_row, last_absolute) = parse_row(last)?;
+    let corner = |row, col, row_absolute| CellRef {
+        row_absolute,
+        ..CellRef::new(row, col)
+    };
+    Some((
+        corner(first_row, 0, first_absolute),
+        corner(last_row, MAX_COLS - 1, last_absolute),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(CellRef::parse("A1").unwrap(), CellRef::new(0, 0));
+        assert_eq!(CellRef::parse("b3").unwrap(), CellRef::new(2, 1));
+
+        let cell = CellRef::parse("$B$2").unwrap();
+        assert_eq!((cell.row, cell.col), (1, 1));
+        assert!(cell.row_absolute && cell.col_absolute);
+
+        let cell = CellRef::parse("XFD$1048576").unwrap();
+        assert_eq!((cell.row, cell.col), (1_048_575, 16_383));
+        assert!(cell.row_absolute && !cell.col_absolute);
+    }
+
+    #[test]
+    fn test_parse_invalid_cell() {
+        for reference in [
+            "", "A", "1", "A0", "XFE1", "A1048577", "AAAA1", "A1B", "$$A1", "A 1", "!A1", "A1:B2",
+        ] {
+            assert!(
+                matches!(
+                    CellRef::parse(reference),
+                    Err(ExcelifyError::InvalidCellReference(_))
+                ),
+                "{}",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_sheet() {
+        let cell = CellRef::parse("Sheet Name!A1").unwrap();
+        assert_eq!(cell.sheet.as_deref(), Some("Sheet Name"));
+
+        let cell = CellRef::parse("'Bob''s Data'!$C$5").unwrap();
+        assert_eq!(cell.sheet.as_deref(), Some("Bob's Data"));
+        assert_eq!(cell.to_string(), "'Bob''s Data'!$C$5");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let range = CellRange::parse("A1:C10").unwrap();
+        assert_eq!(range.bounds(), (0, 0, 9, 2));
+        assert!(range.contains(5, 1));
+        assert!(!range.contains(10, 1));
+
+        assert_eq!(CellRange::parse("C10:A1").unwrap().bounds(), (0, 0, 9, 2));
+        assert!(CellRange::parse("B2").unwrap().is_single_cell());
+        assert_eq!(
+            CellRange::parse("'Q1 Sales'!$A$1:$B$2")
+                .unwrap()
+                .to_string(),
+            "'Q1 Sales'!$A$1:$B$2"
+        );
+    }
+
+    #[test]
+    fn test_parse_whole_rows_and_columns() {
+        let columns = CellRange::parse("A:A").unwrap();
+        assert_eq!(columns.bounds(), (0, 0, 1_048_575, 0));
+
+        let columns = CellRange::parse("$B:$D").unwrap();
+        assert_eq!(columns.bounds(), (0, 1, 1_048_575, 3));
+        assert!(columns.first.col_absolute);
+
+        let rows = CellRange::parse("Data!2:3").unwrap();
+        assert_eq!(rows.bounds(), (1, 0, 2, 16_383));
+        assert_eq!(rows.sheet.as_deref(), Some("Data"));
+
+        for reference in ["A:1", "1:A", "A1:B", ":", "A:", "0:1"] {
+            assert!(CellRange::parse(reference).is_err(), "{}", reference);
+        }
+    }
+
+    #[test]
+    fn test_shift_ranges() {
+        let insert = Shift::InsertRows { at: 2, count: 3 };
+        assert_eq!(insert.cell(1, 4), Some((1, 4)));
+        assert_eq!(insert.cell(2, 4), Some((5, 4)));
+        assert_eq!(insert.row(MAX_ROWS - 2), None);
+        assert_eq!(insert.bounds((0, 0, 4, 1)), Some((0, 0, 7, 1)));
+        assert_eq!(
+            insert.bounds((0, 0, MAX_ROWS - 1, 0)),
+            Some((0, 0, MAX_ROWS - 1, 0))
+        );
+
+        let delete = Shift::DeleteCols { at: 1, count: 2 };
+        assert_eq!(delete.cell(0, 0), Some((0, 0)));
+        assert_eq!(delete.cell(0, 2), None);
+        assert_eq!(delete.cell(0, 3), Some((0, 1)));
+        assert_eq!(delete.bounds((0, 0, 0, 5)), Some((0, 0, 0, 3)));
+        assert_eq!(delete.bounds((0, 2, 0, 5)), Some((0, 1, 0, 3)));
+        assert_eq!(delete.bounds((0, 1, 9, 2)), None);
+        assert_eq!(
+            Shift::DeleteRows { at: 0, count: 2 }.bounds((0, 0, 1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_shift_formula() {
+        let shift = Shift::InsertRows { at: 1, count: 2 };
+        let home = Some("Data");
+        assert_eq!(
+            shift.formula("=SUM($A$2:B10)*A1", "Data", home),
+            "=SUM($A$4:B12)*A1"
+        );
+        assert_eq!(
+            shift.formula("='Q1 Sales'!A5+Data!A5+Other!A5", "q1 sales", home),
+            "='Q1 Sales'!A7+Data!A5+Other!A5"
+        );
+        assert_eq!(
+            shift.formula("=COUNTIF(A:A,\"B2\")+LOG10(B2)+SUM(2:3)", "Data", home),
+            "=COUNTIF(A:A,\"B2\")+LOG10(B4)+SUM(4:5)"
+        );
+        assert_eq!(
+            shift.formula("=Total2024+1.5E3", "Data", home),
+            "=Total2024+1.5E3"
+        );
+
+        let delete = Shift::DeleteCols { at: 1, count: 1 };
+        assert_eq!(
+            delete.formula("=B1+C1+Data!B:C+A1:B1", "Data", home),
+            "=#REF!+B1+Data!B:B+A1:A1"
+        );
+        assert_eq!(delete.formula("=C1", "Data", None), "=C1");
+    }
+
+    #[test]
+    fn test_rename_sheet_references() {
+        assert_eq!(
+            rename_sheet_references("=SUM(data!$A:$A)+A1+Other!B2", "Data", Some("Q1 Sales")),
+            "=SUM('Q1 Sales'!$A:$A)+A1+Other!B2"
+        );
+        assert_eq!(
+            rename_sheet_references("='Bob''s'!A1&\"Bob's!A1\"", "Bob's", None),
+            "=#REF!&\"Bob's!A1\""
+        );
+    }
+}
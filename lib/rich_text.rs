@@ -0,0 +1,109 @@
+use crate::format::Format;
+
+/// A piece of a rich string with its own font.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextRun {
+    text: String,
+    font: Option<Format>,
+}
+
+impl TextRun {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The run's font, or `None` to use the cell's font.
+    pub fn font(&self) -> Option<&Format> {
+        self.font.as_ref()
+    }
+}
+
+/// Cell text made of runs with different fonts, such as a sentence with one
+/// bold keyword. Written as `<r>` runs inside the cell's inline string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RichString {
+    runs: Vec<TextRun>,
+}
+
+impl RichString {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends text in the cell's own font.
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.push(text.into(), None)
+    }
+
+    /// Appends text in the given font. Only the format's font properties are
+    /// used; fills, borders and number formats apply to whole cells.
+    pub fn formatted(self, text: impl Into<String>, format: Format) -> Self {
+        let font = format.has_font().then(|| format.font());
+        self.push(text.into(), font)
+    }
+
+    fn push(mut self, text: String, font: Option<Format>) -> Self {
+        if text.is_empty() {
+            return self;
+        }
+        // Excel merges adjacent runs with the same font when saving, so do
+        // the same to keep the output small.
+        match self.runs.last_mut() {
+            Some(last) if last.font == font => last.text.push_str(&text),
+            _ => self.runs.push(TextRun { text, font }),
+        }
+        self
+    }
+
+    pub fn runs(&self) -> &[TextRun] {
+        &self.runs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// The text without formatting.
+    pub fn to_plain_text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Color;
+
+    #[test]
+    fn test_runs() {
+        let bold = Format::new().bold();
+        let text = RichString::new()
+            .text("Total ")
+            .formatted("due", bold.clone())
+            .formatted(" now", bold.clone())
+            .text("");
+        assert_eq!(text.runs().len(), 2);
+        assert_eq!(text.runs()[0].font(), None);
+        assert_eq!(text.runs()[1].text(), "due now");
+        assert_eq!(text.runs()[1].font(), Some(&bold));
+        assert_eq!(text.to_plain_text(), "Total due now");
+    }
+
+    #[test]
+    fn test_formatted_keeps_font_only() {
+        let text = RichString::new()
+            .formatted(
+                "red",
+                Format::new()
+                    .font_color(Color::rgb(0xFF0000))
+                    .fill_color(Color::BLACK),
+            )
+            .formatted(" plain", Format::new().num_format("0.00"));
+        assert_eq!(
+            text.runs()[0].font(),
+            Some(&Format::new().font_color(Color::rgb(0xFF0000)))
+        );
+        assert_eq!(text.runs()[1].font(), None);
+        assert!(RichString::new().text("").is_empty());
+    }
+}
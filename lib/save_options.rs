@@ -0,0 +1,156 @@
+use zip::write::FileOptions;
+use zip::{CompressionMethod, DateTime};
+
+use crate::error::{ExcelifyError, Result};
+use crate::properties::Timestamp;
+
+/// Excel's own choice, a good balance of speed and size.
+const DEFAULT_COMPRESSION_LEVEL: u32 = 6;
+
+/// How the workbook's parts are packed into the `.xlsx` ZIP archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveOptions {
+    compression_level: u32,
+    zip64: bool,
+    timestamp: Option<Timestamp>,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            zip64: false,
+            timestamp: None,
+        }
+    }
+}
+
+impl SaveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the Deflate level from 1 (fastest) to 9 (smallest). Level 0
+    /// stores the parts uncompressed, which is quickest to write but gives
+    /// the largest files.
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Writes ZIP64 records so that parts can exceed 4 GiB. Only needed for
+    /// very large sheets; without it saving such a sheet fails.
+    pub fn zip64(mut self, zip64: bool) -> Self {
+        self.zip64 = zip64;
+        self
+    }
+
+    /// Uses a fixed time instead of the time of saving for the document's
+    /// modification date, its default creation date and the ZIP entries, so
+    /// that saving the same workbook twice gives identical files.
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn get_compression_level(&self) -> u32 {
+        self.compression_level
+    }
+
+    pub fn is_zip64(&self) -> bool {
+        self.zip64
+    }
+
+    pub fn get_timestamp(&self) -> Option<Timestamp> {
+        self.timestamp
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.compression_level > 9 {
+            return Err(ExcelifyError::InvalidSaveOptions(format!(
+                "compression level must be between 0 and 9, got {}",
+                self.compression_level
+            )));
+        }
+        self.zip_time()?;
+        Ok(())
+    }
+
+    /// The options every part of the archive is written with.
+    pub fn file_options(&self) -> Result<FileOptions> {
+        let options = match self.compression_level {
+            0 => FileOptions::default().compression_method(CompressionMethod::Stored),
+            level => FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .compression_level(Some(level as i32)),
+        };
+        Ok(options
+            .large_file(self.zip64)
+            .last_modified_time(self.zip_time()?))
+    }
+
+    /// The modification time stored for each ZIP entry. Without a timestamp
+    /// this is the format's earliest date, 1980-01-01, which also keeps the
+    /// archive itself reproducible.
+    fn zip_time(&self) -> Result<DateTime> {
+        let Some(timestamp) = self.timestamp else {
+            return Ok(DateTime::default());
+        };
+        let (year, month, day, hour, minute, second) = timestamp.to_civil();
+        u16::try_from(year)
+            .ok()
+            .and_then(|year| {
+                DateTime::from_date_and_time(
+                    year,
+                    month as u8,
+                    day as u8,
+                    hour as u8,
+                    minute as u8,
+                    second as u8,
+                )
+                .ok()
+            })
+            .ok_or_else(|| {
+                ExcelifyError::InvalidSaveOptions(format!(
+                    "timestamp {} is outside the years 1980 to 2107 that ZIP files can store",
+                    timestamp.to_w3cdtf()
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(SaveOptions::new().validate().is_ok());
+        assert!(SaveOptions::new().compression_level(0).validate().is_ok());
+        let err = SaveOptions::new()
+            .compression_level(10)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_INVALID_SAVE_OPTIONS");
+        let before_zip = Timestamp::parse("1970-01-01").unwrap();
+        assert!(SaveOptions::new().timestamp(before_zip).validate().is_err());
+    }
+
+    #[test]
+    fn test_zip_time() {
+        let options =
+            SaveOptions::new().timestamp(Timestamp::parse("2024-03-01T09:30:15Z").unwrap());
+        let time = options.zip_time().unwrap();
+        assert_eq!(
+            (
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute()
+            ),
+            (2024, 3, 1, 9, 30)
+        );
+        assert_eq!(SaveOptions::new().zip_time().unwrap().year(), 1980);
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use crate::cell::CellValue;
+use crate::format::Format;
+use crate::worksheet::RangeBounds;
+
+/// Excel computes a row's `spans` over blocks of this many rows.
+pub const SPAN_BLOCK_ROWS: u32 = 16;
+
+/// A stored cell. A cell that only carries a format holds
+/// `CellValue::Empty`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cell {
+    value: CellValue,
+    format: Option<Format>,
+}
+
+impl Cell {
+    pub fn value(&self) -> &CellValue {
+        &self.value
+    }
+
+    pub fn format(&self) -> Option<&Format> {
+        self.format.as_ref()
+    }
+
+    pub fn has_value(&self) -> bool {
+        !matches!(self.value, CellValue::Empty)
+    }
+
+    fn is_blank(&self) -> bool {
+        !self.has_value() && self.format.is_none()
+    }
+}
+
+/// The cells of a worksheet, stored row by row with each row sorted by
+/// column. Rows come out in order without sorting, and cells written left
+/// to right are appended without a search.
+#[derive(Debug, Clone, Default)]
+pub struct SheetData {
+    rows: BTreeMap<u32, Vec<(u32, Cell)>>,
+}
+
+impl SheetData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(&self, row: u32, col: u32) -> Option<&Cell> {
+        let cells = self.rows.get(&row)?;
+        let index = cells.binary_search_by_key(&col, |&(col, _)| col).ok()?;
+        Some(&cells[index].1)
+    }
+
+    /// The cells of a row, ordered by column.
+    pub fn row(&self, row: u32) -> &[(u32, Cell)] {
+        self.rows.get(&row).map_or(&[], Vec::as_slice)
+    }
+
+    /// The non-empty rows in order, each with its cells ordered by column.
+    pub fn rows(&self) -> impl Iterator<Item = (u32, &[(u32, Cell)])> {
+        self.rows
+            .iter()
+            .map(|(&row, cells)| (row, cells.as_slice()))
+    }
+
+    /// Every stored cell in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u32, &Cell)> {
+        self.rows()
+            .flat_map(|(row, cells)| cells.iter().map(move |(col, cell)| (row, *col, cell)))
+    }
+
+    /// Stores a value, keeping the cell's format. Storing
+    /// `CellValue::Empty` clears the value.
+    pub fn set_value(&mut self, row: u32, col: u32, value: CellValue) {
+        if matches!(value, CellValue::Empty) {
+            self.clear_value(row, col);
+        } else {
+            self.entry(row, col).value = value;
+        }
+    }
+
+    pub fn set_format(&mut self, row: u32, col: u32, format: Format) {
+        self.entry(row, col).format = Some(format);
+    }
+
+    /// Removes a cell's value, keeping its format. Returns whether there was
+    /// a value to remove.
+    pub fn clear_value(&mut self, row: u32, col: u32) -> bool {
+        let mut cleared = false;
+        self.update_range((row, col, row, col), |cell| {
+            cleared = cell.has_value();
+            cell.value = CellValue::Empty;
+        });
+        cleared
+    }
+
+    /// Removes the values in a range, keeping their formats. Returns whether
+    /// any value was removed.
+    pub fn clear_values(&mut self, bounds: RangeBounds) -> bool {
+        let mut cleared = false;
+        self.update_range(bounds, |cell| {
+            cleared |= cell.has_value();
+            cell.value = CellValue::Empty;
+        });
+        cleared
+    }
+
+    pub fn clear_formats(&mut self, bounds: RangeBounds) {
+        self.update_range(bounds, |cell| cell.format = None);
+    }
+
+    /// Moves every cell to the position `f` returns for it, dropping the
+    /// cells it returns `None` for. `f` must keep cells in the same order.
+    pub fn remap(&mut self, f: impl Fn(u32, u32) -> Option<(u32, u32)>) {
+        let mut remapped = SheetData::new();
+        for (row, cells) in std::mem::take(&mut self.rows) {
+            for (col, cell) in cells {
+                if let Some((row, col)) = f(row, col) {
+                    *remapped.entry(row, col) = cell;
+                }
+            }
+        }
+        *self = remapped;
+    }
+
+    /// The `spans` attribute for a row: the first and last column,
+    /// one-based, of the cells `keep` accepts across the block of rows it
+    /// belongs to.
+    pub fn spans(&self, row: u32, keep: impl Fn(u32, &Cell) -> bool) -> Option<(u32, u32)> {
+        let first_row = row - row % SPAN_BLOCK_ROWS;
+        let last_row = first_row.saturating_add(SPAN_BLOCK_ROWS - 1);
+        self.rows
+            .range(first_row..=last_row)
+            .filter_map(|(_, cells)| kept_columns(cells, &keep))
+            .reduce(|(first, last), (row_first, row_last)| {
+                (first.min(row_first), last.max(row_last))
+            })
+            .map(|(first, last)| (first + 1, last + 1))
+    }
+
+    /// Bounds of the cells `keep` accepts.
+    pub fn bounds(&self, keep: impl Fn(u32, &Cell) -> bool) -> Option<RangeBounds> {
+        self.rows
+            .iter()
+            .filter_map(|(&row, cells)| {
+                let (first_col, last_col) = kept_columns(cells, &keep)?;
+                Some((row, first_col, row, last_col))
+            })
+            .reduce(
+                |(first_row, first_col, _, last_col), (_, row_first, row, row_last)| {
+                    (
+                        first_row,
+                        first_col.min(row_first),
+                        row,
+                        last_col.max(row_last),
+                    )
+                },
+            )
+    }
+
+    fn entry(&mut self, row: u32, col: u32) -> &mut Cell {
+        let cells = self.rows.entry(row).or_default();
+        let index = match cells.last() {
+            Some(&(last, _)) if last >= col => {
+                match cells.binary_search_by_key(&col, |&(col, _)| col) {
+                    Ok(index) => index,
+                    Err(index) => {
+                        cells.insert(index, (col, Cell::default()));
+                        index
+                    }
+                }
+            }
+            _ => {
+                cells.push((col, Cell::default()));
+                cells.len() - 1
+            }
+        };
+        &mut cells[index].1
+    }
+
+    /// Applies `f` to the stored cells in a range, then drops the cells and
+    /// rows left blank.
+    fn update_range(&mut self, bounds: RangeBounds, mut f: impl FnMut(&mut Cell)) {
+        let (first_row, first_col, last_row, last_col) = bounds;
+        let mut emptied = Vec::new();
+        for (&row, cells) in self.rows.range_mut(first_row..=last_row) {
+            let start = cells.partition_point(|&(col, _)| col < first_col);
+            let end = cells.partition_point(|&(col, _)| col <= last_col);
+            if start == end {
+                continue;
+            }
+            for (_, cell) in &mut cells[start..end] {
+                f(cell);
+            }
+            let mut index = 0;
+            cells.retain(|(_, cell)| {
+                let keep = !(start..end).contains(&index) || !cell.is_blank();
+                index += 1;
+                keep
+            });
+            if cells.is_empty() {
+                emptied.push(row);
+            }
+        }
+        for row in emptied {
+            self.rows.remove(&row);
+        }
+    }
+}
+
+/// The first and last column of a row's cells that `keep` accepts.
+fn kept_columns(cells: &[(u32, Cell)], keep: impl Fn(u32, &Cell) -> bool) -> Option<(u32, u32)> {
+    let (first, _) = cells.iter().find(|(col, cell)| keep(*col, cell))?;
+    let (last, _) = cells.iter().rev().find(|(col, cell)| keep(*col, cell))?;
+    Some((*first, *last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_stay_sorted() {
+        let mut data = SheetData::new();
+        data.set_value(2, 0, CellValue::Number(3.0));
+        data.set_value(0, 5, CellValue::Number(2.0));
+        data.set_value(0, 1, CellValue::Number(1.0));
+        data.set_format(0, 3, Format::new().bold());
+        data.set_value(0, 5, CellValue::Number(4.0));
+
+        let positions: Vec<_> = data.iter().map(|(row, col, _)| (row, col)).collect();
+        assert_eq!(positions, vec![(0, 1), (0, 3), (0, 5), (2, 0)]);
+        assert_eq!(data.get(0, 5).unwrap().value(), &CellValue::Number(4.0));
+        assert!(data.get(0, 3).unwrap().format().is_some());
+        assert_eq!(data.bounds(|_, _| true), Some((0, 0, 2, 5)));
+        assert_eq!(data.bounds(|col, _| col != 0), Some((0, 1, 0, 5)));
+    }
+
+    #[test]
+    fn test_clearing_drops_blank_cells() {
+        let mut data = SheetData::new();
+        data.set_value(0, 0, CellValue::Boolean(true));
+        data.set_value(0, 1, CellValue::Boolean(true));
+        data.set_format(0, 1, Format::new().italic());
+        data.set_value(1, 0, CellValue::Boolean(true));
+
+        assert!(data.clear_values((0, 0, 1, 1)));
+        assert!(!data.clear_value(0, 0));
+        let positions: Vec<_> = data.iter().map(|(row, col, _)| (row, col)).collect();
+        assert_eq!(positions, vec![(0, 1)]);
+
+        data.clear_formats((0, 0, 0, 5));
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_spans_cover_row_blocks() {
+        let mut data = SheetData::new();
+        data.set_value(0, 2, CellValue::Number(1.0));
+        data.set_value(15, 7, CellValue::Number(1.0));
+        data.set_value(16, 0, CellValue::Number(1.0));
+        data.set_format(15, 9, Format::new().bold());
+        let has_value = |_: u32, cell: &Cell| cell.has_value();
+        assert_eq!(data.spans(0, has_value), Some((3, 8)));
+        assert_eq!(data.spans(15, has_value), Some((3, 8)));
+        assert_eq!(data.spans(15, |_, _| true), Some((3, 10)));
+        assert_eq!(data.spans(16, has_value), Some((1, 1)));
+        assert_eq!(data.spans(40, has_value), None);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::error::{ExcelifyError, Result};
+use crate::worksheet::{check_cell, RangeBounds};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SheetVisibility {
+    #[default]
+    Visible,
+    /// Hidden, but listed in Excel's Unhide dialog.
+    Hidden,
+    /// Hidden and only visible again through VBA.
+    VeryHidden,
+}
+
+impl SheetVisibility {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "visible" => Some(SheetVisibility::Visible),
+            "hidden" => Some(SheetVisibility::Hidden),
+            "veryHidden" => Some(SheetVisibility::VeryHidden),
+            _ => None,
+        }
+    }
+
+    /// The `state` attribute on the workbook's `<sheet>` element, if any.
+    pub fn as_state(&self) -> Option<&'static str> {
+        match self {
+            SheetVisibility::Visible => None,
+            SheetVisibility::Hidden => Some("hidden"),
+            SheetVisibility::VeryHidden => Some("veryHidden"),
+        }
+    }
+}
+
+/// How a worksheet is displayed when the file is opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetView {
+    zoom: u32,
+    show_gridlines: bool,
+    show_headings: bool,
+    right_to_left: bool,
+    selection: Option<RangeBounds>,
+}
+
+impl Default for SheetView {
+    fn default() -> Self {
+        Self {
+            zoom: 100,
+            show_gridlines: true,
+            show_headings: true,
+            right_to_left: false,
+            selection: None,
+        }
+    }
+}
+
+impl SheetView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the zoom level in percent (10-400).
+    pub fn zoom(mut self, zoom: u32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn show_gridlines(mut self, show: bool) -> Self {
+        self.show_gridlines = show;
+        self
+    }
+
+    /// Shows the row numbers and column letters.
+    pub fn show_headings(mut self, show: bool) -> Self {
+        self.show_headings = show;
+        self
+    }
+
+    /// Lays the sheet out from right to left, for right-to-left languages.
+    pub fn right_to_left(mut self, rtl: bool) -> Self {
+        self.right_to_left = rtl;
+        self
+    }
+
+    /// Selects a range; its top-left cell becomes the active cell.
+    pub fn selection(
+        mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+    ) -> Self {
+        self.selection = Some((
+            first_row.min(last_row),
+            first_col.min(last_col),
+            first_row.max(last_row),
+            first_col.max(last_col),
+        ));
+        self
+    }
+
+    pub fn get_zoom(&self) -> u32 {
+        self.zoom
+    }
+
+    pub fn has_gridlines(&self) -> bool {
+        self.show_gridlines
+    }
+
+    pub fn has_headings(&self) -> bool {
+        self.show_headings
+    }
+
+    pub fn is_right_to_left(&self) -> bool {
+        self.right_to_left
+    }
+
+    pub fn get_selection(&self) -> Option<RangeBounds> {
+        self.selection
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(10..=400).contains(&self.zoom) {
+            return Err(ExcelifyError::InvalidSheetView(format!(
+                "zoom must be between 10 and 400 percent, got {}",
+                self.zoom
+            )));
+        }
+        // The selection is normalized, so its last cell is its furthest.
+        if let Some((_, _, last_row, last_col)) = self.selection {
+            check_cell(last_row, last_col)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility_names() {
+        assert_eq!(
+            SheetVisibility::from_name("veryHidden"),
+            Some(SheetVisibility::VeryHidden)
+        );
+        assert_eq!(SheetVisibility::from_name("shown"), None);
+        assert_eq!(SheetVisibility::Visible.as_state(), None);
+        assert_eq!(SheetVisibility::Hidden.as_state(), Some("hidden"));
+    }
+
+    #[test]
+    fn test_view() {
+        let view = SheetView::new().zoom(150).selection(4, 3, 1, 1);
+        assert_eq!(view.get_selection(), Some((1, 1, 4, 3)));
+        assert!(view.validate().is_ok());
+        assert!(SheetView::new().zoom(5).validate().is_err());
+        let err = SheetView::new()
+            .selection(0, 0, 2_000_000, 0)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_ROW_OUT_OF_RANGE");
+        let err = SheetView::new()
+            .selection(0, 16_384, 0, 0)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_COLUMN_OUT_OF_RANGE");
+        assert!(SheetView::new().has_gridlines());
+    }
+}
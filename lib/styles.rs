@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::format::{Color, Format};
+use crate::workbook::Workbook;
+
+/// Number format ids below this are built into Excel.
+pub const FIRST_CUSTOM_NUM_FORMAT_ID: usize = 164;
+
+/// Distinct records in the order they were first added, with a hash index
+/// so that looking one up doesn't scan the list.
+#[derive(Debug)]
+struct Records<T> {
+    items: Vec<T>,
+    index: HashMap<T, usize>,
+}
+
+impl<T> Default for Records<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> Records<T> {
+    fn with_default(item: T) -> Self {
+        let mut records = Self::default();
+        records.add(&item);
+        records
+    }
+
+    /// Adds `item` unless an equal record exists. Returns whether it was new.
+    fn add(&mut self, item: &T) -> bool {
+        if self.index.contains_key(item) {
+            return false;
+        }
+        self.index.insert(item.clone(), self.items.len());
+        self.items.push(item.clone());
+        true
+    }
+
+    fn position(&self, item: &T) -> Option<usize> {
+        self.index.get(item).copied()
+    }
+}
+
+/// Style records shared by every worksheet, collected once per save so that
+/// identical formats map to a single entry in `styles.xml`.
+#[derive(Debug)]
+pub struct Styles {
+    xfs: Records<Format>,
+    fonts: Records<Format>,
+    fills: Records<Color>,
+    borders: Records<Color>,
+    dxfs: Records<Format>,
+    num_formats: Records<String>,
+}
+
+impl Default for Styles {
+    /// Index 0 of the cell formats and fonts is Excel's default style.
+    fn default() -> Self {
+        Self {
+            xfs: Records::with_default(Format::default()),
+            fonts: Records::with_default(Format::default()),
+            fills: Records::default(),
+            borders: Records::default(),
+            dxfs: Records::default(),
+            num_formats: Records::default(),
+        }
+    }
+}
+
+impl Styles {
+    pub fn from_workbook(workbook: &Workbook) -> Self {
+        let mut styles = Styles::default();
+        for worksheet in workbook.worksheets() {
+            // Columns come before cells and cells come in row-major order, so
+            // style ids follow the sheet.
+            for (_, _, format) in worksheet.column_format_spans() {
+                styles.add_xf(format);
+            }
+            for (_, format) in worksheet.formats() {
+                styles.add_xf(format);
+            }
+        }
+        for worksheet in workbook.worksheets() {
+            for (_, rule) in worksheet.conditional_formats() {
+                if let Some(format) = rule.format() {
+                    styles.add_dxf(format);
+                }
+            }
+        }
+        styles
+    }
+
+    fn add_xf(&mut self, format: &Format) {
+        if !self.xfs.add(format) {
+            return;
+        }
+        self.fonts.add(&format.font());
+        if let Some(color) = format.get_fill_color() {
+            self.fills.add(&color);
+        }
+        if let Some(color) = format.get_border_color() {
+            self.borders.add(&color);
+        }
+        if let Some(code) = format.get_num_format() {
+            self.num_formats.add(&code.to_string());
+        }
+    }
+
+    fn add_dxf(&mut self, format: &Format) {
+        if self.dxfs.add(format) {
+            if let Some(code) = format.get_num_format() {
+                self.num_formats.add(&code.to_string());
+            }
+        }
+    }
+
+    /// Cell formats, in `cellXfs` order.
+    pub fn xfs(&self) -> &[Format] {
+        &self.xfs.items
+    }
+
+    /// The `s` attribute for a cell with `format`.
+    pub fn xf_id(&self, format: &Format) -> Option<usize> {
+        self.xfs.position(format)
+    }
+
+    /// Distinct fonts, as formats carrying only font properties.
+    pub fn fonts(&self) -> &[Format] {
+        &self.fonts.items
+    }
+
+    pub fn font_id(&self, format: &Format) -> usize {
+        self.fonts.position(&format.font()).unwrap_or(0)
+    }
+
+    /// Solid fills after the two that Excel reserves.
+    pub fn fills(&self) -> &[Color] {
+        &self.fills.items
+    }
+
+    pub fn fill_id(&self, format: &Format) -> usize {
+        format
+            .get_fill_color()
+            .and_then(|color| self.fills.position(&color))
+            .map_or(0, |idx| idx + 2)
+    }
+
+    /// Thin borders after the empty default border.
+    pub fn borders(&self) -> &[Color] {
+        &self.borders.items
+    }
+
+    pub fn border_id(&self, format: &Format) -> usize {
+        format
+            .get_border_color()
+            .and_then(|color| self.borders.position(&color))
+            .map_or(0, |idx| idx + 1)
+    }
+
+    pub fn dxfs(&self) -> &[Format] {
+        &self.dxfs.items
+    }
+
+    pub fn dxf_id(&self, format: &Format) -> Option<usize> {
+        self.dxfs.position(format)
+    }
+
+    pub fn num_formats(&self) -> &[String] {
+        &self.num_formats.items
+    }
+
+    pub fn num_format_id(&self, code: &str) -> Option<usize> {
+        self.num_formats
+            .index
+            .get(code)
+            .map(|idx| idx + FIRST_CUSTOM_NUM_FORMAT_ID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditional_format::ConditionalFormat;
+
+    #[test]
+    fn test_dxfs_are_deduplicated() {
+        let red = Format::new().font_color(Color::rgb(0x9C0006));
+        let money = Format::new().num_format("$#,##0.00");
+
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.add_worksheet("Sheet2").unwrap();
+        for idx in 0..2 {
+            let ws = wb.get_worksheet_mut(idx).unwrap();
+            for format in [&red, &money] {
+                let rule = ConditionalFormat::Formula {
+                    formula: "A1>0".into(),
+                    format: format.clone(),
+                };
+                ws.add_conditional_format(0, 0, 0, 0, rule).unwrap();
+            }
+        }
+
+        let styles = Styles::from_workbook(&wb);
+        assert_eq!(styles.dxfs().len(), 2);
+        assert_eq!(styles.dxf_id(&money), Some(1));
+        assert_eq!(styles.num_format_id("$#,##0.00"), Some(164));
+        assert_eq!(styles.num_format_id("0.0"), None);
+    }
+
+    #[test]
+    fn test_cell_formats_share_records() {
+        let header = Format::new().bold().fill_color(Color::rgb(0xDDEBF7));
+        let input = Format::new().locked(false);
+        let bold_input = Format::new().bold().locked(false);
+
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.set_format(0, 0, header.clone()).unwrap();
+        ws.set_format(0, 1, header.clone()).unwrap();
+        ws.set_format(1, 0, input.clone()).unwrap();
+        ws.set_format(2, 0, bold_input.clone()).unwrap();
+
+        let styles = Styles::from_workbook(&wb);
+        assert_eq!(styles.xfs().len(), 4);
+        assert_eq!(styles.xf_id(&header), Some(1));
+        assert_eq!(styles.xf_id(&Format::new()), Some(0));
+        // The header and the bold input cells share a font.
+        assert_eq!(styles.fonts().len(), 2);
+        assert_eq!(styles.font_id(&bold_input), 1);
+        assert_eq!(styles.font_id(&input), 0);
+        assert_eq!(styles.fill_id(&header), 2);
+        assert_eq!(styles.border_id(&header), 0);
+    }
+}
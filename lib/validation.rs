@@ -0,0 +1,350 @@
+use crate::cell::format_number;
+use crate::error::{ExcelifyError, Result};
+
+/// Excel rejects literal list sources longer than this.
+const MAX_LIST_LENGTH: usize = 255;
+const MAX_TITLE_LENGTH: usize = 32;
+const MAX_MESSAGE_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationCriteria {
+    Between(f64, f64),
+    NotBetween(f64, f64),
+    EqualTo(f64),
+    NotEqualTo(f64),
+    GreaterThan(f64),
+    LessThan(f64),
+    GreaterThanOrEqualTo(f64),
+    LessThanOrEqualTo(f64),
+}
+
+impl ValidationCriteria {
+    pub fn operator(&self) -> &'static str {
+        match self {
+            ValidationCriteria::Between(_, _) => "between",
+            ValidationCriteria::NotBetween(_, _) => "notBetween",
+            ValidationCriteria::EqualTo(_) => "equal",
+            ValidationCriteria::NotEqualTo(_) => "notEqual",
+            ValidationCriteria::GreaterThan(_) => "greaterThan",
+            ValidationCriteria::LessThan(_) => "lessThan",
+            ValidationCriteria::GreaterThanOrEqualTo(_) => "greaterThanOrEqual",
+            ValidationCriteria::LessThanOrEqualTo(_) => "lessThanOrEqual",
+        }
+    }
+
+    pub fn values(&self) -> (f64, Option<f64>) {
+        match *self {
+            ValidationCriteria::Between(min, max) | ValidationCriteria::NotBetween(min, max) => {
+                (min, Some(max))
+            }
+            ValidationCriteria::EqualTo(v)
+            | ValidationCriteria::NotEqualTo(v)
+            | ValidationCriteria::GreaterThan(v)
+            | ValidationCriteria::LessThan(v)
+            | ValidationCriteria::GreaterThanOrEqualTo(v)
+            | ValidationCriteria::LessThanOrEqualTo(v) => (v, None),
+        }
+    }
+}
+
+/// What a validated cell is allowed to contain. Date bounds are Excel serial
+/// date numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationRule {
+    List(Vec<String>),
+    ListRange(String),
+    WholeNumber(ValidationCriteria),
+    Decimal(ValidationCriteria),
+    Date(ValidationCriteria),
+    TextLength(ValidationCriteria),
+    Custom(String),
+}
+
+impl ValidationRule {
+    pub fn validation_type(&self) -> &'static str {
+        match self {
+            ValidationRule::List(_) | ValidationRule::ListRange(_) => "list",
+            ValidationRule::WholeNumber(_) => "whole",
+            ValidationRule::Decimal(_) => "decimal",
+            ValidationRule::Date(_) => "date",
+            ValidationRule::TextLength(_) => "textLength",
+            ValidationRule::Custom(_) => "custom",
+        }
+    }
+
+    pub fn criteria(&self) -> Option<&ValidationCriteria> {
+        match self {
+            ValidationRule::WholeNumber(c)
+            | ValidationRule::Decimal(c)
+            | ValidationRule::Date(c)
+            | ValidationRule::TextLength(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Returns the `formula1` and optional `formula2` contents.
+    pub fn formulas(&self) -> (String, Option<String>) {
+        match self {
+            ValidationRule::List(values) => {
+                let joined = values.join(",").replace('"', "\"\"");
+                (format!("\"{}\"", joined), None)
+            }
+            ValidationRule::ListRange(range) | ValidationRule::Custom(range) => {
+                (strip_formula_prefix(range).to_string(), None)
+            }
+            _ => {
+                let (first, second) = self.criteria().map(|c| c.values()).unwrap_or((0.0, None));
+                (format_number(first), second.map(format_number))
+            }
+        }
+    }
+}
+
+fn strip_formula_prefix(formula: &str) -> &str {
+    formula.strip_prefix('=').unwrap_or(formula)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationErrorStyle {
+    #[default]
+    Stop,
+    Warning,
+    Information,
+}
+
+impl ValidationErrorStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationErrorStyle::Stop => "stop",
+            ValidationErrorStyle::Warning => "warning",
+            ValidationErrorStyle::Information => "information",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataValidation {
+    rule: ValidationRule,
+    ignore_blank: bool,
+    show_dropdown: bool,
+    input_title: Option<String>,
+    input_message: Option<String>,
+    error_style: ValidationErrorStyle,
+    error_title: Option<String>,
+    error_message: Option<String>,
+}
+
+impl DataValidation {
+    pub fn new(rule: ValidationRule) -> Self {
+        Self {
+            rule,
+            ignore_blank: true,
+            show_dropdown: true,
+            input_title: None,
+            input_message: None,
+            error_style: ValidationErrorStyle::default(),
+            error_title: None,
+            error_message: None,
+        }
+    }
+
+    pub fn ignore_blank(mut self, ignore: bool) -> Self {
+        self.ignore_blank = ignore;
+        self
+    }
+
+    pub fn show_dropdown(mut self, show: bool) -> Self {
+        self.show_dropdown = show;
+        self
+    }
+
+    pub fn input_prompt(mut self, title: impl Into<String>, message: impl Into<String>) -> Self {
+        self.input_title = Some(title.into());
+        self.input_message = Some(message.into());
+        self
+    }
+
+    pub fn error_alert(
+        mut self,
+        style: ValidationErrorStyle,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.error_style = style;
+        self.error_title = Some(title.into());
+        self.error_message = Some(message.into());
+        self
+    }
+
+    pub fn rule(&self) -> &ValidationRule {
+        &self.rule
+    }
+
+    pub fn is_ignore_blank(&self) -> bool {
+        self.ignore_blank
+    }
+
+    pub fn is_show_dropdown(&self) -> bool {
+        self.show_dropdown
+    }
+
+    pub fn input_title(&self) -> Option<&str> {
+        self.input_title.as_deref()
+    }
+
+    pub fn input_message(&self) -> Option<&str> {
+        self.input_message.as_deref()
+    }
+
+    pub fn error_style(&self) -> ValidationErrorStyle {
+        self.error_style
+    }
+
+    pub fn error_title(&self) -> Option<&str> {
+        self.error_title.as_deref()
+    }
+
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+
+    /// Replaces the rule's formulas, e.g. to move references after rows
+    /// or columns were inserted.
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        if let ValidationRule::ListRange(formula) | ValidationRule::Custom(formula) = &mut self.rule
+        {
+            *formula = rewrite(formula);
+        }
+    }
+
+    /// Checks the limits Excel enforces when opening the file.
+    pub fn validate(&self) -> Result<()> {
+        if let ValidationRule::List(values) = &self.rule {
+            if values.is_empty() {
+                return Err(invalid("list validation requires at least one value"));
+            }
+            // Excel splits the list source on commas and has no way to escape
+            // them, so such a value would turn into several entries.
+            if let Some(value) = values.iter().find(|v| v.contains(',')) {
+                return Err(invalid(format!(
+                    "list value {:?} contains a comma; use a range source instead",
+                    value
+                )));
+            }
+            let length = values.iter().map(|v| v.chars().count()).sum::<usize>() + values.len() - 1;
+            if length > MAX_LIST_LENGTH {
+                return Err(invalid(format!(
+                    "list values exceed {} characters; use a range source instead",
+                    MAX_LIST_LENGTH
+                )));
+            }
+        }
+
+        if let Some(criteria) = self.rule.criteria() {
+            let (first, second) = criteria.values();
+            if !first.is_finite() || second.is_some_and(|v| !v.is_finite()) {
+                return Err(invalid("criteria values must be finite numbers"));
+            }
+        }
+
+        if let Some(
+            ValidationCriteria::Between(min, max) | ValidationCriteria::NotBetween(min, max),
+        ) = self.rule.criteria()
+        {
+            if min > max {
+                return Err(invalid(format!(
+                    "minimum {} is greater than maximum {}",
+                    min, max
+                )));
+            }
+        }
+
+        for title in [&self.input_title, &self.error_title].into_iter().flatten() {
+            if title.chars().count() > MAX_TITLE_LENGTH {
+                return Err(invalid(format!(
+                    "title exceeds {} characters",
+                    MAX_TITLE_LENGTH
+                )));
+            }
+        }
+
+        for message in [&self.input_message, &self.error_message]
+            .into_iter()
+            .flatten()
+        {
+            if message.chars().count() > MAX_MESSAGE_LENGTH {
+                return Err(invalid(format!(
+                    "message exceeds {} characters",
+                    MAX_MESSAGE_LENGTH
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> ExcelifyError {
+    ExcelifyError::InvalidDataValidation(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_formula() {
+        let rule = ValidationRule::List(vec!["Yes".into(), "No".into()]);
+        assert_eq!(rule.validation_type(), "list");
+        assert_eq!(rule.formulas(), ("\"Yes,No\"".to_string(), None));
+    }
+
+    #[test]
+    fn test_list_range_formula() {
+        let rule = ValidationRule::ListRange("=$A$1:$A$5".into());
+        assert_eq!(rule.formulas(), ("$A$1:$A$5".to_string(), None));
+    }
+
+    #[test]
+    fn test_criteria_formulas() {
+        let rule = ValidationRule::WholeNumber(ValidationCriteria::Between(1.0, 10.0));
+        assert_eq!(rule.formulas(), ("1".to_string(), Some("10".to_string())));
+        assert_eq!(rule.criteria().unwrap().operator(), "between");
+
+        let rule = ValidationRule::TextLength(ValidationCriteria::LessThanOrEqualTo(20.0));
+        assert_eq!(rule.validation_type(), "textLength");
+        assert_eq!(rule.formulas(), ("20".to_string(), None));
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let long = vec!["x".repeat(200), "y".repeat(60)];
+        assert!(DataValidation::new(ValidationRule::List(long))
+            .validate()
+            .is_err());
+        assert!(DataValidation::new(ValidationRule::List(vec![]))
+            .validate()
+            .is_err());
+
+        let comma = ValidationRule::List(vec!["Smith, John".into(), "Doe".into()]);
+        let err = DataValidation::new(comma).validate().unwrap_err();
+        assert_eq!(err.code(), "ERR_INVALID_DATA_VALIDATION");
+
+        let inverted = ValidationRule::Decimal(ValidationCriteria::Between(5.0, 1.0));
+        assert!(DataValidation::new(inverted).validate().is_err());
+
+        let nan = ValidationRule::Decimal(ValidationCriteria::GreaterThan(f64::NAN));
+        assert!(DataValidation::new(nan).validate().is_err());
+
+        let prompt = DataValidation::new(ValidationRule::Custom("=A1>0".into()))
+            .input_prompt("t".repeat(33), "message");
+        assert!(prompt.validate().is_err());
+
+        let ok = DataValidation::new(ValidationRule::List(vec!["a".into()])).error_alert(
+            ValidationErrorStyle::Warning,
+            "Oops",
+            "Pick a value",
+        );
+        assert!(ok.validate().is_ok());
+    }
+}
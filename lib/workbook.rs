@@ -1,28 +1,229 @@
 use std::path::Path;
 
-use crate::cell::CellValue;
+use crate::cell::{CellValue, NonFinitePolicy};
+use crate::defined_name::DefinedName;
 use crate::error::{ExcelifyError, Result};
-use crate::worksheet::Worksheet;
+use crate::properties::{validate_custom_name, CustomProperty, DocumentProperties};
+use crate::protection::PasswordHash;
+use crate::reference::{rename_sheet_references, Shift};
+use crate::save_options::SaveOptions;
+use crate::sheet_view::SheetVisibility;
+use crate::worksheet::{
+    sanitize_sheet_name, validate_sheet_name, Worksheet, MAX_SHEET_NAME_LENGTH,
+};
 use crate::writer::XlsxWriter;
 
 #[derive(Debug)]
 pub struct Workbook {
     worksheets: Vec<Worksheet>,
+    /// Stable identities for `worksheets`, in the same order.
+    sheet_ids: Vec<u32>,
+    next_sheet_id: u32,
+    defined_names: Vec<DefinedName>,
+    structure_protected: bool,
+    structure_password: Option<PasswordHash>,
+    properties: DocumentProperties,
+    custom_properties: Vec<(String, CustomProperty)>,
+    active_sheet: usize,
+    non_finite_policy: NonFinitePolicy,
 }
 
 impl Workbook {
     pub fn new() -> Self {
         Self {
             worksheets: Vec::new(),
+            sheet_ids: Vec::new(),
+            next_sheet_id: 1,
+            defined_names: Vec::new(),
+            structure_protected: false,
+            structure_password: None,
+            properties: DocumentProperties::default(),
+            custom_properties: Vec::new(),
+            active_sheet: 0,
+            non_finite_policy: NonFinitePolicy::default(),
         }
     }
 
-    pub fn add_worksheet(&mut self, name: impl Into<String>) -> usize {
-        let ws = Worksheet::new(name);
-        self.worksheets.push(ws);
+    /// Adds a worksheet, rejecting names Excel can't open: empty, longer
+    /// than 31 characters, containing `[]:*?/\\`, or already in use.
+    pub fn add_worksheet(&mut self, name: impl Into<String>) -> Result<usize> {
+        let name = name.into();
+        self.check_new_name(&name, None)?;
+        self.push_worksheet(name);
+        Ok(self.worksheets.len() - 1)
+    }
+
+    /// Checks that `name` is valid and not used by a sheet other than
+    /// `except`.
+    fn check_new_name(&self, name: &str, except: Option<usize>) -> Result<()> {
+        validate_sheet_name(name)?;
+        if self
+            .worksheet_index_by_name(name)
+            .is_some_and(|index| Some(index) != except)
+        {
+            return Err(ExcelifyError::InvalidSheetName(format!(
+                "{:?}: a worksheet with this name already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Adds a worksheet after turning `name` into a valid, unique name.
+    /// Duplicates get a numeric suffix, e.g. `Sales (2)`.
+    pub fn add_worksheet_sanitized(&mut self, name: &str) -> usize {
+        let base = sanitize_sheet_name(name);
+        let mut candidate = base.clone();
+        let mut counter = 2;
+        while self.has_worksheet_named(&candidate) {
+            let suffix = format!(" ({})", counter);
+            let keep = MAX_SHEET_NAME_LENGTH - suffix.chars().count();
+            let stem: String = base.chars().take(keep).collect();
+            candidate = format!("{}{}", stem.trim_end_matches('\''), suffix);
+            counter += 1;
+        }
+        self.push_worksheet(candidate);
         self.worksheets.len() - 1
     }
 
+    fn push_worksheet(&mut self, name: String) {
+        let mut worksheet = Worksheet::new(name);
+        worksheet.set_non_finite_policy(self.non_finite_policy);
+        self.push_with_id(worksheet);
+    }
+
+    fn push_with_id(&mut self, worksheet: Worksheet) {
+        self.worksheets.push(worksheet);
+        self.sheet_ids.push(self.next_sheet_id);
+        self.next_sheet_id += 1;
+    }
+
+    /// Removes a worksheet and returns it. Names scoped to the sheet are
+    /// removed as well, and references to it elsewhere become `#REF!`.
+    pub fn remove_worksheet(&mut self, index: usize) -> Result<Worksheet> {
+        self.check_index(index)?;
+        let worksheet = self.worksheets.remove(index);
+        self.sheet_ids.remove(index);
+
+        self.defined_names
+            .retain(|name| name.sheet_scope() != Some(index));
+        self.remap_scopes(|scope| if scope > index { scope - 1 } else { scope });
+        // The next sheet becomes active, or the previous one if the last
+        // sheet was removed.
+        if self.active_sheet > index || self.active_sheet >= self.worksheets.len() {
+            self.active_sheet = self.active_sheet.saturating_sub(1);
+        }
+        self.rename_references(worksheet.name(), None);
+        Ok(worksheet)
+    }
+
+    /// Renames a worksheet and updates the references to it in formulas,
+    /// chart series and defined names.
+    pub fn rename_worksheet(&mut self, index: usize, name: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        self.check_index(index)?;
+        self.check_new_name(&name, Some(index))?;
+        let old = self.worksheets[index].name().to_string();
+        self.worksheets[index].set_name(name.as_str());
+        self.rename_references(&old, Some(&name));
+        Ok(())
+    }
+
+    /// Moves a worksheet to position `to`, shifting the sheets in between.
+    pub fn move_worksheet(&mut self, from: usize, to: usize) -> Result<()> {
+        self.check_index(from)?;
+        self.check_index(to)?;
+        let worksheet = self.worksheets.remove(from);
+        self.worksheets.insert(to, worksheet);
+        let id = self.sheet_ids.remove(from);
+        self.sheet_ids.insert(to, id);
+
+        self.remap_scopes(|index| moved_index(index, from, to));
+        self.active_sheet = moved_index(self.active_sheet, from, to);
+        Ok(())
+    }
+
+    /// Appends a copy of a worksheet under a new name. Names scoped to the
+    /// original sheet are copied to the new one.
+    pub fn copy_worksheet(&mut self, index: usize, name: impl Into<String>) -> Result<usize> {
+        let name = name.into();
+        self.check_index(index)?;
+        self.check_new_name(&name, None)?;
+        let mut copy = self.worksheets[index].clone();
+        copy.set_name(name);
+        copy.set_selected(false);
+        self.push_with_id(copy);
+
+        let new_index = self.worksheets.len() - 1;
+        let scoped: Vec<DefinedName> = self
+            .defined_names
+            .iter()
+            .filter(|name| name.sheet_scope() == Some(index))
+            .map(|name| name.clone().scope(new_index))
+            .collect();
+        self.defined_names.extend(scoped);
+        Ok(new_index)
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.worksheets.len() {
+            return Err(ExcelifyError::SheetNotFound(index));
+        }
+        Ok(())
+    }
+
+    fn remap_scopes(&mut self, remap: impl Fn(usize) -> usize) {
+        for name in &mut self.defined_names {
+            if let Some(scope) = name.sheet_scope() {
+                *name = name.clone().scope(remap(scope));
+            }
+        }
+    }
+
+    fn rename_references(&mut self, old: &str, new: Option<&str>) {
+        let mut rewrite = |formula: &str| rename_sheet_references(formula, old, new);
+        for worksheet in &mut self.worksheets {
+            worksheet.rewrite_formulas(&mut rewrite);
+        }
+        for name in &mut self.defined_names {
+            name.rewrite_formulas(&mut rewrite);
+        }
+    }
+
+    /// Sets how NaN and infinite numbers are written, for existing and
+    /// future worksheets.
+    pub fn set_non_finite_policy(&mut self, policy: NonFinitePolicy) {
+        self.non_finite_policy = policy;
+        for worksheet in &mut self.worksheets {
+            worksheet.set_non_finite_policy(policy);
+        }
+    }
+
+    fn has_worksheet_named(&self, name: &str) -> bool {
+        self.worksheet_index_by_name(name).is_some()
+    }
+
+    /// Finds a worksheet by name. Excel compares sheet names
+    /// case-insensitively.
+    pub fn worksheet_index_by_name(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        self.worksheets
+            .iter()
+            .position(|ws| ws.name().to_lowercase() == name)
+    }
+
+    /// A worksheet's stable id. Unlike its index, the id doesn't change when
+    /// other sheets are removed or moved.
+    pub fn worksheet_id(&self, index: usize) -> Option<u32> {
+        self.sheet_ids.get(index).copied()
+    }
+
+    /// The current index of the worksheet with the given id, or `None` if
+    /// it was removed.
+    pub fn worksheet_index(&self, id: u32) -> Option<usize> {
+        self.sheet_ids.iter().position(|&sheet_id| sheet_id == id)
+    }
+
     pub fn get_worksheet(&self, index: usize) -> Option<&Worksheet> {
         self.worksheets.get(index)
     }
@@ -50,8 +251,7 @@ impl Workbook {
             .worksheets
             .get_mut(sheet_index)
             .ok_or(ExcelifyError::SheetNotFound(sheet_index))?;
-        ws.write_string(row, col, value);
-        Ok(())
+        ws.write_string(row, col, value)
     }
 
     pub fn write_number(
@@ -65,8 +265,7 @@ impl Workbook {
             .worksheets
             .get_mut(sheet_index)
             .ok_or(ExcelifyError::SheetNotFound(sheet_index))?;
-        ws.write_number(row, col, value);
-        Ok(())
+        ws.write_number(row, col, value)
     }
 
     pub fn write_boolean(
@@ -80,8 +279,7 @@ impl Workbook {
             .worksheets
             .get_mut(sheet_index)
             .ok_or(ExcelifyError::SheetNotFound(sheet_index))?;
-        ws.write_boolean(row, col, value);
-        Ok(())
+        ws.write_boolean(row, col, value)
     }
 
     pub fn write(
@@ -95,16 +293,161 @@ impl Workbook {
             .worksheets
             .get_mut(sheet_index)
             .ok_or(ExcelifyError::SheetNotFound(sheet_index))?;
-        ws.write(row, col, value);
+        ws.write(row, col, value)
+    }
+
+    /// Inserts rows into a worksheet and updates the references to the moved
+    /// cells on every sheet and in defined names.
+    pub fn insert_rows(&mut self, sheet_index: usize, row: u32, count: u32) -> Result<()> {
+        self.apply_shift(sheet_index, Shift::InsertRows { at: row, count })
+    }
+
+    /// Deletes rows from a worksheet. References to deleted cells become
+    /// `#REF!` wherever they appear.
+    pub fn delete_rows(&mut self, sheet_index: usize, row: u32, count: u32) -> Result<()> {
+        self.apply_shift(sheet_index, Shift::DeleteRows { at: row, count })
+    }
+
+    pub fn insert_cols(&mut self, sheet_index: usize, col: u32, count: u32) -> Result<()> {
+        self.apply_shift(sheet_index, Shift::InsertCols { at: col, count })
+    }
+
+    pub fn delete_cols(&mut self, sheet_index: usize, col: u32, count: u32) -> Result<()> {
+        self.apply_shift(sheet_index, Shift::DeleteCols { at: col, count })
+    }
+
+    fn apply_shift(&mut self, sheet_index: usize, shift: Shift) -> Result<()> {
+        let ws = self
+            .worksheets
+            .get_mut(sheet_index)
+            .ok_or(ExcelifyError::SheetNotFound(sheet_index))?;
+        ws.apply_shift(shift)?;
+        let name = ws.name().to_string();
+
+        for (index, ws) in self.worksheets.iter_mut().enumerate() {
+            if index != sheet_index {
+                ws.shift_references(&name, shift);
+            }
+        }
+        let mut rewrite = |formula: &str| shift.formula(formula, &name, None);
+        for defined_name in &mut self.defined_names {
+            defined_name.rewrite_formulas(&mut rewrite);
+        }
+        Ok(())
+    }
+
+    pub fn define_name(&mut self, name: DefinedName) -> Result<()> {
+        name.validate()?;
+        if let Some(index) = name.sheet_scope() {
+            if index >= self.worksheets.len() {
+                return Err(ExcelifyError::SheetNotFound(index));
+            }
+        }
+        if self.defined_names.iter().any(|n| n.conflicts_with(&name)) {
+            return Err(ExcelifyError::InvalidDefinedName(format!(
+                "{}: name is already defined in this scope",
+                name.name()
+            )));
+        }
+        self.defined_names.push(name);
+        Ok(())
+    }
+
+    pub fn defined_names(&self) -> &[DefinedName] {
+        &self.defined_names
+    }
+
+    /// Stops users from adding, removing, renaming or reordering sheets.
+    pub fn protect_structure(&mut self, password: Option<PasswordHash>) {
+        self.structure_protected = true;
+        self.structure_password = password;
+    }
+
+    pub fn is_structure_protected(&self) -> bool {
+        self.structure_protected
+    }
+
+    pub fn structure_password(&self) -> Option<&PasswordHash> {
+        self.structure_password.as_ref()
+    }
+
+    /// Sets the sheet shown when the file is opened.
+    pub fn set_active_sheet(&mut self, index: usize) -> Result<()> {
+        if index >= self.worksheets.len() {
+            return Err(ExcelifyError::SheetNotFound(index));
+        }
+        self.active_sheet = index;
+        Ok(())
+    }
+
+    /// The sheet shown when the file is opened: the chosen active sheet, or
+    /// the first visible one if that sheet is hidden.
+    pub fn active_sheet(&self) -> Result<usize> {
+        let visible = |idx: &usize| self.worksheets[*idx].visibility() == SheetVisibility::Visible;
+        if self.worksheets.is_empty() || visible(&self.active_sheet) {
+            return Ok(self.active_sheet);
+        }
+        (0..self.worksheets.len()).find(visible).ok_or_else(|| {
+            ExcelifyError::InvalidSheetView("at least one worksheet must be visible".into())
+        })
+    }
+
+    pub fn set_properties(&mut self, properties: DocumentProperties) {
+        self.properties = properties;
+    }
+
+    pub fn properties(&self) -> &DocumentProperties {
+        &self.properties
+    }
+
+    /// Sets a custom document property, replacing any existing property
+    /// with the same name.
+    pub fn set_custom_property(
+        &mut self,
+        name: impl Into<String>,
+        value: CustomProperty,
+    ) -> Result<()> {
+        let name = name.into();
+        validate_custom_name(&name)?;
+        value.validate()?;
+        match self
+            .custom_properties
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(&name))
+        {
+            Some(entry) => *entry = (name, value),
+            None => self.custom_properties.push((name, value)),
+        }
         Ok(())
     }
 
+    pub fn custom_properties(&self) -> &[(String, CustomProperty)] {
+        &self.custom_properties
+    }
+
     pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
-        let writer = XlsxWriter::new(self);
+        self.save_with_options(path, SaveOptions::default())
+    }
+
+    pub fn save_with_options(&self, path: impl AsRef<Path>, options: SaveOptions) -> Result<()> {
+        let writer = XlsxWriter::new(self).options(options);
         writer.save(path)
     }
 }
 
+/// Where the sheet at `index` ends up when the sheet at `from` moves to `to`.
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
 impl Default for Workbook {
     fn default() -> Self {
         Self::new()
@@ -124,16 +467,45 @@ mod tests {
     #[test]
     fn test_add_worksheet() {
         let mut wb = Workbook::new();
-        let idx = wb.add_worksheet("Sheet1");
+        let idx = wb.add_worksheet("Sheet1").unwrap();
         assert_eq!(idx, 0);
         assert_eq!(wb.worksheet_count(), 1);
         assert_eq!(wb.get_worksheet(0).unwrap().name(), "Sheet1");
     }
 
+    #[test]
+    fn test_add_worksheet_rejects_invalid_names() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sales").unwrap();
+        for name in ["SALES", "", "a:b", &"n".repeat(32)] {
+            assert!(matches!(
+                wb.add_worksheet(name),
+                Err(ExcelifyError::InvalidSheetName(_))
+            ));
+        }
+        assert_eq!(wb.worksheet_count(), 1);
+    }
+
+    #[test]
+    fn test_add_worksheet_sanitized() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sales").unwrap();
+        let idx = wb.add_worksheet_sanitized("sales");
+        assert_eq!(wb.get_worksheet(idx).unwrap().name(), "sales (2)");
+        let idx = wb.add_worksheet_sanitized("Sales");
+        assert_eq!(wb.get_worksheet(idx).unwrap().name(), "Sales (3)");
+
+        let long = "L".repeat(40);
+        wb.add_worksheet_sanitized(&long);
+        let idx = wb.add_worksheet_sanitized(&long);
+        let name = wb.get_worksheet(idx).unwrap().name();
+        assert_eq!(name, format!("{} (2)", "L".repeat(27)));
+    }
+
     #[test]
     fn test_write_to_worksheet() {
         let mut wb = Workbook::new();
-        wb.add_worksheet("Sheet1");
+        wb.add_worksheet("Sheet1").unwrap();
 
         wb.write_string(0, 0, 0, "Hello").unwrap();
         wb.write_number(0, 0, 1, 42.0).unwrap();
@@ -151,4 +523,182 @@ mod tests {
         let result = wb.write_string(0, 0, 0, "Hello");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_define_name() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+
+        wb.define_name(DefinedName::new("Sales", "=Sheet1!$A$1:$A$10"))
+            .unwrap();
+        wb.define_name(DefinedName::new("Sales", "Sheet1!$B$1").scope(0))
+            .unwrap();
+        assert_eq!(wb.defined_names().len(), 2);
+
+        assert!(wb
+            .define_name(DefinedName::new("SALES", "Sheet1!$C$1"))
+            .is_err());
+        assert!(matches!(
+            wb.define_name(DefinedName::new("Other", "Sheet1!$C$1").scope(3)),
+            Err(ExcelifyError::SheetNotFound(3))
+        ));
+        assert!(wb
+            .define_name(DefinedName::new("A1", "Sheet1!$C$1"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_delete_rows_updates_other_sheets() {
+        use crate::chart::{Chart, ChartSeries, ChartType};
+
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.add_worksheet("Report").unwrap();
+        wb.define_name(DefinedName::new("Totals", "Data!$B$2:$B$10"))
+            .unwrap();
+        let chart = Chart::new(ChartType::Line).add_series(
+            ChartSeries::new("Data!$B$2:$B$10")
+                .categories("Data!$A$2:$A$10")
+                .name("=Data!$B$1"),
+        );
+        wb.get_worksheet_mut(1)
+            .unwrap()
+            .insert_chart(0, 0, chart)
+            .unwrap();
+
+        wb.delete_rows(0, 0, 1).unwrap();
+        assert_eq!(wb.defined_names()[0].refers_to(), "Data!$B$1:$B$9");
+        let series = &wb.get_worksheet(1).unwrap().charts()[0].2.series()[0];
+        assert_eq!(series.get_values(), "Data!$B$1:$B$9");
+        assert_eq!(series.get_categories(), Some("Data!$A$1:$A$9"));
+        assert_eq!(series.get_name(), Some("=#REF!"));
+
+        assert!(matches!(
+            wb.insert_rows(5, 0, 1),
+            Err(ExcelifyError::SheetNotFound(5))
+        ));
+    }
+
+    #[test]
+    fn test_rename_and_remove_worksheets() {
+        use crate::validation::{DataValidation, ValidationRule};
+
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.add_worksheet("Form").unwrap();
+        wb.add_worksheet("Notes").unwrap();
+        wb.define_name(DefinedName::new("Codes", "Data!$A$1:$A$5"))
+            .unwrap();
+        wb.define_name(DefinedName::new("Local", "Notes!$A$1").scope(2))
+            .unwrap();
+        wb.get_worksheet_mut(1)
+            .unwrap()
+            .add_data_validation(
+                0,
+                0,
+                0,
+                0,
+                DataValidation::new(ValidationRule::ListRange("=Data!$A$1:$A$5".into())),
+            )
+            .unwrap();
+        let notes_id = wb.worksheet_id(2).unwrap();
+
+        wb.rename_worksheet(0, "Lookup Codes").unwrap();
+        assert_eq!(
+            wb.defined_names()[0].refers_to(),
+            "'Lookup Codes'!$A$1:$A$5"
+        );
+        assert_eq!(
+            wb.get_worksheet(1).unwrap().data_validations()[0].1.rule(),
+            &ValidationRule::ListRange("='Lookup Codes'!$A$1:$A$5".into())
+        );
+        assert!(wb.rename_worksheet(1, "NOTES").is_err());
+        assert!(wb.rename_worksheet(1, "FORM").is_ok());
+
+        wb.set_active_sheet(2).unwrap();
+        let removed = wb.remove_worksheet(0).unwrap();
+        assert_eq!(removed.name(), "Lookup Codes");
+        assert_eq!(wb.worksheet_count(), 2);
+        assert_eq!(wb.worksheet_index(notes_id), Some(1));
+        assert_eq!(wb.active_sheet().unwrap(), 1);
+        assert_eq!(wb.defined_names()[0].refers_to(), "#REF!");
+        assert_eq!(wb.defined_names()[1].sheet_scope(), Some(1));
+
+        wb.remove_worksheet(1).unwrap();
+        assert_eq!(wb.worksheet_index(notes_id), None);
+        assert_eq!(wb.defined_names().len(), 1);
+        assert!(matches!(
+            wb.remove_worksheet(1),
+            Err(ExcelifyError::SheetNotFound(1))
+        ));
+    }
+
+    #[test]
+    fn test_move_and_copy_worksheets() {
+        let mut wb = Workbook::new();
+        for name in ["A", "B", "C"] {
+            wb.add_worksheet(name).unwrap();
+        }
+        wb.define_name(DefinedName::new("Total", "C!$A$1").scope(2))
+            .unwrap();
+        wb.write_number(2, 0, 0, 42.0).unwrap();
+        let c_id = wb.worksheet_id(2).unwrap();
+
+        wb.move_worksheet(2, 0).unwrap();
+        let names: Vec<_> = wb.worksheets().iter().map(Worksheet::name).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+        assert_eq!(wb.worksheet_index(c_id), Some(0));
+        assert_eq!(wb.defined_names()[0].sheet_scope(), Some(0));
+        assert!(wb.move_worksheet(0, 3).is_err());
+
+        let copy = wb.copy_worksheet(0, "C (2)").unwrap();
+        assert_eq!(copy, 3);
+        assert!(wb.get_worksheet(copy).unwrap().get(0, 0).is_some());
+        assert_ne!(wb.worksheet_id(copy), Some(c_id));
+        assert_eq!(wb.defined_names()[1].sheet_scope(), Some(3));
+        assert!(wb.copy_worksheet(0, "a").is_err());
+        assert_eq!(wb.worksheet_index_by_name("c (2)"), Some(3));
+    }
+
+    #[test]
+    fn test_custom_properties_replace_by_name() {
+        let mut wb = Workbook::new();
+        wb.set_custom_property("Department", CustomProperty::Text("Finance".into()))
+            .unwrap();
+        wb.set_custom_property("Reviewed", CustomProperty::Boolean(false))
+            .unwrap();
+        wb.set_custom_property("department", CustomProperty::Text("Sales".into()))
+            .unwrap();
+
+        assert_eq!(wb.custom_properties().len(), 2);
+        assert_eq!(wb.custom_properties()[0].0, "department");
+        assert!(wb
+            .set_custom_property("", CustomProperty::Boolean(true))
+            .is_err());
+    }
+
+    #[test]
+    fn test_active_sheet_skips_hidden() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("One").unwrap();
+        wb.add_worksheet("Two").unwrap();
+        wb.add_worksheet("Three").unwrap();
+        assert_eq!(wb.active_sheet().unwrap(), 0);
+
+        wb.set_active_sheet(1).unwrap();
+        assert_eq!(wb.active_sheet().unwrap(), 1);
+        assert!(wb.set_active_sheet(3).is_err());
+
+        wb.get_worksheet_mut(1)
+            .unwrap()
+            .set_visibility(SheetVisibility::Hidden);
+        assert_eq!(wb.active_sheet().unwrap(), 0);
+
+        for idx in [0, 2] {
+            wb.get_worksheet_mut(idx)
+                .unwrap()
+                .set_visibility(SheetVisibility::VeryHidden);
+        }
+        assert!(wb.active_sheet().is_err());
+    }
 }
@@ -1,22 +1,95 @@
-use std::collections::HashMap;
+use std::fmt::Write;
 
-use crate::cell::CellValue;
+use crate::cell::{CellError, CellValue, NonFinitePolicy};
+use crate::chart::Chart;
+use crate::conditional_format::ConditionalFormat;
+use crate::defined_name::is_r1c1_reference;
+use crate::error::{ExcelifyError, Result};
+use crate::format::{Color, Format};
+use crate::header_footer::HeaderFooter;
+use crate::image::Image;
+use crate::page_setup::PageSetup;
+use crate::protection::SheetProtection;
+use crate::reference::{CellRef, Shift};
+use crate::sheet_data::{Cell, SheetData};
+use crate::sheet_view::{SheetView, SheetVisibility};
+use crate::validation::DataValidation;
 
-#[derive(Debug)]
+/// Number of rows in a worksheet; row indexes run from 0 to `MAX_ROWS - 1`.
+pub const MAX_ROWS: u32 = 1_048_576;
+
+/// Number of columns in a worksheet (A to XFD).
+pub const MAX_COLS: u32 = 16_384;
+
+/// Longest string a cell can hold, in UTF-16 code units as Excel counts them.
+pub const MAX_STRING_LENGTH: usize = 32_767;
+
+/// Longest worksheet name Excel accepts.
+pub const MAX_SHEET_NAME_LENGTH: usize = 31;
+
+const INVALID_SHEET_NAME_CHARS: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+
+/// Excel's Track Changes uses a sheet with this name.
+const RESERVED_SHEET_NAME: &str = "History";
+
+/// Excel ignores manual page breaks beyond this many per direction.
+const MAX_PAGE_BREAKS: usize = 1023;
+
+/// Most cells a format is stored for one by one. Ranges spanning whole
+/// columns are stored as column formats and have no limit.
+pub const MAX_RANGE_FORMAT_CELLS: u64 = 1_000_000;
+
+/// Inclusive cell range as `(first_row, first_col, last_row, last_col)`.
+pub type RangeBounds = (u32, u32, u32, u32);
+
+#[derive(Debug, Clone)]
 pub struct Worksheet {
     name: String,
-    cells: HashMap<(u32, u32), CellValue>,
-    max_row: u32,
-    max_col: u32,
+    data: SheetData,
+    /// Bounds of the cells that hold values, or `None` if there are none.
+    used_range: Option<RangeBounds>,
+    /// Formats of whole columns, later ones taking precedence. A cell's own
+    /// format takes precedence over its column's.
+    column_formats: Vec<(RangeBounds, Format)>,
+    data_validations: Vec<(RangeBounds, DataValidation)>,
+    conditional_formats: Vec<(RangeBounds, ConditionalFormat)>,
+    charts: Vec<(u32, u32, Chart)>,
+    images: Vec<(u32, u32, Image)>,
+    page_setup: PageSetup,
+    header: Option<HeaderFooter>,
+    footer: Option<HeaderFooter>,
+    h_page_breaks: Vec<u32>,
+    v_page_breaks: Vec<u32>,
+    protection: Option<SheetProtection>,
+    visibility: SheetVisibility,
+    tab_color: Option<Color>,
+    selected: bool,
+    view: SheetView,
+    non_finite_policy: NonFinitePolicy,
 }
 
 impl Worksheet {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
-            cells: HashMap::new(),
-            max_row: 0,
-            max_col: 0,
+            data: SheetData::new(),
+            used_range: None,
+            column_formats: Vec::new(),
+            data_validations: Vec::new(),
+            conditional_formats: Vec::new(),
+            charts: Vec::new(),
+            images: Vec::new(),
+            page_setup: PageSetup::default(),
+            header: None,
+            footer: None,
+            h_page_breaks: Vec::new(),
+            v_page_breaks: Vec::new(),
+            protection: None,
+            visibility: SheetVisibility::default(),
+            tab_color: None,
+            selected: false,
+            view: SheetView::default(),
+            non_finite_policy: NonFinitePolicy::default(),
         }
     }
 
@@ -24,39 +97,677 @@ impl Worksheet {
         &self.name
     }
 
-    pub fn write(&mut self, row: u32, col: u32, value: impl Into<CellValue>) {
-        self.cells.insert((row, col), value.into());
-        self.max_row = self.max_row.max(row);
-        self.max_col = self.max_col.max(col);
+    /// Changes the name without checking it or updating references to the
+    /// sheet; `Workbook::rename_worksheet` does both.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Writes a value to a cell. Fails if the cell lies outside Excel's grid
+    /// or a string is longer than Excel can store. NaN and infinite numbers
+    /// are handled according to the sheet's `NonFinitePolicy`.
+    pub fn write(&mut self, row: u32, col: u32, value: impl Into<CellValue>) -> Result<()> {
+        check_cell(row, col)?;
+        let mut value = value.into();
+        match &value {
+            CellValue::String(s) => check_string_length(s)?,
+            CellValue::RichString(rich) => check_string_length(&rich.to_plain_text())?,
+            CellValue::Number(n) if !n.is_finite() => match self.non_finite_policy {
+                NonFinitePolicy::Error => return Err(ExcelifyError::NonFiniteNumber(*n)),
+                NonFinitePolicy::Empty => return self.clear_cell(row, col),
+                NonFinitePolicy::NumError => value = CellValue::Error(CellError::Num),
+            },
+            CellValue::Empty => return self.clear_cell(row, col),
+            _ => {}
+        }
+        self.data.set_value(row, col, value);
+        self.used_range = Some(extend_range(self.used_range, row, col));
+        Ok(())
+    }
+
+    /// Removes a cell's value. Its format is kept, like pressing Delete in
+    /// Excel.
+    pub fn clear_cell(&mut self, row: u32, col: u32) -> Result<()> {
+        check_cell(row, col)?;
+        if self.data.clear_value(row, col) {
+            self.shrink_used_range(row, col, row, col);
+        }
+        Ok(())
+    }
+
+    /// Removes the values in a range, keeping their formats.
+    pub fn clear_range(
+        &mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+    ) -> Result<()> {
+        let (first_row, first_col, last_row, last_col) =
+            normalize_bounds(first_row, first_col, last_row, last_col);
+        check_cell(last_row, last_col)?;
+        if self
+            .data
+            .clear_values((first_row, first_col, last_row, last_col))
+        {
+            self.shrink_used_range(first_row, first_col, last_row, last_col);
+        }
+        Ok(())
     }
 
-    pub fn write_string(&mut self, row: u32, col: u32, value: impl Into<String>) {
-        self.write(row, col, CellValue::String(value.into()));
+    /// Removes the formats in a range, so that its cells use the default
+    /// style again.
+    pub fn clear_range_formats(
+        &mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+    ) -> Result<()> {
+        let bounds = normalize_bounds(first_row, first_col, last_row, last_col);
+        check_cell(bounds.2, bounds.3)?;
+        if is_whole_columns(bounds) {
+            self.column_formats = std::mem::take(&mut self.column_formats)
+                .into_iter()
+                .flat_map(|(columns, format)| {
+                    without_columns(columns, bounds.1, bounds.3)
+                        .into_iter()
+                        .map(move |columns| (columns, format.clone()))
+                })
+                .collect();
+            self.data.clear_formats(bounds);
+            return Ok(());
+        }
+
+        // Inside formatted columns, cells need the default format to stop
+        // them from taking the column's.
+        let overlaps: Vec<RangeBounds> = self
+            .column_formats
+            .iter()
+            .filter_map(|&(columns, _)| intersect_bounds(columns, bounds))
+            .collect();
+        for &overlap in &overlaps {
+            check_format_cells(overlap)?;
+        }
+        self.data.clear_formats(bounds);
+        for (first_row, first_col, last_row, last_col) in overlaps {
+            for row in first_row..=last_row {
+                for col in first_col..=last_col {
+                    self.data.set_format(row, col, Format::default());
+                }
+            }
+        }
+        Ok(())
     }
 
-    pub fn write_number(&mut self, row: u32, col: u32, value: f64) {
-        self.write(row, col, CellValue::Number(value));
+    /// Recomputes the used range after values were removed from the given
+    /// area, unless the area lies strictly inside it.
+    fn shrink_used_range(&mut self, first_row: u32, first_col: u32, last_row: u32, last_col: u32) {
+        let Some((used_first_row, used_first_col, used_last_row, used_last_col)) = self.used_range
+        else {
+            return;
+        };
+        let on_edge = first_row <= used_first_row
+            || first_col <= used_first_col
+            || last_row >= used_last_row
+            || last_col >= used_last_col;
+        if on_edge {
+            self.recompute_used_range();
+        }
+    }
+
+    fn recompute_used_range(&mut self) {
+        self.used_range = self.cells().fold(None, |range, ((row, col), _)| {
+            Some(extend_range(range, row, col))
+        });
     }
 
-    pub fn write_boolean(&mut self, row: u32, col: u32, value: bool) {
-        self.write(row, col, CellValue::Boolean(value));
+    pub fn write_string(&mut self, row: u32, col: u32, value: impl Into<String>) -> Result<()> {
+        self.write(row, col, CellValue::String(value.into()))
+    }
+
+    pub fn write_number(&mut self, row: u32, col: u32, value: f64) -> Result<()> {
+        self.write(row, col, CellValue::Number(value))
+    }
+
+    pub fn write_boolean(&mut self, row: u32, col: u32, value: bool) -> Result<()> {
+        self.write(row, col, CellValue::Boolean(value))
+    }
+
+    pub fn set_non_finite_policy(&mut self, policy: NonFinitePolicy) {
+        self.non_finite_policy = policy;
+    }
+
+    pub fn non_finite_policy(&self) -> NonFinitePolicy {
+        self.non_finite_policy
     }
 
     pub fn get(&self, row: u32, col: u32) -> Option<&CellValue> {
-        self.cells.get(&(row, col))
+        self.data
+            .get(row, col)
+            .filter(|cell| cell.has_value())
+            .map(Cell::value)
+    }
+
+    /// The cells that hold values, in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = ((u32, u32), &CellValue)> {
+        self.data
+            .iter()
+            .filter(|(_, _, cell)| cell.has_value())
+            .map(|(row, col, cell)| ((row, col), cell.value()))
     }
 
-    pub fn cells(&self) -> &HashMap<(u32, u32), CellValue> {
-        &self.cells
+    /// The stored cells, row by row, for writing the sheet out.
+    pub fn sheet_data(&self) -> &SheetData {
+        &self.data
     }
 
-    pub fn dimensions(&self) -> (u32, u32) {
-        (self.max_row, self.max_col)
+    /// The smallest range holding every cell with a value, or `None` for a
+    /// sheet without values. Formats alone don't count.
+    pub fn dimensions(&self) -> Option<RangeBounds> {
+        self.used_range
     }
 
     pub fn is_empty(&self) -> bool {
-        self.cells.is_empty()
+        self.used_range.is_none()
+    }
+
+    /// Inserts `count` empty rows above `row`, moving the rows below down.
+    /// Formats, ranges, objects, page breaks and formulas on this sheet
+    /// follow the cells they point at; `Workbook::insert_rows` also updates
+    /// other sheets and defined names.
+    pub fn insert_rows(&mut self, row: u32, count: u32) -> Result<()> {
+        self.apply_shift(Shift::InsertRows { at: row, count })
+    }
+
+    /// Deletes `count` rows starting at `row`, moving the rows below up.
+    /// References to deleted cells become `#REF!`.
+    pub fn delete_rows(&mut self, row: u32, count: u32) -> Result<()> {
+        self.apply_shift(Shift::DeleteRows { at: row, count })
+    }
+
+    /// Inserts `count` empty columns left of `col`, moving the columns to
+    /// the right along.
+    pub fn insert_cols(&mut self, col: u32, count: u32) -> Result<()> {
+        self.apply_shift(Shift::InsertCols { at: col, count })
+    }
+
+    /// Deletes `count` columns starting at `col`, moving the columns to the
+    /// right back.
+    pub fn delete_cols(&mut self, col: u32, count: u32) -> Result<()> {
+        self.apply_shift(Shift::DeleteCols { at: col, count })
+    }
+
+    pub fn apply_shift(&mut self, shift: Shift) -> Result<()> {
+        self.check_shift(shift)?;
+
+        self.data.remap(|row, col| shift.cell(row, col));
+        self.recompute_used_range();
+
+        self.column_formats = std::mem::take(&mut self.column_formats)
+            .into_iter()
+            .filter_map(|(bounds, format)| Some((shift.bounds(bounds)?, format)))
+            .collect();
+        self.data_validations = std::mem::take(&mut self.data_validations)
+            .into_iter()
+            .filter_map(|(bounds, validation)| Some((shift.bounds(bounds)?, validation)))
+            .collect();
+        self.conditional_formats = std::mem::take(&mut self.conditional_formats)
+            .into_iter()
+            .filter_map(|(bounds, rule)| Some((shift.bounds(bounds)?, rule)))
+            .collect();
+        for (row, col, _) in &mut self.charts {
+            (*row, *col) = shift_anchor(shift, *row, *col);
+        }
+        for (row, col, _) in &mut self.images {
+            (*row, *col) = shift_anchor(shift, *row, *col);
+        }
+
+        self.page_setup.apply_shift(shift);
+        self.h_page_breaks = self
+            .h_page_breaks
+            .iter()
+            .filter_map(|&row| shift.row(row))
+            .filter(|&row| row > 0)
+            .collect();
+        self.v_page_breaks = self
+            .v_page_breaks
+            .iter()
+            .filter_map(|&col| shift.col(col))
+            .filter(|&col| col > 0)
+            .collect();
+        if let Some(selection) = self.view.get_selection() {
+            let (first_row, first_col, last_row, last_col) =
+                shift.bounds(selection).unwrap_or_else(|| {
+                    let (row, col) = shift_anchor(shift, selection.0, selection.1);
+                    (row, col, row, col)
+                });
+            self.view = self
+                .view
+                .clone()
+                .selection(first_row, first_col, last_row, last_col);
+        }
+
+        let name = self.name.clone();
+        self.shift_references(&name, shift);
+        Ok(())
+    }
+
+    /// Rejects edits outside the grid and insertions that would push cells
+    /// off the sheet, which Excel refuses as well.
+    fn check_shift(&self, shift: Shift) -> Result<()> {
+        let mut occupied = self.data.iter().map(|(row, col, _)| (row, col));
+        match shift {
+            Shift::InsertRows { at, count } => {
+                check_cell(at, 0)?;
+                if let Some((row, _)) = occupied.find(|&(row, _)| shift.row(row).is_none()) {
+                    return Err(ExcelifyError::RowOutOfRange(row.saturating_add(count)));
+                }
+            }
+            Shift::InsertCols { at, count } => {
+                check_cell(0, at)?;
+                if let Some((_, col)) = occupied.find(|&(_, col)| shift.col(col).is_none()) {
+                    return Err(ExcelifyError::ColumnOutOfRange(col.saturating_add(count)));
+                }
+            }
+            Shift::DeleteRows { at, count } => {
+                check_cell(at.saturating_add(count.saturating_sub(1)), 0)?
+            }
+            Shift::DeleteCols { at, count } => {
+                check_cell(0, at.saturating_add(count.saturating_sub(1)))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites the references to `sheet` in this sheet's formulas after rows
+    /// or columns were inserted into or deleted from `sheet`.
+    pub fn shift_references(&mut self, sheet: &str, shift: Shift) {
+        let home = self.name.clone();
+        self.rewrite_formulas(&mut |formula| shift.formula(formula, sheet, Some(&home)));
+    }
+
+    /// Replaces every formula on the sheet: validation and conditional
+    /// format formulas and chart series references.
+    pub fn rewrite_formulas(&mut self, rewrite: &mut impl FnMut(&str) -> String) {
+        for (_, validation) in &mut self.data_validations {
+            validation.rewrite_formulas(rewrite);
+        }
+        for (_, rule) in &mut self.conditional_formats {
+            rule.rewrite_formulas(rewrite);
+        }
+        for (_, _, chart) in &mut self.charts {
+            chart.rewrite_formulas(rewrite);
+        }
+    }
+
+    /// Applies a format to a cell. The format is kept if the cell is empty,
+    /// which is how input cells are unlocked on a protected sheet.
+    pub fn set_format(&mut self, row: u32, col: u32, format: Format) -> Result<()> {
+        check_cell(row, col)?;
+        self.data.set_format(row, col, format);
+        Ok(())
+    }
+
+    /// Applies a format to every cell in a range, replacing their formats.
+    /// A range spanning whole columns is stored as a column format, which
+    /// also applies to cells written later; other ranges are stored cell by
+    /// cell and may hold at most `MAX_RANGE_FORMAT_CELLS` cells.
+    pub fn set_range_format(
+        &mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        format: Format,
+    ) -> Result<()> {
+        let bounds = normalize_bounds(first_row, first_col, last_row, last_col);
+        check_cell(bounds.2, bounds.3)?;
+        if is_whole_columns(bounds) {
+            self.data.clear_formats(bounds);
+            self.column_formats
+                .retain(|&(columns, _)| !(bounds.1 <= columns.1 && columns.3 <= bounds.3));
+            self.column_formats.push((bounds, format));
+            return Ok(());
+        }
+
+        check_format_cells(bounds)?;
+        let (first_row, first_col, last_row, last_col) = bounds;
+        for row in first_row..=last_row {
+            for col in first_col..=last_col {
+                self.data.set_format(row, col, format.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// The format a cell is shown with: its own, or else its column's.
+    /// `None` stands for the default style.
+    pub fn get_format(&self, row: u32, col: u32) -> Option<&Format> {
+        match self.data.get(row, col).and_then(Cell::format) {
+            Some(format) => (*format != Format::default()).then_some(format),
+            None => self.column_format(col),
+        }
+    }
+
+    /// The format of a whole column, if one was set.
+    pub fn column_format(&self, col: u32) -> Option<&Format> {
+        self.column_formats
+            .iter()
+            .rev()
+            .find(|&&((_, first_col, _, last_col), _)| (first_col..=last_col).contains(&col))
+            .map(|(_, format)| format)
+    }
+
+    /// The column formats resolved into ordered, non-overlapping spans of
+    /// `(first_col, last_col, format)`, as written to `<cols>`.
+    pub fn column_format_spans(&self) -> Vec<(u32, u32, &Format)> {
+        let mut edges: Vec<u32> = self
+            .column_formats
+            .iter()
+            .flat_map(|&((_, first_col, _, last_col), _)| [first_col, last_col + 1])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut spans: Vec<(u32, u32, &Format)> = Vec::new();
+        for edge in edges.windows(2) {
+            let (first_col, last_col) = (edge[0], edge[1] - 1);
+            let Some(format) = self.column_format(first_col) else {
+                continue;
+            };
+            match spans.last_mut() {
+                Some((_, last, previous)) if *last + 1 == first_col && *previous == format => {
+                    *last = last_col;
+                }
+                _ => spans.push((first_col, last_col, format)),
+            }
+        }
+        spans
+    }
+
+    /// The cells with a format of their own, in row-major order. Column
+    /// formats are listed by `column_format_spans`.
+    pub fn formats(&self) -> impl Iterator<Item = ((u32, u32), &Format)> {
+        self.data
+            .iter()
+            .filter_map(|(row, col, cell)| Some(((row, col), cell.format()?)))
+    }
+
+    pub fn add_data_validation(
+        &mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        validation: DataValidation,
+    ) -> Result<()> {
+        validation.validate()?;
+        let bounds = normalize_bounds(first_row, first_col, last_row, last_col);
+        check_cell(bounds.2, bounds.3)?;
+        self.data_validations.push((bounds, validation));
+        Ok(())
+    }
+
+    pub fn data_validations(&self) -> &[(RangeBounds, DataValidation)] {
+        &self.data_validations
+    }
+
+    /// Adds a conditional format rule. Rules are evaluated in the order they
+    /// were added, so earlier rules take priority.
+    pub fn add_conditional_format(
+        &mut self,
+        first_row: u32,
+        first_col: u32,
+        last_row: u32,
+        last_col: u32,
+        rule: ConditionalFormat,
+    ) -> Result<()> {
+        rule.validate()?;
+        let bounds = normalize_bounds(first_row, first_col, last_row, last_col);
+        check_cell(bounds.2, bounds.3)?;
+        self.conditional_formats.push((bounds, rule));
+        Ok(())
+    }
+
+    pub fn conditional_formats(&self) -> &[(RangeBounds, ConditionalFormat)] {
+        &self.conditional_formats
+    }
+
+    /// Places a chart with its top-left corner at the given cell.
+    pub fn insert_chart(&mut self, row: u32, col: u32, chart: Chart) -> Result<()> {
+        check_cell(row, col)?;
+        chart.validate()?;
+        self.charts.push((row, col, chart));
+        Ok(())
+    }
+
+    pub fn charts(&self) -> &[(u32, u32, Chart)] {
+        &self.charts
+    }
+
+    /// Places an image with its top-left corner at the given cell, shifted
+    /// by the image's offset.
+    pub fn insert_image(&mut self, row: u32, col: u32, image: Image) -> Result<()> {
+        check_cell(row, col)?;
+        image.validate()?;
+        self.images.push((row, col, image));
+        Ok(())
+    }
+
+    pub fn images(&self) -> &[(u32, u32, Image)] {
+        &self.images
+    }
+
+    pub fn set_page_setup(&mut self, page_setup: PageSetup) -> Result<()> {
+        page_setup.validate()?;
+        self.page_setup = page_setup;
+        Ok(())
+    }
+
+    pub fn page_setup(&self) -> &PageSetup {
+        &self.page_setup
+    }
+
+    pub fn set_header(&mut self, header: HeaderFooter) -> Result<()> {
+        header.validate()?;
+        self.header = Some(header);
+        Ok(())
+    }
+
+    pub fn header(&self) -> Option<&HeaderFooter> {
+        self.header.as_ref()
+    }
+
+    pub fn set_footer(&mut self, footer: HeaderFooter) -> Result<()> {
+        footer.validate()?;
+        self.footer = Some(footer);
+        Ok(())
+    }
+
+    pub fn footer(&self) -> Option<&HeaderFooter> {
+        self.footer.as_ref()
+    }
+
+    /// Starts a new printed page above each of `rows`. Replaces any breaks set
+    /// before.
+    pub fn set_h_page_breaks(&mut self, rows: &[u32]) -> Result<()> {
+        if let Some(&row) = rows.iter().find(|&&row| row >= MAX_ROWS) {
+            return Err(ExcelifyError::RowOutOfRange(row));
+        }
+        self.h_page_breaks = page_breaks(rows)?;
+        Ok(())
+    }
+
+    pub fn h_page_breaks(&self) -> &[u32] {
+        &self.h_page_breaks
+    }
+
+    /// Starts a new printed page left of each of `cols`. Replaces any breaks
+    /// set before.
+    pub fn set_v_page_breaks(&mut self, cols: &[u32]) -> Result<()> {
+        if let Some(&col) = cols.iter().find(|&&col| col >= MAX_COLS) {
+            return Err(ExcelifyError::ColumnOutOfRange(col));
+        }
+        self.v_page_breaks = page_breaks(cols)?;
+        Ok(())
+    }
+
+    pub fn v_page_breaks(&self) -> &[u32] {
+        &self.v_page_breaks
+    }
+
+    /// Protects the sheet so that locked cells can't be edited. Use
+    /// `Format::locked(false)` on input cells to keep them editable.
+    pub fn protect(&mut self, protection: SheetProtection) {
+        self.protection = Some(protection);
+    }
+
+    pub fn protection(&self) -> Option<&SheetProtection> {
+        self.protection.as_ref()
+    }
+
+    pub fn set_visibility(&mut self, visibility: SheetVisibility) {
+        self.visibility = visibility;
+    }
+
+    pub fn visibility(&self) -> SheetVisibility {
+        self.visibility
+    }
+
+    pub fn set_tab_color(&mut self, color: Color) {
+        self.tab_color = Some(color);
+    }
+
+    pub fn tab_color(&self) -> Option<Color> {
+        self.tab_color
+    }
+
+    /// Selects the sheet's tab along with the active sheet, grouping them.
+    pub fn set_selected(&mut self, selected: bool) {
+        self.selected = selected;
+    }
+
+    pub fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn set_view(&mut self, view: SheetView) -> Result<()> {
+        view.validate()?;
+        self.view = view;
+        Ok(())
     }
+
+    pub fn view(&self) -> &SheetView {
+        &self.view
+    }
+
+    /// Whether the sheet needs a drawing part for embedded objects.
+    pub fn has_drawing(&self) -> bool {
+        !self.charts.is_empty() || !self.images.is_empty()
+    }
+}
+
+/// Sorts and de-duplicates breaks. A break before the first row or column
+/// has no effect and is dropped.
+fn page_breaks(positions: &[u32]) -> Result<Vec<u32>> {
+    let mut breaks: Vec<u32> = positions.iter().copied().filter(|&p| p > 0).collect();
+    breaks.sort_unstable();
+    breaks.dedup();
+    if breaks.len() > MAX_PAGE_BREAKS {
+        return Err(ExcelifyError::InvalidPageSetup(format!(
+            "at most {} page breaks are allowed, got {}",
+            MAX_PAGE_BREAKS,
+            breaks.len()
+        )));
+    }
+    Ok(breaks)
+}
+
+/// Where an object anchored at a cell ends up. Objects in deleted rows or
+/// columns move to the first one after the deletion, like in Excel.
+fn shift_anchor(shift: Shift, row: u32, col: u32) -> (u32, u32) {
+    let row = shift.row(row).unwrap_or(match shift {
+        Shift::DeleteRows { at, .. } => at.min(MAX_ROWS - 1),
+        _ => MAX_ROWS - 1,
+    });
+    let col = shift.col(col).unwrap_or(match shift {
+        Shift::DeleteCols { at, .. } => at.min(MAX_COLS - 1),
+        _ => MAX_COLS - 1,
+    });
+    (row, col)
+}
+
+fn extend_range(range: Option<RangeBounds>, row: u32, col: u32) -> RangeBounds {
+    match range {
+        Some((first_row, first_col, last_row, last_col)) => (
+            first_row.min(row),
+            first_col.min(col),
+            last_row.max(row),
+            last_col.max(col),
+        ),
+        None => (row, col, row, col),
+    }
+}
+
+fn is_whole_columns(bounds: RangeBounds) -> bool {
+    bounds.0 == 0 && bounds.2 == MAX_ROWS - 1
+}
+
+/// The columns of `columns` left and right of `first_col..=last_col`.
+fn without_columns(columns: RangeBounds, first_col: u32, last_col: u32) -> Vec<RangeBounds> {
+    let (first_row, start, last_row, end) = columns;
+    let mut kept = Vec::new();
+    if start < first_col {
+        kept.push((first_row, start, last_row, end.min(first_col - 1)));
+    }
+    if end > last_col {
+        kept.push((first_row, start.max(last_col + 1), last_row, end));
+    }
+    kept
+}
+
+fn intersect_bounds(a: RangeBounds, b: RangeBounds) -> Option<RangeBounds> {
+    let bounds = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    (bounds.0 <= bounds.2 && bounds.1 <= bounds.3).then_some(bounds)
+}
+
+/// Rejects ranges too large to store a format for each cell.
+fn check_format_cells(bounds: RangeBounds) -> Result<()> {
+    let (first_row, first_col, last_row, last_col) = bounds;
+    let cells = u64::from(last_row - first_row + 1) * u64::from(last_col - first_col + 1);
+    if cells > MAX_RANGE_FORMAT_CELLS {
+        return Err(ExcelifyError::RangeTooLarge(cells));
+    }
+    Ok(())
+}
+
+fn normalize_bounds(first_row: u32, first_col: u32, last_row: u32, last_col: u32) -> RangeBounds {
+    (
+        first_row.min(last_row),
+        first_col.min(last_col),
+        first_row.max(last_row),
+        first_col.max(last_col),
+    )
+}
+
+/// Checks that a zero-based cell position lies within Excel's grid.
+pub fn check_cell(row: u32, col: u32) -> Result<()> {
+    if row >= MAX_ROWS {
+        return Err(ExcelifyError::RowOutOfRange(row));
+    }
+    if col >= MAX_COLS {
+        return Err(ExcelifyError::ColumnOutOfRange(col));
+    }
+    Ok(())
+}
+
+fn check_string_length(s: &str) -> Result<()> {
+    let length = s.encode_utf16().count();
+    if length > MAX_STRING_LENGTH {
+        return Err(ExcelifyError::StringTooLong(length));
+    }
+    Ok(())
 }
 
 pub fn col_to_letter(col: u32) -> String {
@@ -73,7 +784,114 @@ pub fn col_to_letter(col: u32) -> String {
 }
 
 pub fn cell_reference(row: u32, col: u32) -> String {
-    format!("{}{}", col_to_letter(col), row + 1)
+    let mut reference = String::new();
+    push_cell_reference(&mut reference, row, col);
+    reference
+}
+
+/// Appends a cell's A1 reference to `buffer`, so that writing many
+/// references can reuse one allocation.
+pub fn push_cell_reference(buffer: &mut String, row: u32, col: u32) {
+    let mut letters = [0u8; 7];
+    let mut len = 0;
+    let mut n = col as u64 + 1;
+    while n > 0 {
+        n -= 1;
+        letters[len] = b'A' + (n % 26) as u8;
+        len += 1;
+        n /= 26;
+    }
+    buffer.extend(letters[..len].iter().rev().map(|&letter| letter as char));
+    let _ = write!(buffer, "{}", row as u64 + 1);
+}
+
+/// Checks a worksheet name against Excel's rules. Uniqueness is checked by
+/// the workbook.
+pub fn validate_sheet_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        Err(ExcelifyError::InvalidSheetName(format!(
+            "{:?}: {}",
+            name, reason
+        )))
+    };
+
+    if name.is_empty() {
+        return invalid("name must not be empty");
+    }
+    if name.chars().count() > MAX_SHEET_NAME_LENGTH {
+        return invalid("name exceeds 31 characters");
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_SHEET_NAME_CHARS.contains(c)) {
+        return invalid(&format!("name must not contain '{}'", c));
+    }
+    if name.starts_with('\'') || name.ends_with('\'') {
+        return invalid("name must not start or end with an apostrophe");
+    }
+    if name.eq_ignore_ascii_case(RESERVED_SHEET_NAME) {
+        return invalid("name is reserved by Excel");
+    }
+    Ok(())
+}
+
+/// Turns any string into a valid worksheet name by replacing forbidden
+/// characters with `_`, trimming apostrophes and truncating to 31
+/// characters. Uniqueness is handled by the workbook.
+pub fn sanitize_sheet_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| {
+            if INVALID_SHEET_NAME_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let trimmed: String = replaced
+        .trim_matches('\'')
+        .chars()
+        .take(MAX_SHEET_NAME_LENGTH)
+        .collect();
+    // Truncation can expose a trailing apostrophe.
+    let trimmed = trimmed.trim_end_matches('\'');
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case(RESERVED_SHEET_NAME) {
+        format!("{}_", if trimmed.is_empty() { "Sheet" } else { trimmed })
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Quotes a sheet name for use in a formula, e.g. `'Q1 Sales'`. Names made
+/// only of letters, digits and underscores that don't start with a digit are
+/// left as-is, unless Excel would read them as a cell reference (`Q1`,
+/// `R2C3`) or a boolean.
+pub fn quote_sheet_name(name: &str) -> String {
+    let plain = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && CellRef::parse(name).is_err()
+        && !is_r1c1_reference(name)
+        && !name.eq_ignore_ascii_case("TRUE")
+        && !name.eq_ignore_ascii_case("FALSE");
+    if plain {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+/// Formats a range as `A1:C10`, collapsing single-cell ranges to `A1`.
+pub fn range_reference(bounds: RangeBounds) -> String {
+    let (first_row, first_col, last_row, last_col) = bounds;
+    if first_row == last_row && first_col == last_col {
+        cell_reference(first_row, first_col)
+    } else {
+        format!(
+            "{}:{}",
+            cell_reference(first_row, first_col),
+            cell_reference(last_row, last_col)
+        )
+    }
 }
 
 #[cfg(test)]
@@ -90,9 +908,9 @@ mod tests {
     #[test]
     fn test_write_and_get() {
         let mut ws = Worksheet::new("Test");
-        ws.write_string(0, 0, "Hello");
-        ws.write_number(0, 1, 42.0);
-        ws.write_boolean(1, 0, true);
+        ws.write_string(0, 0, "Hello").unwrap();
+        ws.write_number(0, 1, 42.0).unwrap();
+        ws.write_boolean(1, 0, true).unwrap();
 
         assert!(matches!(ws.get(0, 0), Some(CellValue::String(_))));
         assert!(matches!(ws.get(0, 1), Some(CellValue::Number(_))));
@@ -103,8 +921,274 @@ mod tests {
     #[test]
     fn test_dimensions() {
         let mut ws = Worksheet::new("Test");
-        ws.write_string(5, 10, "value");
-        assert_eq!(ws.dimensions(), (5, 10));
+        ws.write_string(5, 10, "value").unwrap();
+        assert_eq!(ws.dimensions(), Some((5, 10, 5, 10)));
+    }
+
+    #[test]
+    fn test_clearing_shrinks_used_range() {
+        let mut ws = Worksheet::new("Test");
+        assert_eq!(ws.dimensions(), None);
+        ws.write_number(1, 1, 1.0).unwrap();
+        ws.write_number(3, 4, 2.0).unwrap();
+        ws.write_number(2, 2, 3.0).unwrap();
+        ws.set_format(9, 9, Format::new().bold()).unwrap();
+        assert_eq!(ws.dimensions(), Some((1, 1, 3, 4)));
+
+        ws.clear_cell(2, 2).unwrap();
+        assert_eq!(ws.dimensions(), Some((1, 1, 3, 4)));
+        ws.clear_cell(3, 4).unwrap();
+        assert_eq!(ws.dimensions(), Some((1, 1, 1, 1)));
+
+        ws.write(1, 1, CellValue::Empty).unwrap();
+        assert_eq!(ws.dimensions(), None);
+        assert!(ws.is_empty());
+        assert!(ws.get_format(9, 9).is_some());
+
+        ws.write_number(0, 0, 1.0).unwrap();
+        ws.write_number(0, 5, 1.0).unwrap();
+        ws.clear_range(0, 3, 4, 0).unwrap();
+        assert_eq!(ws.dimensions(), Some((0, 5, 0, 5)));
+        ws.clear_range_formats(0, 0, MAX_ROWS - 1, MAX_COLS - 1)
+            .unwrap();
+        assert!(ws.formats().next().is_none());
+    }
+
+    #[test]
+    fn test_whole_column_formats() {
+        let bold = Format::new().bold();
+        let italic = Format::new().italic();
+        let mut ws = Worksheet::new("Data");
+        ws.write_number(5, 0, 1.0).unwrap();
+        ws.set_format(2, 0, italic.clone()).unwrap();
+
+        // A:A is stored once, not per cell.
+        ws.set_range_format(0, 0, MAX_ROWS - 1, 0, bold.clone())
+            .unwrap();
+        assert!(ws.formats().next().is_none());
+        assert_eq!(ws.get_format(2, 0), Some(&bold));
+        assert_eq!(ws.get_format(MAX_ROWS - 1, 0), Some(&bold));
+        assert_eq!(ws.get_format(0, 1), None);
+
+        ws.set_format(3, 0, italic.clone()).unwrap();
+        assert_eq!(ws.get_format(3, 0), Some(&italic));
+        ws.clear_range_formats(3, 0, 4, 0).unwrap();
+        assert_eq!(ws.get_format(3, 0), None);
+        assert_eq!(ws.get_format(4, 0), None);
+        assert_eq!(ws.get_format(5, 0), Some(&bold));
+
+        ws.set_range_format(0, 0, MAX_ROWS - 1, MAX_COLS - 1, italic.clone())
+            .unwrap();
+        assert_eq!(ws.get_format(3, 0), Some(&italic));
+        ws.clear_range_formats(0, 1, MAX_ROWS - 1, 1).unwrap();
+        assert_eq!(
+            ws.column_format_spans(),
+            vec![(0, 0, &italic), (2, MAX_COLS - 1, &italic)]
+        );
+
+        ws.insert_cols(0, 1).unwrap();
+        assert_eq!(ws.get_format(0, 1), Some(&italic));
+        assert_eq!(ws.get_format(0, 2), None);
+
+        let err = ws
+            .set_range_format(0, 0, MAX_ROWS - 2, 0, bold)
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_RANGE_TOO_LARGE");
+    }
+
+    #[test]
+    fn test_insert_and_delete_rows() {
+        use crate::validation::ValidationRule;
+
+        let mut ws = Worksheet::new("Data");
+        ws.write_string(0, 0, "header").unwrap();
+        ws.write_number(1, 0, 1.0).unwrap();
+        ws.write_number(2, 0, 2.0).unwrap();
+        ws.set_format(2, 0, Format::new().bold()).unwrap();
+        ws.add_data_validation(
+            1,
+            1,
+            2,
+            1,
+            DataValidation::new(ValidationRule::Custom("=B2>$A$3".into())),
+        )
+        .unwrap();
+        ws.set_h_page_breaks(&[2]).unwrap();
+
+        ws.insert_rows(1, 2).unwrap();
+        assert!(matches!(ws.get(3, 0), Some(CellValue::Number(n)) if *n == 1.0));
+        assert!(ws.get(1, 0).is_none());
+        assert!(ws.get_format(4, 0).is_some());
+        assert_eq!(ws.dimensions(), Some((0, 0, 4, 0)));
+        assert_eq!(ws.h_page_breaks(), &[4]);
+        let (bounds, validation) = &ws.data_validations()[0];
+        assert_eq!(*bounds, (3, 1, 4, 1));
+        assert_eq!(
+            validation.rule(),
+            &ValidationRule::Custom("=B4>$A$5".into())
+        );
+
+        ws.delete_rows(3, 2).unwrap();
+        assert_eq!(ws.dimensions(), Some((0, 0, 0, 0)));
+        assert!(ws.data_validations().is_empty());
+        assert!(ws.formats().next().is_none());
+        assert!(ws.h_page_breaks().is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_delete_cols() {
+        let mut ws = Worksheet::new("Data");
+        ws.write_number(0, 0, 1.0).unwrap();
+        ws.write_number(0, 2, 3.0).unwrap();
+        ws.insert_chart(
+            5,
+            2,
+            Chart::new(crate::chart::ChartType::Bar)
+                .add_series(crate::chart::ChartSeries::new("Data!$C$1")),
+        )
+        .unwrap();
+
+        ws.delete_cols(1, 2).unwrap();
+        assert!(ws.get(0, 1).is_none());
+        assert_eq!(ws.dimensions(), Some((0, 0, 0, 0)));
+        let (row, col, chart) = &ws.charts()[0];
+        assert_eq!((*row, *col), (5, 1));
+        assert_eq!(chart.series()[0].get_values(), "#REF!");
+
+        ws.insert_cols(0, 1).unwrap();
+        assert!(matches!(ws.get(0, 1), Some(CellValue::Number(n)) if *n == 1.0));
+    }
+
+    #[test]
+    fn test_insert_rejects_pushing_cells_off_the_sheet() {
+        let mut ws = Worksheet::new("Data");
+        ws.write_number(MAX_ROWS - 1, 0, 1.0).unwrap();
+        assert!(matches!(
+            ws.insert_rows(0, 1),
+            Err(ExcelifyError::RowOutOfRange(_))
+        ));
+        assert!(ws.get(MAX_ROWS - 1, 0).is_some());
+        assert!(matches!(
+            ws.delete_cols(MAX_COLS - 1, 2),
+            Err(ExcelifyError::ColumnOutOfRange(_))
+        ));
+        assert!(ws.insert_cols(0, 1).is_ok());
+    }
+
+    #[test]
+    fn test_cells_are_ordered() {
+        let mut ws = Worksheet::new("Test");
+        ws.write_number(2, 0, 3.0).unwrap();
+        ws.write_number(0, 5, 2.0).unwrap();
+        ws.write_number(0, 1, 1.0).unwrap();
+        let positions: Vec<_> = ws.cells().map(|(pos, _)| pos).collect();
+        assert_eq!(positions, vec![(0, 1), (0, 5), (2, 0)]);
+    }
+
+    #[test]
+    fn test_grid_limits() {
+        let mut ws = Worksheet::new("Test");
+        assert!(ws.write_number(MAX_ROWS - 1, MAX_COLS - 1, 1.0).is_ok());
+        assert!(matches!(
+            ws.write_number(MAX_ROWS, 0, 1.0),
+            Err(ExcelifyError::RowOutOfRange(1_048_576))
+        ));
+        assert!(matches!(
+            ws.write_number(0, MAX_COLS, 1.0),
+            Err(ExcelifyError::ColumnOutOfRange(16_384))
+        ));
+        assert!(matches!(
+            ws.set_format(MAX_ROWS, 0, Format::new()),
+            Err(ExcelifyError::RowOutOfRange(_))
+        ));
+        assert_eq!(
+            ws.dimensions(),
+            Some((MAX_ROWS - 1, MAX_COLS - 1, MAX_ROWS - 1, MAX_COLS - 1))
+        );
+    }
+
+    #[test]
+    fn test_non_finite_policy() {
+        let mut ws = Worksheet::new("Test");
+        let err = ws.write_number(0, 0, f64::NAN).unwrap_err();
+        assert_eq!(err.code(), "ERR_NON_FINITE_NUMBER");
+        assert!(ws.get(0, 0).is_none());
+
+        ws.write_number(0, 0, 1.0).unwrap();
+        ws.set_non_finite_policy(NonFinitePolicy::Empty);
+        ws.write_number(0, 0, f64::INFINITY).unwrap();
+        assert!(ws.get(0, 0).is_none());
+
+        ws.set_non_finite_policy(NonFinitePolicy::NumError);
+        ws.write_number(0, 0, f64::NEG_INFINITY).unwrap();
+        assert_eq!(ws.get(0, 0), Some(&CellValue::Error(CellError::Num)));
+    }
+
+    #[test]
+    fn test_string_length_limit() {
+        let mut ws = Worksheet::new("Test");
+        assert!(ws.write_string(0, 0, "x".repeat(MAX_STRING_LENGTH)).is_ok());
+        let err = ws
+            .write_string(0, 1, "x".repeat(MAX_STRING_LENGTH + 1))
+            .unwrap_err();
+        assert_eq!(err.code(), "ERR_STRING_TOO_LONG");
+        // Characters outside the BMP count twice, as they do in Excel.
+        assert!(ws.write_string(0, 2, "\u{1F600}".repeat(16_384)).is_err());
+        assert!(ws.get(0, 1).is_none());
+    }
+
+    #[test]
+    fn test_page_breaks() {
+        let mut ws = Worksheet::new("Test");
+        ws.set_h_page_breaks(&[40, 0, 20, 40]).unwrap();
+        assert_eq!(ws.h_page_breaks(), &[20, 40]);
+
+        let too_many: Vec<u32> = (1..=1024).collect();
+        assert!(ws.set_v_page_breaks(&too_many).is_err());
+        assert!(ws.set_v_page_breaks(&too_many[..1023]).is_ok());
+        assert!(ws.set_v_page_breaks(&[MAX_COLS]).is_err());
+    }
+
+    #[test]
+    fn test_validate_sheet_name() {
+        for name in ["Sheet1", "Q1 Sales", "Données", &"x".repeat(31), "Bob's"] {
+            assert!(validate_sheet_name(name).is_ok(), "{}", name);
+        }
+        for name in [
+            "",
+            &"x".repeat(32),
+            "a/b",
+            "[x]",
+            "Why?",
+            "'quoted'",
+            "history",
+        ] {
+            assert!(validate_sheet_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_sheet_name() {
+        assert_eq!(sanitize_sheet_name("Q1/Q2: Sales*"), "Q1_Q2_ Sales_");
+        assert_eq!(sanitize_sheet_name("'Budget'"), "Budget");
+        assert_eq!(sanitize_sheet_name(""), "Sheet_");
+        assert_eq!(sanitize_sheet_name("History"), "History_");
+        assert_eq!(sanitize_sheet_name(&"y".repeat(40)).len(), 31);
+        for name in ["a?b", "''", &format!("{}'x", "z".repeat(30))] {
+            assert!(validate_sheet_name(&sanitize_sheet_name(name)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_quote_sheet_name() {
+        assert_eq!(quote_sheet_name("Sheet1"), "Sheet1");
+        assert_eq!(quote_sheet_name("Q1 Sales"), "'Q1 Sales'");
+        assert_eq!(quote_sheet_name("2024"), "'2024'");
+        assert_eq!(quote_sheet_name("Bob's"), "'Bob''s'");
+        for name in ["Q1", "a1", "XFD1", "R2C3", "RC", "C5", "TRUE", "false"] {
+            assert_eq!(quote_sheet_name(name), format!("'{}'", name));
+        }
+        assert_eq!(quote_sheet_name("Q1A"), "Q1A");
     }
 
     #[test]
@@ -124,5 +1208,41 @@ mod tests {
         assert_eq!(cell_reference(0, 1), "B1");
         assert_eq!(cell_reference(9, 2), "C10");
         assert_eq!(cell_reference(0, 26), "AA1");
+        assert_eq!(cell_reference(MAX_ROWS - 1, MAX_COLS - 1), "XFD1048576");
+    }
+
+    #[test]
+    fn test_range_reference() {
+        assert_eq!(range_reference((0, 0, 9, 2)), "A1:C10");
+        assert_eq!(range_reference((4, 1, 4, 1)), "B5");
+    }
+
+    #[test]
+    fn test_insert_chart() {
+        use crate::chart::{Chart, ChartSeries, ChartType};
+
+        let mut ws = Worksheet::new("Test");
+        assert!(!ws.has_drawing());
+        assert!(ws.insert_chart(0, 0, Chart::new(ChartType::Pie)).is_err());
+
+        let chart = Chart::new(ChartType::Pie).add_series(ChartSeries::new("Test!$B$1:$B$3"));
+        ws.insert_chart(1, 3, chart).unwrap();
+        assert!(ws.has_drawing());
+        assert_eq!(ws.charts()[0].0, 1);
+    }
+
+    #[test]
+    fn test_add_data_validation() {
+        use crate::validation::{DataValidation, ValidationRule};
+
+        let mut ws = Worksheet::new("Test");
+        let rule = ValidationRule::List(vec!["Open".into(), "Closed".into()]);
+        ws.add_data_validation(9, 0, 1, 0, DataValidation::new(rule))
+            .unwrap();
+        assert_eq!(ws.data_validations()[0].0, (1, 0, 9, 0));
+
+        let empty = DataValidation::new(ValidationRule::List(vec![]));
+        assert!(ws.add_data_validation(0, 0, 0, 0, empty).is_err());
+        assert_eq!(ws.data_validations().len(), 1);
     }
 }
@@ -1,50 +1,165 @@
-use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{Cursor, Write};
+use std::io::{Cursor, Seek, Write};
 use std::path::Path;
 
+use quick_xml::escape::{escape, partial_escape};
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
 use zip::write::FileOptions;
 use zip::ZipWriter;
 
-use crate::cell::CellValue;
+use crate::cell::{escape_xstring, needs_space_preserve, write_number, CellError, CellValue};
+use crate::chart::{Chart, ChartAxis, ChartType};
+use crate::conditional_format::ConditionalFormat;
+use crate::defined_name::{DefinedName, BUILTIN_PREFIX};
+use crate::drawing::{Anchor, AnchorPoint, DEFAULT_COL_WIDTH_CHARS};
 use crate::error::Result;
+use crate::format::{Color, Format};
+use crate::image::{Image, ImageType};
+use crate::page_setup::PageSetup;
+use crate::properties::Timestamp;
+use crate::protection::PasswordHash;
+use crate::save_options::SaveOptions;
+use crate::sheet_data::{Cell, SheetData, SPAN_BLOCK_ROWS};
+use crate::styles::Styles;
+use crate::validation::{DataValidation, ValidationErrorStyle};
 use crate::workbook::Workbook;
-use crate::worksheet::{cell_reference, Worksheet};
+use crate::worksheet::{
+    cell_reference, col_to_letter, push_cell_reference, quote_sheet_name, range_reference,
+    RangeBounds, Worksheet, MAX_COLS, MAX_ROWS,
+};
 
+/// Axis ids only need to be unique within a chart part.
+const CATEGORY_AXIS_ID: u32 = 50_010_001;
+const VALUE_AXIS_ID: u32 = 50_010_002;
+
+/// Serializes a workbook to an `.xlsx` package. The output depends only on
+/// the workbook and the save options: parts are written in a fixed order,
+/// part and style ids follow sheet order, and ZIP entries carry fixed
+/// metadata, so with a fixed timestamp identical workbooks give identical
+/// bytes.
 pub struct XlsxWriter<'a> {
     workbook: &'a Workbook,
+    styles: Styles,
+    saved_at: Timestamp,
+    options: SaveOptions,
 }
 
 impl<'a> XlsxWriter<'a> {
     pub fn new(workbook: &'a Workbook) -> Self {
-        Self { workbook }
+        Self {
+            workbook,
+            styles: Styles::from_workbook(workbook),
+            saved_at: Timestamp::now(),
+            options: SaveOptions::default(),
+        }
+    }
+
+    pub fn options(mut self, options: SaveOptions) -> Self {
+        if let Some(timestamp) = options.get_timestamp() {
+            self.saved_at = timestamp;
+        }
+        self.options = options;
+        self
     }
 
     pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        // Check before creating the file so that bad options don't leave an
+        // empty one behind.
+        self.options.validate()?;
         let file = File::create(path)?;
-        let mut zip = ZipWriter::new(file);
-        let options: FileOptions = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated)
-            .compression_level(Some(6));
+        self.write_to(file)?;
+        Ok(())
+    }
+
+    /// Writes the package to any seekable sink, returning it once the ZIP
+    /// central directory has been written.
+    pub fn write_to<W: Write + Seek>(&self, sink: W) -> Result<W> {
+        self.options.validate()?;
+        let options = self.options.file_options()?;
+        let mut zip = ZipWriter::new(sink);
 
         self.write_content_types(&mut zip, options)?;
         self.write_rels(&mut zip, options)?;
+        self.write_core_properties(&mut zip, options)?;
+        self.write_app_properties(&mut zip, options)?;
+        if !self.workbook.custom_properties().is_empty() {
+            self.write_custom_properties(&mut zip, options)?;
+        }
         self.write_workbook_xml(&mut zip, options)?;
         self.write_workbook_rels(&mut zip, options)?;
+        self.write_styles_xml(&mut zip, options)?;
 
         for (idx, worksheet) in self.workbook.worksheets().iter().enumerate() {
             self.write_worksheet_xml(&mut zip, options, idx, worksheet)?;
+
+            if let Some(drawing_id) = self.drawing_id(idx) {
+                self.write_worksheet_rels(&mut zip, options, idx, drawing_id)?;
+                self.write_drawing_xml(&mut zip, options, idx, drawing_id, worksheet)?;
+                self.write_drawing_rels(&mut zip, options, idx, drawing_id, worksheet)?;
+
+                for (offset, (_, _, chart)) in worksheet.charts().iter().enumerate() {
+                    let chart_id = self.first_chart_id(idx) + offset;
+                    self.write_chart_xml(&mut zip, options, chart_id, chart)?;
+                }
+
+                for (offset, (_, _, image)) in worksheet.images().iter().enumerate() {
+                    let image_id = self.first_image_id(idx) + offset;
+                    zip.start_file(self.media_path(image_id, image), options)?;
+                    zip.write_all(image.data())?;
+                }
+            }
         }
 
-        zip.finish()?;
-        Ok(())
+        Ok(zip.finish()?)
     }
 
-    fn write_content_types(
+    /// Drawing parts are numbered consecutively across the sheets that have one.
+    fn drawing_id(&self, sheet_idx: usize) -> Option<usize> {
+        let worksheets = self.workbook.worksheets();
+        if !worksheets[sheet_idx].has_drawing() {
+            return None;
+        }
+        Some(
+            worksheets[..sheet_idx]
+                .iter()
+                .filter(|ws| ws.has_drawing())
+                .count()
+                + 1,
+        )
+    }
+
+    /// Charts are numbered consecutively across the whole workbook.
+    fn first_chart_id(&self, sheet_idx: usize) -> usize {
+        let worksheets = self.workbook.worksheets();
+        worksheets[..sheet_idx]
+            .iter()
+            .map(|ws| ws.charts().len())
+            .sum::<usize>()
+            + 1
+    }
+
+    /// Images are stored as `xl/media/imageN` and numbered across the workbook.
+    fn first_image_id(&self, sheet_idx: usize) -> usize {
+        let worksheets = self.workbook.worksheets();
+        worksheets[..sheet_idx]
+            .iter()
+            .map(|ws| ws.images().len())
+            .sum::<usize>()
+            + 1
+    }
+
+    fn media_path(&self, image_id: usize, image: &Image) -> String {
+        format!(
+            "xl/media/image{}.{}",
+            image_id,
+            image.image_type().extension()
+        )
+    }
+
+    fn write_content_types<W: Write + Seek>(
         &self,
-        zip: &mut ZipWriter<File>,
+        zip: &mut ZipWriter<W>,
         options: FileOptions,
     ) -> Result<()> {
         zip.start_file("[Content_Types].xml", options)?;
@@ -72,6 +187,38 @@ impl<'a> XlsxWriter<'a> {
         default_xml.push_attribute(("ContentType", "application/xml"));
         writer.write_event(Event::Empty(default_xml))?;
 
+        for image_type in [ImageType::Png, ImageType::Jpeg, ImageType::Gif] {
+            let used = self.workbook.worksheets().iter().any(|ws| {
+                ws.images()
+                    .iter()
+                    .any(|(_, _, image)| image.image_type() == image_type)
+            });
+            if used {
+                let mut default_image = BytesStart::new("Default");
+                default_image.push_attribute(("Extension", image_type.extension()));
+                default_image.push_attribute(("ContentType", image_type.content_type()));
+                writer.write_event(Event::Empty(default_image))?;
+            }
+        }
+
+        self.write_override(
+            &mut writer,
+            "/docProps/core.xml",
+            "application/vnd.openxmlformats-package.core-properties+xml",
+        )?;
+        self.write_override(
+            &mut writer,
+            "/docProps/app.xml",
+            "application/vnd.openxmlformats-officedocument.extended-properties+xml",
+        )?;
+        if !self.workbook.custom_properties().is_empty() {
+            self.write_override(
+                &mut writer,
+                "/docProps/custom.xml",
+                "application/vnd.openxmlformats-officedocument.custom-properties+xml",
+            )?;
+        }
+
         let mut override_wb = BytesStart::new("Override");
         override_wb.push_attribute(("PartName", "/xl/workbook.xml"));
         override_wb.push_attribute((
@@ -90,13 +237,62 @@ impl<'a> XlsxWriter<'a> {
             writer.write_event(Event::Empty(override_sheet))?;
         }
 
+        for idx in 0..self.workbook.worksheet_count() {
+            if let Some(drawing_id) = self.drawing_id(idx) {
+                self.write_override(
+                    &mut writer,
+                    &format!("/xl/drawings/drawing{}.xml", drawing_id),
+                    "application/vnd.openxmlformats-officedocument.drawing+xml",
+                )?;
+            }
+        }
+
+        let chart_count: usize = self
+            .workbook
+            .worksheets()
+            .iter()
+            .map(|ws| ws.charts().len())
+            .sum();
+        for chart_id in 1..=chart_count {
+            self.write_override(
+                &mut writer,
+                &format!("/xl/charts/chart{}.xml", chart_id),
+                "application/vnd.openxmlformats-officedocument.drawingml.chart+xml",
+            )?;
+        }
+
+        let mut override_styles = BytesStart::new("Override");
+        override_styles.push_attribute(("PartName", "/xl/styles.xml"));
+        override_styles.push_attribute((
+            "ContentType",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml",
+        ));
+        writer.write_event(Event::Empty(override_styles))?;
+
         writer.write_event(Event::End(BytesEnd::new("Types")))?;
 
         zip.write_all(writer.into_inner().into_inner().as_slice())?;
         Ok(())
     }
 
-    fn write_rels(&self, zip: &mut ZipWriter<File>, options: FileOptions) -> Result<()> {
+    fn write_override(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        part_name: &str,
+        content_type: &str,
+    ) -> Result<()> {
+        let mut elem = BytesStart::new("Override");
+        elem.push_attribute(("PartName", part_name));
+        elem.push_attribute(("ContentType", content_type));
+        writer.write_event(Event::Empty(elem))?;
+        Ok(())
+    }
+
+    fn write_rels<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+    ) -> Result<()> {
         zip.start_file("_rels/.rels", options)?;
 
         let mut writer = Writer::new(Cursor::new(Vec::new()));
@@ -118,15 +314,212 @@ impl<'a> XlsxWriter<'a> {
         rel.push_attribute(("Target", "xl/workbook.xml"));
         writer.write_event(Event::Empty(rel))?;
 
+        self.write_relationship(
+            &mut writer,
+            2,
+            "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties",
+            "docProps/core.xml",
+        )?;
+        self.write_relationship(
+            &mut writer,
+            3,
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties",
+            "docProps/app.xml",
+        )?;
+        if !self.workbook.custom_properties().is_empty() {
+            self.write_relationship(
+                &mut writer,
+                4,
+                "http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties",
+                "docProps/custom.xml",
+            )?;
+        }
+
         writer.write_event(Event::End(BytesEnd::new("Relationships")))?;
 
         zip.write_all(writer.into_inner().into_inner().as_slice())?;
         Ok(())
     }
 
-    fn write_workbook_xml(
+    fn write_core_properties<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+    ) -> Result<()> {
+        zip.start_file("docProps/core.xml", options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut core = BytesStart::new("cp:coreProperties");
+        core.push_attribute((
+            "xmlns:cp",
+            "http://schemas.openxmlformats.org/package/2006/metadata/core-properties",
+        ));
+        core.push_attribute(("xmlns:dc", "http://purl.org/dc/elements/1.1/"));
+        core.push_attribute(("xmlns:dcterms", "http://purl.org/dc/terms/"));
+        core.push_attribute(("xmlns:dcmitype", "http://purl.org/dc/dcmitype/"));
+        core.push_attribute(("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"));
+        writer.write_event(Event::Start(core))?;
+
+        let properties = self.workbook.properties();
+        let author = properties.get_author();
+        let elements = [
+            ("dc:title", properties.get_title()),
+            ("dc:subject", properties.get_subject()),
+            ("dc:creator", author),
+            ("cp:keywords", properties.get_keywords()),
+            ("dc:description", properties.get_comments()),
+            ("cp:lastModifiedBy", author),
+        ];
+        for (name, value) in elements {
+            if let Some(value) = value {
+                self.write_text_element(&mut writer, name, value)?;
+            }
+        }
+
+        let created = properties.get_created().unwrap_or(self.saved_at);
+        for (name, timestamp) in [
+            ("dcterms:created", created),
+            ("dcterms:modified", self.saved_at),
+        ] {
+            let mut elem = BytesStart::new(name);
+            elem.push_attribute(("xsi:type", "dcterms:W3CDTF"));
+            writer.write_event(Event::Start(elem))?;
+            writer.write_event(Event::Text(BytesText::new(&timestamp.to_w3cdtf())))?;
+            writer.write_event(Event::End(BytesEnd::new(name)))?;
+        }
+
+        for (name, value) in [
+            ("cp:category", properties.get_category()),
+            ("cp:contentStatus", properties.get_status()),
+        ] {
+            if let Some(value) = value {
+                self.write_text_element(&mut writer, name, value)?;
+            }
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("cp:coreProperties")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_app_properties<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+    ) -> Result<()> {
+        zip.start_file("docProps/app.xml", options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut props = BytesStart::new("Properties");
+        props.push_attribute((
+            "xmlns",
+            "http://schemas.openxmlformats.org/officeDocument/2006/extended-properties",
+        ));
+        props.push_attribute((
+            "xmlns:vt",
+            "http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes",
+        ));
+        writer.write_event(Event::Start(props))?;
+
+        self.write_text_element(&mut writer, "Application", "Microsoft Excel")?;
+        self.write_text_element(&mut writer, "DocSecurity", "0")?;
+        self.write_text_element(&mut writer, "ScaleCrop", "false")?;
+
+        let worksheets = self.workbook.worksheets();
+        let sheet_count = worksheets.len().to_string();
+
+        writer.write_event(Event::Start(BytesStart::new("HeadingPairs")))?;
+        let mut vector = BytesStart::new("vt:vector");
+        vector.push_attribute(("size", "2"));
+        vector.push_attribute(("baseType", "variant"));
+        writer.write_event(Event::Start(vector))?;
+        writer.write_event(Event::Start(BytesStart::new("vt:variant")))?;
+        self.write_text_element(&mut writer, "vt:lpstr", "Worksheets")?;
+        writer.write_event(Event::End(BytesEnd::new("vt:variant")))?;
+        writer.write_event(Event::Start(BytesStart::new("vt:variant")))?;
+        self.write_text_element(&mut writer, "vt:i4", &sheet_count)?;
+        writer.write_event(Event::End(BytesEnd::new("vt:variant")))?;
+        writer.write_event(Event::End(BytesEnd::new("vt:vector")))?;
+        writer.write_event(Event::End(BytesEnd::new("HeadingPairs")))?;
+
+        writer.write_event(Event::Start(BytesStart::new("TitlesOfParts")))?;
+        let mut vector = BytesStart::new("vt:vector");
+        vector.push_attribute(("size", sheet_count.as_str()));
+        vector.push_attribute(("baseType", "lpstr"));
+        writer.write_event(Event::Start(vector))?;
+        for worksheet in worksheets {
+            self.write_text_element(&mut writer, "vt:lpstr", worksheet.name())?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("vt:vector")))?;
+        writer.write_event(Event::End(BytesEnd::new("TitlesOfParts")))?;
+
+        let properties = self.workbook.properties();
+        if let Some(manager) = properties.get_manager() {
+            self.write_text_element(&mut writer, "Manager", manager)?;
+        }
+        if let Some(company) = properties.get_company() {
+            self.write_text_element(&mut writer, "Company", company)?;
+        }
+        self.write_text_element(&mut writer, "LinksUpToDate", "false")?;
+        self.write_text_element(&mut writer, "SharedDoc", "false")?;
+        if let Some(base) = properties.get_hyperlink_base() {
+            self.write_text_element(&mut writer, "HyperlinkBase", base)?;
+        }
+        self.write_text_element(&mut writer, "HyperlinksChanged", "false")?;
+        self.write_text_element(&mut writer, "AppVersion", "12.0000")?;
+
+        writer.write_event(Event::End(BytesEnd::new("Properties")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_custom_properties<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+    ) -> Result<()> {
+        zip.start_file("docProps/custom.xml", options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut props = BytesStart::new("Properties");
+        props.push_attribute((
+            "xmlns",
+            "http://schemas.openxmlformats.org/officeDocument/2006/custom-properties",
+        ));
+        props.push_attribute((
+            "xmlns:vt",
+            "http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes",
+        ));
+        writer.write_event(Event::Start(props))?;
+
+        // Property ids 0 and 1 are reserved by the property set format.
+        for (pid, (name, value)) in (2..).zip(self.workbook.custom_properties()) {
+            let mut property = BytesStart::new("property");
+            property.push_attribute(("fmtid", "{D5CDD505-2E9C-101B-9397-08002B2CF9AE}"));
+            property.push_attribute(("pid", pid.to_string().as_str()));
+            property.push_attribute(("name", name.as_str()));
+            writer.write_event(Event::Start(property))?;
+            self.write_text_element(&mut writer, value.vt_type(), &value.vt_value())?;
+            writer.write_event(Event::End(BytesEnd::new("property")))?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("Properties")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_workbook_xml<W: Write + Seek>(
         &self,
-        zip: &mut ZipWriter<File>,
+        zip: &mut ZipWriter<W>,
         options: FileOptions,
     ) -> Result<()> {
         zip.start_file("xl/workbook.xml", options)?;
@@ -145,26 +538,152 @@ impl<'a> XlsxWriter<'a> {
         ));
         writer.write_event(Event::Start(workbook))?;
 
+        if self.workbook.is_structure_protected() {
+            let mut protection = BytesStart::new("workbookProtection");
+            if let Some(password) = self.workbook.structure_password() {
+                self.push_password_attributes(&mut protection, "workbook", password);
+            }
+            protection.push_attribute(("lockStructure", "1"));
+            writer.write_event(Event::Empty(protection))?;
+        }
+
+        writer.write_event(Event::Start(BytesStart::new("bookViews")))?;
+        let mut book_view = BytesStart::new("workbookView");
+        let active_tab = self.workbook.active_sheet()?;
+        if active_tab > 0 {
+            book_view.push_attribute(("activeTab", active_tab.to_string().as_str()));
+        }
+        writer.write_event(Event::Empty(book_view))?;
+        writer.write_event(Event::End(BytesEnd::new("bookViews")))?;
+
         writer.write_event(Event::Start(BytesStart::new("sheets")))?;
 
         for (idx, worksheet) in self.workbook.worksheets().iter().enumerate() {
             let mut sheet = BytesStart::new("sheet");
             sheet.push_attribute(("name", worksheet.name()));
             sheet.push_attribute(("sheetId", (idx + 1).to_string().as_str()));
+            if let Some(state) = worksheet.visibility().as_state() {
+                sheet.push_attribute(("state", state));
+            }
             sheet.push_attribute(("r:id", format!("rId{}", idx + 1).as_str()));
             writer.write_event(Event::Empty(sheet))?;
         }
 
         writer.write_event(Event::End(BytesEnd::new("sheets")))?;
+
+        let defined_names = self.defined_names();
+        if !defined_names.is_empty() {
+            writer.write_event(Event::Start(BytesStart::new("definedNames")))?;
+            for defined_name in &defined_names {
+                let mut elem = BytesStart::new("definedName");
+                elem.push_attribute(("name", defined_name.name()));
+                if let Some(index) = defined_name.sheet_scope() {
+                    elem.push_attribute(("localSheetId", index.to_string().as_str()));
+                }
+                if defined_name.is_hidden() {
+                    elem.push_attribute(("hidden", "1"));
+                }
+                writer.write_event(Event::Start(elem))?;
+                // Sheet names in references are quoted with apostrophes, which
+                // don't need escaping in element text.
+                writer.write_event(Event::Text(BytesText::from_escaped(partial_escape(
+                    defined_name.refers_to(),
+                ))))?;
+                writer.write_event(Event::End(BytesEnd::new("definedName")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("definedNames")))?;
+        }
         writer.write_event(Event::End(BytesEnd::new("workbook")))?;
 
         zip.write_all(writer.into_inner().into_inner().as_slice())?;
         Ok(())
     }
 
-    fn write_workbook_rels(
+    /// Adds a password hash to a protection element. Workbook protection
+    /// prefixes the attribute names with `workbook`.
+    fn push_password_attributes(
+        &self,
+        elem: &mut BytesStart,
+        prefix: &str,
+        password: &PasswordHash,
+    ) {
+        let attribute = |name: &str| {
+            if prefix.is_empty() {
+                name.to_string()
+            } else {
+                let mut chars = name.chars();
+                let first = chars.next().map(|c| c.to_ascii_uppercase());
+                format!("{}{}{}", prefix, first.unwrap_or_default(), chars.as_str())
+            }
+        };
+        match password {
+            PasswordHash::Legacy(hash) => {
+                elem.push_attribute((
+                    attribute("password").as_str(),
+                    format!("{:04X}", hash).as_str(),
+                ));
+            }
+            PasswordHash::Sha512 {
+                hash,
+                salt,
+                spin_count,
+            } => {
+                elem.push_attribute((attribute("algorithmName").as_str(), "SHA-512"));
+                elem.push_attribute((attribute("hashValue").as_str(), hash.as_str()));
+                elem.push_attribute((attribute("saltValue").as_str(), salt.as_str()));
+                elem.push_attribute((
+                    attribute("spinCount").as_str(),
+                    spin_count.to_string().as_str(),
+                ));
+            }
+        }
+    }
+
+    /// User-defined names followed by the built-in print names each sheet's
+    /// page setup needs.
+    fn defined_names(&self) -> Vec<DefinedName> {
+        let mut names = self.workbook.defined_names().to_vec();
+        for (idx, worksheet) in self.workbook.worksheets().iter().enumerate() {
+            let page_setup = worksheet.page_setup();
+            let sheet = quote_sheet_name(worksheet.name());
+            let builtin = |name: &str, refers_to: String| {
+                DefinedName::new(format!("{}{}", BUILTIN_PREFIX, name), refers_to).scope(idx)
+            };
+
+            if let Some((first_row, first_col, last_row, last_col)) = page_setup.get_print_area() {
+                names.push(builtin(
+                    "Print_Area",
+                    format!(
+                        "{}!{}:{}",
+                        sheet,
+                        absolute_cell_reference(first_row, first_col),
+                        absolute_cell_reference(last_row, last_col)
+                    ),
+                ));
+            }
+
+            let mut titles = Vec::new();
+            if let Some((first, last)) = page_setup.get_repeat_cols() {
+                titles.push(format!(
+                    "{}!${}:${}",
+                    sheet,
+                    col_to_letter(first),
+                    col_to_letter(last)
+                ));
+            }
+            if let Some((first, last)) = page_setup.get_repeat_rows() {
+                titles.push(format!("{}!${}:${}", sheet, first + 1, last + 1));
+            }
+            if !titles.is_empty() {
+                names.push(builtin("Print_Titles", titles.join(",")));
+            }
+        }
+        names
+    }
+
+    fn write_workbook_rels<W: Write + Seek>(
         &self,
-        zip: &mut ZipWriter<File>,
+        zip: &mut ZipWriter<W>,
         options: FileOptions,
     ) -> Result<()> {
         zip.start_file("xl/_rels/workbook.xml.rels", options)?;
@@ -190,119 +709,2306 @@ impl<'a> XlsxWriter<'a> {
             writer.write_event(Event::Empty(rel))?;
         }
 
+        let mut styles_rel = BytesStart::new("Relationship");
+        styles_rel.push_attribute((
+            "Id",
+            format!("rId{}", self.workbook.worksheet_count() + 1).as_str(),
+        ));
+        styles_rel.push_attribute((
+            "Type",
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles",
+        ));
+        styles_rel.push_attribute(("Target", "styles.xml"));
+        writer.write_event(Event::Empty(styles_rel))?;
+
         writer.write_event(Event::End(BytesEnd::new("Relationships")))?;
 
         zip.write_all(writer.into_inner().into_inner().as_slice())?;
         Ok(())
     }
 
-    fn write_worksheet_xml(
+    fn write_styles_xml<W: Write + Seek>(
         &self,
-        zip: &mut ZipWriter<File>,
+        zip: &mut ZipWriter<W>,
         options: FileOptions,
-        idx: usize,
-        worksheet: &Worksheet,
     ) -> Result<()> {
-        zip.start_file(format!("xl/worksheets/sheet{}.xml", idx + 1), options)?;
+        zip.start_file("xl/styles.xml", options)?;
 
         let mut writer = Writer::new(Cursor::new(Vec::new()));
         writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
 
-        let mut ws = BytesStart::new("worksheet");
-        ws.push_attribute((
+        let mut style_sheet = BytesStart::new("styleSheet");
+        style_sheet.push_attribute((
             "xmlns",
             "http://schemas.openxmlformats.org/spreadsheetml/2006/main",
         ));
-        ws.push_attribute((
-            "xmlns:r",
-            "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
-        ));
-        writer.write_event(Event::Start(ws))?;
+        writer.write_event(Event::Start(style_sheet))?;
 
-        writer.write_event(Event::Start(BytesStart::new("sheetData")))?;
+        let num_formats = self.styles.num_formats();
+        if !num_formats.is_empty() {
+            let mut num_fmts = BytesStart::new("numFmts");
+            num_fmts.push_attribute(("count", num_formats.len().to_string().as_str()));
+            writer.write_event(Event::Start(num_fmts))?;
+            for code in num_formats {
+                self.write_num_format(&mut writer, code)?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("numFmts")))?;
+        }
 
-        let cells = worksheet.cells();
-        if !cells.is_empty() {
-            // Group cells by row using BTreeMap for sorted order
-            let mut rows_map: BTreeMap<u32, BTreeMap<u32, &CellValue>> = BTreeMap::new();
-            for ((row, col), value) in cells.iter() {
-                rows_map
-                    .entry(*row)
-                    .or_default()
-                    .insert(*col, value);
-            }
-
-            // Write rows in order
-            for (row, cols) in rows_map {
-                let mut row_elem = BytesStart::new("row");
-                row_elem.push_attribute(("r", (row + 1).to_string().as_str()));
-                writer.write_event(Event::Start(row_elem))?;
-
-                for (col, value) in cols {
-                    self.write_cell(&mut writer, row, col, value)?;
-                }
+        // The default font, the two fills Excel reserves and an empty border
+        // come first, followed by those used by cell formats.
+        let fonts = self.styles.fonts();
+        let mut fonts_elem = BytesStart::new("fonts");
+        fonts_elem.push_attribute(("count", fonts.len().to_string().as_str()));
+        writer.write_event(Event::Start(fonts_elem))?;
+        for font in fonts {
+            self.write_font(&mut writer, "font", font)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("fonts")))?;
+
+        let fills = self.styles.fills();
+        let mut fills_elem = BytesStart::new("fills");
+        fills_elem.push_attribute(("count", (fills.len() + 2).to_string().as_str()));
+        writer.write_event(Event::Start(fills_elem))?;
+        for pattern in ["none", "gray125"] {
+            writer.write_event(Event::Start(BytesStart::new("fill")))?;
+            let mut pattern_fill = BytesStart::new("patternFill");
+            pattern_fill.push_attribute(("patternType", pattern));
+            writer.write_event(Event::Empty(pattern_fill))?;
+            writer.write_event(Event::End(BytesEnd::new("fill")))?;
+        }
+        for color in fills {
+            writer.write_event(Event::Start(BytesStart::new("fill")))?;
+            let mut pattern_fill = BytesStart::new("patternFill");
+            pattern_fill.push_attribute(("patternType", "solid"));
+            writer.write_event(Event::Start(pattern_fill))?;
+            self.write_color(&mut writer, "fgColor", *color)?;
+            let mut bg_color = BytesStart::new("bgColor");
+            bg_color.push_attribute(("indexed", "64"));
+            writer.write_event(Event::Empty(bg_color))?;
+            writer.write_event(Event::End(BytesEnd::new("patternFill")))?;
+            writer.write_event(Event::End(BytesEnd::new("fill")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("fills")))?;
+
+        let borders = self.styles.borders();
+        let mut borders_elem = BytesStart::new("borders");
+        borders_elem.push_attribute(("count", (borders.len() + 1).to_string().as_str()));
+        writer.write_event(Event::Start(borders_elem))?;
+        self.write_border(&mut writer, None)?;
+        for color in borders {
+            self.write_border(&mut writer, Some(*color))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("borders")))?;
+
+        let mut cell_style_xfs = BytesStart::new("cellStyleXfs");
+        cell_style_xfs.push_attribute(("count", "1"));
+        writer.write_event(Event::Start(cell_style_xfs))?;
+        let mut xf = BytesStart::new("xf");
+        xf.push_attribute(("numFmtId", "0"));
+        xf.push_attribute(("fontId", "0"));
+        xf.push_attribute(("fillId", "0"));
+        xf.push_attribute(("borderId", "0"));
+        writer.write_event(Event::Empty(xf))?;
+        writer.write_event(Event::End(BytesEnd::new("cellStyleXfs")))?;
 
-                writer.write_event(Event::End(BytesEnd::new("row")))?;
+        let xfs = self.styles.xfs();
+        let mut cell_xfs = BytesStart::new("cellXfs");
+        cell_xfs.push_attribute(("count", xfs.len().to_string().as_str()));
+        writer.write_event(Event::Start(cell_xfs))?;
+        for format in xfs {
+            self.write_xf(&mut writer, format)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("cellXfs")))?;
+
+        let mut cell_styles = BytesStart::new("cellStyles");
+        cell_styles.push_attribute(("count", "1"));
+        writer.write_event(Event::Start(cell_styles))?;
+        let mut normal = BytesStart::new("cellStyle");
+        normal.push_attribute(("name", "Normal"));
+        normal.push_attribute(("xfId", "0"));
+        normal.push_attribute(("builtinId", "0"));
+        writer.write_event(Event::Empty(normal))?;
+        writer.write_event(Event::End(BytesEnd::new("cellStyles")))?;
+
+        let dxfs = self.styles.dxfs();
+        let mut dxfs_elem = BytesStart::new("dxfs");
+        dxfs_elem.push_attribute(("count", dxfs.len().to_string().as_str()));
+        if dxfs.is_empty() {
+            writer.write_event(Event::Empty(dxfs_elem))?;
+        } else {
+            writer.write_event(Event::Start(dxfs_elem))?;
+            for format in dxfs {
+                self.write_dxf(&mut writer, format)?;
             }
+            writer.write_event(Event::End(BytesEnd::new("dxfs")))?;
         }
 
-        writer.write_event(Event::End(BytesEnd::new("sheetData")))?;
-        writer.write_event(Event::End(BytesEnd::new("worksheet")))?;
+        writer.write_event(Event::End(BytesEnd::new("styleSheet")))?;
 
         zip.write_all(writer.into_inner().into_inner().as_slice())?;
         Ok(())
     }
 
-    fn write_cell(
+    /// Writes a `<font>` for the style sheet or an `<rPr>` for a rich text
+    /// run. The two share their children except for the font name.
+    fn write_font(
         &self,
         writer: &mut Writer<Cursor<Vec<u8>>>,
-        row: u32,
-        col: u32,
-        value: &CellValue,
+        tag: &str,
+        font: &Format,
     ) -> Result<()> {
-        let cell_ref = cell_reference(row, col);
-
-        match value {
-            CellValue::Empty => {}
-            CellValue::String(s) => {
-                let mut cell = BytesStart::new("c");
-                cell.push_attribute(("r", cell_ref.as_str()));
-                cell.push_attribute(("t", "inlineStr"));
-                writer.write_event(Event::Start(cell))?;
+        writer.write_event(Event::Start(BytesStart::new(tag)))?;
+        if font.is_bold() {
+            writer.write_event(Event::Empty(BytesStart::new("b")))?;
+        }
+        if font.is_italic() {
+            writer.write_event(Event::Empty(BytesStart::new("i")))?;
+        }
+        if font.is_strikeout() {
+            writer.write_event(Event::Empty(BytesStart::new("strike")))?;
+        }
+        if font.is_underline() {
+            writer.write_event(Event::Empty(BytesStart::new("u")))?;
+        }
+        self.write_val_element(writer, "sz", "11")?;
+        if let Some(color) = font.get_font_color() {
+            self.write_color(writer, "color", color)?;
+        }
+        let name_tag = if tag == "rPr" { "rFont" } else { "name" };
+        self.write_val_element(writer, name_tag, "Calibri")?;
+        self.write_val_element(writer, "family", "2")?;
+        writer.write_event(Event::End(BytesEnd::new(tag)))?;
+        Ok(())
+    }
 
-                writer.write_event(Event::Start(BytesStart::new("is")))?;
-                writer.write_event(Event::Start(BytesStart::new("t")))?;
-                writer.write_event(Event::Text(BytesText::new(s)))?;
-                writer.write_event(Event::End(BytesEnd::new("t")))?;
-                writer.write_event(Event::End(BytesEnd::new("is")))?;
+    fn write_xf(&self, writer: &mut Writer<Cursor<Vec<u8>>>, format: &Format) -> Result<()> {
+        let num_fmt_id = format
+            .get_num_format()
+            .and_then(|code| self.styles.num_format_id(code))
+            .unwrap_or(0);
+        let font_id = self.styles.font_id(format);
+        let fill_id = self.styles.fill_id(format);
+        let border_id = self.styles.border_id(format);
 
-                writer.write_event(Event::End(BytesEnd::new("c")))?;
+        let mut xf = BytesStart::new("xf");
+        xf.push_attribute(("numFmtId", num_fmt_id.to_string().as_str()));
+        xf.push_attribute(("fontId", font_id.to_string().as_str()));
+        xf.push_attribute(("fillId", fill_id.to_string().as_str()));
+        xf.push_attribute(("borderId", border_id.to_string().as_str()));
+        xf.push_attribute(("xfId", "0"));
+        let applied = [
+            ("applyNumberFormat", num_fmt_id != 0),
+            ("applyFont", font_id != 0),
+            ("applyFill", fill_id != 0),
+            ("applyBorder", border_id != 0),
+            ("applyProtection", format.has_protection()),
+        ];
+        for (name, apply) in applied {
+            if apply {
+                xf.push_attribute((name, "1"));
             }
-            CellValue::Number(n) => {
-                let mut cell = BytesStart::new("c");
-                cell.push_attribute(("r", cell_ref.as_str()));
-                writer.write_event(Event::Start(cell))?;
+        }
+
+        if !format.has_protection() {
+            writer.write_event(Event::Empty(xf))?;
+            return Ok(());
+        }
+        writer.write_event(Event::Start(xf))?;
+        let mut protection = BytesStart::new("protection");
+        if !format.is_locked() {
+            protection.push_attribute(("locked", "0"));
+        }
+        if format.is_hidden() {
+            protection.push_attribute(("hidden", "1"));
+        }
+        writer.write_event(Event::Empty(protection))?;
+        writer.write_event(Event::End(BytesEnd::new("xf")))?;
+        Ok(())
+    }
 
-                writer.write_event(Event::Start(BytesStart::new("v")))?;
-                writer.write_event(Event::Text(BytesText::new(&n.to_string())))?;
-                writer.write_event(Event::End(BytesEnd::new("v")))?;
+    fn write_dxf(&self, writer: &mut Writer<Cursor<Vec<u8>>>, format: &Format) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new("dxf")))?;
 
-                writer.write_event(Event::End(BytesEnd::new("c")))?;
+        if format.has_font() {
+            writer.write_event(Event::Start(BytesStart::new("font")))?;
+            if format.is_bold() {
+                writer.write_event(Event::Empty(BytesStart::new("b")))?;
             }
-            CellValue::Boolean(b) => {
-                let mut cell = BytesStart::new("c");
-                cell.push_attribute(("r", cell_ref.as_str()));
-                cell.push_attribute(("t", "b"));
-                writer.write_event(Event::Start(cell))?;
+            if format.is_italic() {
+                writer.write_event(Event::Empty(BytesStart::new("i")))?;
+            }
+            if format.is_strikeout() {
+                writer.write_event(Event::Empty(BytesStart::new("strike")))?;
+            }
+            if format.is_underline() {
+                writer.write_event(Event::Empty(BytesStart::new("u")))?;
+            }
+            if let Some(color) = format.get_font_color() {
+                self.write_color(writer, "color", color)?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("font")))?;
+        }
+
+        if let Some(code) = format.get_num_format() {
+            self.write_num_format(writer, code)?;
+        }
+
+        if let Some(color) = format.get_fill_color() {
+            writer.write_event(Event::Start(BytesStart::new("fill")))?;
+            writer.write_event(Event::Start(BytesStart::new("patternFill")))?;
+            // Differential fills use the background color for solid fills.
+            self.write_color(writer, "bgColor", color)?;
+            writer.write_event(Event::End(BytesEnd::new("patternFill")))?;
+            writer.write_event(Event::End(BytesEnd::new("fill")))?;
+        }
+
+        if let Some(color) = format.get_border_color() {
+            self.write_border(writer, Some(color))?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("dxf")))?;
+        Ok(())
+    }
 
-                writer.write_event(Event::Start(BytesStart::new("v")))?;
-                writer.write_event(Event::Text(BytesText::new(if *b { "1" } else { "0" })))?;
-                writer.write_event(Event::End(BytesEnd::new("v")))?;
+    fn write_num_format(&self, writer: &mut Writer<Cursor<Vec<u8>>>, code: &str) -> Result<()> {
+        let id = self.styles.num_format_id(code).unwrap_or_default();
+        let mut num_fmt = BytesStart::new("numFmt");
+        num_fmt.push_attribute(("numFmtId", id.to_string().as_str()));
+        num_fmt.push_attribute(("formatCode", code));
+        writer.write_event(Event::Empty(num_fmt))?;
+        Ok(())
+    }
 
-                writer.write_event(Event::End(BytesEnd::new("c")))?;
+    fn write_border(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        color: Option<Color>,
+    ) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new("border")))?;
+        for side in ["left", "right", "top", "bottom"] {
+            match color {
+                Some(color) => {
+                    let mut elem = BytesStart::new(side);
+                    elem.push_attribute(("style", "thin"));
+                    writer.write_event(Event::Start(elem))?;
+                    self.write_color(writer, "color", color)?;
+                    writer.write_event(Event::End(BytesEnd::new(side)))?;
+                }
+                None => writer.write_event(Event::Empty(BytesStart::new(side)))?,
             }
         }
+        if color.is_none() {
+            writer.write_event(Event::Empty(BytesStart::new("diagonal")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("border")))?;
+        Ok(())
+    }
+
+    fn write_color(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        name: &str,
+        color: Color,
+    ) -> Result<()> {
+        let mut elem = BytesStart::new(name);
+        elem.push_attribute(("rgb", color.to_argb_hex().as_str()));
+        writer.write_event(Event::Empty(elem))?;
+        Ok(())
+    }
 
+    fn write_val_element(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        name: &str,
+        val: &str,
+    ) -> Result<()> {
+        let mut elem = BytesStart::new(name);
+        elem.push_attribute(("val", val));
+        writer.write_event(Event::Empty(elem))?;
         Ok(())
     }
+
+    fn write_worksheet_xml<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+        idx: usize,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        zip.start_file(format!("xl/worksheets/sheet{}.xml", idx + 1), options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut ws = BytesStart::new("worksheet");
+        ws.push_attribute((
+            "xmlns",
+            "http://schemas.openxmlformats.org/spreadsheetml/2006/main",
+        ));
+        ws.push_attribute((
+            "xmlns:r",
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+        ));
+        writer.write_event(Event::Start(ws))?;
+
+        let fit_to_page = worksheet.page_setup().get_fit_to_pages().is_some();
+        if worksheet.tab_color().is_some() || fit_to_page {
+            writer.write_event(Event::Start(BytesStart::new("sheetPr")))?;
+            if let Some(color) = worksheet.tab_color() {
+                self.write_color(&mut writer, "tabColor", color)?;
+            }
+            if fit_to_page {
+                let mut page_setup_pr = BytesStart::new("pageSetUpPr");
+                page_setup_pr.push_attribute(("fitToPage", "1"));
+                writer.write_event(Event::Empty(page_setup_pr))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("sheetPr")))?;
+        }
+
+        let columns: Vec<(u32, u32, usize)> = worksheet
+            .column_format_spans()
+            .into_iter()
+            .map(|(first, last, format)| (first, last, self.styles.xf_id(format).unwrap_or(0)))
+            .collect();
+
+        // Like Excel, the dimension covers every written cell, formatted
+        // empty cells included, and is `A1` for an empty sheet.
+        let data = worksheet.sheet_data();
+        let dimension = data
+            .bounds(|col, cell| self.writes_cell(&columns, col, cell))
+            .map_or_else(|| "A1".to_string(), range_reference);
+        let mut dimension_elem = BytesStart::new("dimension");
+        dimension_elem.push_attribute(("ref", dimension.as_str()));
+        writer.write_event(Event::Empty(dimension_elem))?;
+
+        self.write_sheet_view(&mut writer, idx, worksheet)?;
+
+        self.write_cols(&mut writer, &columns)?;
+        self.write_sheet_data(&mut writer, data, &columns)?;
+
+        if let Some(protection) = worksheet.protection() {
+            let mut elem = BytesStart::new("sheetProtection");
+            if let Some(password) = protection.get_password() {
+                self.push_password_attributes(&mut elem, "", password);
+            }
+            elem.push_attribute(("sheet", "1"));
+            for attribute in protection.attributes() {
+                elem.push_attribute(attribute);
+            }
+            writer.write_event(Event::Empty(elem))?;
+        }
+
+        self.write_conditional_formats(&mut writer, worksheet)?;
+        self.write_data_validations(&mut writer, worksheet)?;
+        self.write_page_setup(&mut writer, worksheet.page_setup())?;
+        self.write_header_footer(&mut writer, worksheet)?;
+        self.write_page_breaks(
+            &mut writer,
+            "rowBreaks",
+            worksheet.h_page_breaks(),
+            MAX_COLS - 1,
+        )?;
+        self.write_page_breaks(
+            &mut writer,
+            "colBreaks",
+            worksheet.v_page_breaks(),
+            MAX_ROWS - 1,
+        )?;
+
+        if worksheet.has_drawing() {
+            let mut drawing = BytesStart::new("drawing");
+            drawing.push_attribute(("r:id", "rId1"));
+            writer.write_event(Event::Empty(drawing))?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("worksheet")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_sheet_view(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        idx: usize,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        let view = worksheet.view();
+        writer.write_event(Event::Start(BytesStart::new("sheetViews")))?;
+
+        let mut sheet_view = BytesStart::new("sheetView");
+        if !view.has_gridlines() {
+            sheet_view.push_attribute(("showGridLines", "0"));
+        }
+        if !view.has_headings() {
+            sheet_view.push_attribute(("showRowColHeaders", "0"));
+        }
+        if view.is_right_to_left() {
+            sheet_view.push_attribute(("rightToLeft", "1"));
+        }
+        if worksheet.is_selected() || idx == self.workbook.active_sheet()? {
+            sheet_view.push_attribute(("tabSelected", "1"));
+        }
+        if view.get_zoom() != 100 {
+            let zoom = view.get_zoom().to_string();
+            sheet_view.push_attribute(("zoomScale", zoom.as_str()));
+            sheet_view.push_attribute(("zoomScaleNormal", zoom.as_str()));
+        }
+        sheet_view.push_attribute(("workbookViewId", "0"));
+
+        match view.get_selection() {
+            Some(bounds) => {
+                writer.write_event(Event::Start(sheet_view))?;
+                let mut selection = BytesStart::new("selection");
+                let active_cell = cell_reference(bounds.0, bounds.1);
+                selection.push_attribute(("activeCell", active_cell.as_str()));
+                selection.push_attribute(("sqref", range_reference(bounds).as_str()));
+                writer.write_event(Event::Empty(selection))?;
+                writer.write_event(Event::End(BytesEnd::new("sheetView")))?;
+            }
+            None => writer.write_event(Event::Empty(sheet_view))?,
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("sheetViews")))?;
+        Ok(())
+    }
+
+    fn write_page_setup(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        page_setup: &PageSetup,
+    ) -> Result<()> {
+        if page_setup.has_print_options() {
+            let mut print_options = BytesStart::new("printOptions");
+            let flags = [
+                ("horizontalCentered", page_setup.is_centered_horizontally()),
+                ("verticalCentered", page_setup.is_centered_vertically()),
+                ("headings", page_setup.has_print_headings()),
+                ("gridLines", page_setup.has_print_gridlines()),
+            ];
+            for (name, enabled) in flags {
+                if enabled {
+                    print_options.push_attribute((name, "1"));
+                }
+            }
+            writer.write_event(Event::Empty(print_options))?;
+        }
+
+        let margins = page_setup.get_margins();
+        let mut page_margins = BytesStart::new("pageMargins");
+        for (name, value) in [
+            ("left", margins.left),
+            ("right", margins.right),
+            ("top", margins.top),
+            ("bottom", margins.bottom),
+            ("header", margins.header),
+            ("footer", margins.footer),
+        ] {
+            page_margins.push_attribute((name, value.to_string().as_str()));
+        }
+        writer.write_event(Event::Empty(page_margins))?;
+
+        if page_setup.has_page_setup() {
+            let mut elem = BytesStart::new("pageSetup");
+            if let Some(paper_size) = page_setup.get_paper_size() {
+                elem.push_attribute(("paperSize", paper_size.to_string().as_str()));
+            }
+            if let Some(scale) = page_setup.get_scale() {
+                elem.push_attribute(("scale", scale.to_string().as_str()));
+            }
+            if let Some((width, height)) = page_setup.get_fit_to_pages() {
+                elem.push_attribute(("fitToWidth", width.to_string().as_str()));
+                elem.push_attribute(("fitToHeight", height.to_string().as_str()));
+            }
+            if let Some(orientation) = page_setup.get_orientation() {
+                elem.push_attribute(("orientation", orientation.as_str()));
+            }
+            writer.write_event(Event::Empty(elem))?;
+        }
+        Ok(())
+    }
+
+    fn write_header_footer(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        let (header, footer) = (worksheet.header(), worksheet.footer());
+        if header.is_none() && footer.is_none() {
+            return Ok(());
+        }
+
+        let both = || header.into_iter().chain(footer);
+        let odd_even = both().any(|hf| hf.get_even_pages().is_some());
+        let first = both().any(|hf| hf.get_first_page().is_some());
+
+        let mut elem = BytesStart::new("headerFooter");
+        if odd_even {
+            elem.push_attribute(("differentOddEven", "1"));
+        }
+        if first {
+            elem.push_attribute(("differentFirst", "1"));
+        }
+        writer.write_event(Event::Start(elem))?;
+
+        // Once a variant is enabled Excel uses it for both the header and the
+        // footer, so a side without its own variant repeats its main text.
+        let variants = [
+            ("oddHeader", header.map(|h| h.text())),
+            ("oddFooter", footer.map(|f| f.text())),
+            (
+                "evenHeader",
+                header
+                    .filter(|_| odd_even)
+                    .map(|h| h.get_even_pages().unwrap_or(h.text())),
+            ),
+            (
+                "evenFooter",
+                footer
+                    .filter(|_| odd_even)
+                    .map(|f| f.get_even_pages().unwrap_or(f.text())),
+            ),
+            (
+                "firstHeader",
+                header
+                    .filter(|_| first)
+                    .map(|h| h.get_first_page().unwrap_or(h.text())),
+            ),
+            (
+                "firstFooter",
+                footer
+                    .filter(|_| first)
+                    .map(|f| f.get_first_page().unwrap_or(f.text())),
+            ),
+        ];
+        for (name, text) in variants {
+            if let Some(text) = text {
+                self.write_text_element(writer, name, text)?;
+            }
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("headerFooter")))?;
+        Ok(())
+    }
+
+    /// Writes manual breaks; `max` is the last column (for row breaks) or
+    /// row (for column breaks) the break line spans.
+    fn write_page_breaks(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        name: &str,
+        breaks: &[u32],
+        max: u32,
+    ) -> Result<()> {
+        if breaks.is_empty() {
+            return Ok(());
+        }
+
+        let count = breaks.len().to_string();
+        let mut elem = BytesStart::new(name);
+        elem.push_attribute(("count", count.as_str()));
+        elem.push_attribute(("manualBreakCount", count.as_str()));
+        writer.write_event(Event::Start(elem))?;
+        for position in breaks {
+            let mut brk = BytesStart::new("brk");
+            brk.push_attribute(("id", position.to_string().as_str()));
+            brk.push_attribute(("max", max.to_string().as_str()));
+            brk.push_attribute(("man", "1"));
+            writer.write_event(Event::Empty(brk))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+
+    fn write_worksheet_rels<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+        idx: usize,
+        drawing_id: usize,
+    ) -> Result<()> {
+        zip.start_file(
+            format!("xl/worksheets/_rels/sheet{}.xml.rels", idx + 1),
+            options,
+        )?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut rels = BytesStart::new("Relationships");
+        rels.push_attribute((
+            "xmlns",
+            "http://schemas.openxmlformats.org/package/2006/relationships",
+        ));
+        writer.write_event(Event::Start(rels))?;
+
+        self.write_relationship(
+            &mut writer,
+            1,
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing",
+            &format!("../drawings/drawing{}.xml", drawing_id),
+        )?;
+
+        writer.write_event(Event::End(BytesEnd::new("Relationships")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_relationship(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        id: usize,
+        rel_type: &str,
+        target: &str,
+    ) -> Result<()> {
+        let mut rel = BytesStart::new("Relationship");
+        rel.push_attribute(("Id", format!("rId{}", id).as_str()));
+        rel.push_attribute(("Type", rel_type));
+        rel.push_attribute(("Target", target));
+        writer.write_event(Event::Empty(rel))?;
+        Ok(())
+    }
+
+    fn write_drawing_xml<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+        sheet_idx: usize,
+        drawing_id: usize,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        zip.start_file(format!("xl/drawings/drawing{}.xml", drawing_id), options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut ws_dr = BytesStart::new("xdr:wsDr");
+        ws_dr.push_attribute((
+            "xmlns:xdr",
+            "http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing",
+        ));
+        ws_dr.push_attribute((
+            "xmlns:a",
+            "http://schemas.openxmlformats.org/drawingml/2006/main",
+        ));
+        writer.write_event(Event::Start(ws_dr))?;
+
+        for (offset, (row, col, chart)) in worksheet.charts().iter().enumerate() {
+            let (width, height) = chart.get_size();
+            let anchor = Anchor::new(*row, *col, 0, 0, width, height);
+            // Shape id 1 is reserved for the drawing itself.
+            let shape_id = offset + 2;
+            let name = format!("Chart {}", self.first_chart_id(sheet_idx) + offset);
+
+            self.write_anchor_start(&mut writer, &anchor)?;
+
+            let mut frame = BytesStart::new("xdr:graphicFrame");
+            frame.push_attribute(("macro", ""));
+            writer.write_event(Event::Start(frame))?;
+
+            writer.write_event(Event::Start(BytesStart::new("xdr:nvGraphicFramePr")))?;
+            let mut c_nv_pr = BytesStart::new("xdr:cNvPr");
+            c_nv_pr.push_attribute(("id", shape_id.to_string().as_str()));
+            c_nv_pr.push_attribute(("name", name.as_str()));
+            writer.write_event(Event::Empty(c_nv_pr))?;
+            writer.write_event(Event::Empty(BytesStart::new("xdr:cNvGraphicFramePr")))?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:nvGraphicFramePr")))?;
+
+            writer.write_event(Event::Start(BytesStart::new("xdr:xfrm")))?;
+            self.write_offset_and_extent(&mut writer, &anchor)?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:xfrm")))?;
+
+            writer.write_event(Event::Start(BytesStart::new("a:graphic")))?;
+            let mut graphic_data = BytesStart::new("a:graphicData");
+            graphic_data.push_attribute((
+                "uri",
+                "http://schemas.openxmlformats.org/drawingml/2006/chart",
+            ));
+            writer.write_event(Event::Start(graphic_data))?;
+            let mut chart_ref = BytesStart::new("c:chart");
+            chart_ref.push_attribute((
+                "xmlns:c",
+                "http://schemas.openxmlformats.org/drawingml/2006/chart",
+            ));
+            chart_ref.push_attribute((
+                "xmlns:r",
+                "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+            ));
+            chart_ref.push_attribute(("r:id", format!("rId{}", offset + 1).as_str()));
+            writer.write_event(Event::Empty(chart_ref))?;
+            writer.write_event(Event::End(BytesEnd::new("a:graphicData")))?;
+            writer.write_event(Event::End(BytesEnd::new("a:graphic")))?;
+
+            writer.write_event(Event::End(BytesEnd::new("xdr:graphicFrame")))?;
+            self.write_anchor_end(&mut writer)?;
+        }
+
+        let chart_count = worksheet.charts().len();
+        for (offset, (row, col, image)) in worksheet.images().iter().enumerate() {
+            let (width, height) = image.display_size();
+            let (x_offset, y_offset) = image.get_offset();
+            let anchor = Anchor::new(*row, *col, x_offset, y_offset, width, height);
+            let shape_id = chart_count + offset + 2;
+            let name = format!("Picture {}", self.first_image_id(sheet_idx) + offset);
+
+            self.write_anchor_start(&mut writer, &anchor)?;
+            writer.write_event(Event::Start(BytesStart::new("xdr:pic")))?;
+
+            writer.write_event(Event::Start(BytesStart::new("xdr:nvPicPr")))?;
+            let mut c_nv_pr = BytesStart::new("xdr:cNvPr");
+            c_nv_pr.push_attribute(("id", shape_id.to_string().as_str()));
+            c_nv_pr.push_attribute(("name", name.as_str()));
+            if let Some(alt_text) = image.get_alt_text() {
+                c_nv_pr.push_attribute(("descr", alt_text));
+            }
+            writer.write_event(Event::Empty(c_nv_pr))?;
+            writer.write_event(Event::Start(BytesStart::new("xdr:cNvPicPr")))?;
+            let mut pic_locks = BytesStart::new("a:picLocks");
+            pic_locks.push_attribute(("noChangeAspect", "1"));
+            writer.write_event(Event::Empty(pic_locks))?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:cNvPicPr")))?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:nvPicPr")))?;
+
+            writer.write_event(Event::Start(BytesStart::new("xdr:blipFill")))?;
+            let mut blip = BytesStart::new("a:blip");
+            blip.push_attribute((
+                "xmlns:r",
+                "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+            ));
+            blip.push_attribute((
+                "r:embed",
+                format!("rId{}", chart_count + offset + 1).as_str(),
+            ));
+            writer.write_event(Event::Empty(blip))?;
+            writer.write_event(Event::Start(BytesStart::new("a:stretch")))?;
+            writer.write_event(Event::Empty(BytesStart::new("a:fillRect")))?;
+            writer.write_event(Event::End(BytesEnd::new("a:stretch")))?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:blipFill")))?;
+
+            writer.write_event(Event::Start(BytesStart::new("xdr:spPr")))?;
+            writer.write_event(Event::Start(BytesStart::new("a:xfrm")))?;
+            self.write_offset_and_extent(&mut writer, &anchor)?;
+            writer.write_event(Event::End(BytesEnd::new("a:xfrm")))?;
+            let mut geometry = BytesStart::new("a:prstGeom");
+            geometry.push_attribute(("prst", "rect"));
+            writer.write_event(Event::Start(geometry))?;
+            writer.write_event(Event::Empty(BytesStart::new("a:avLst")))?;
+            writer.write_event(Event::End(BytesEnd::new("a:prstGeom")))?;
+            writer.write_event(Event::End(BytesEnd::new("xdr:spPr")))?;
+
+            writer.write_event(Event::End(BytesEnd::new("xdr:pic")))?;
+            self.write_anchor_end(&mut writer)?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("xdr:wsDr")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_anchor_start(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        anchor: &Anchor,
+    ) -> Result<()> {
+        let mut elem = BytesStart::new("xdr:twoCellAnchor");
+        elem.push_attribute(("editAs", "oneCell"));
+        writer.write_event(Event::Start(elem))?;
+        self.write_anchor_point(writer, "xdr:from", &anchor.from)?;
+        self.write_anchor_point(writer, "xdr:to", &anchor.to)?;
+        Ok(())
+    }
+
+    fn write_anchor_end(&self, writer: &mut Writer<Cursor<Vec<u8>>>) -> Result<()> {
+        writer.write_event(Event::Empty(BytesStart::new("xdr:clientData")))?;
+        writer.write_event(Event::End(BytesEnd::new("xdr:twoCellAnchor")))?;
+        Ok(())
+    }
+
+    fn write_anchor_point(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        name: &str,
+        point: &AnchorPoint,
+    ) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new(name)))?;
+        self.write_text_element(writer, "xdr:col", &point.col.to_string())?;
+        self.write_text_element(writer, "xdr:colOff", &point.col_offset.to_string())?;
+        self.write_text_element(writer, "xdr:row", &point.row.to_string())?;
+        self.write_text_element(writer, "xdr:rowOff", &point.row_offset.to_string())?;
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+
+    fn write_offset_and_extent(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        anchor: &Anchor,
+    ) -> Result<()> {
+        let mut off = BytesStart::new("a:off");
+        off.push_attribute(("x", anchor.x.to_string().as_str()));
+        off.push_attribute(("y", anchor.y.to_string().as_str()));
+        writer.write_event(Event::Empty(off))?;
+        let mut ext = BytesStart::new("a:ext");
+        ext.push_attribute(("cx", anchor.width.to_string().as_str()));
+        ext.push_attribute(("cy", anchor.height.to_string().as_str()));
+        writer.write_event(Event::Empty(ext))?;
+        Ok(())
+    }
+
+    fn write_drawing_rels<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+        sheet_idx: usize,
+        drawing_id: usize,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        zip.start_file(
+            format!("xl/drawings/_rels/drawing{}.xml.rels", drawing_id),
+            options,
+        )?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut rels = BytesStart::new("Relationships");
+        rels.push_attribute((
+            "xmlns",
+            "http://schemas.openxmlformats.org/package/2006/relationships",
+        ));
+        writer.write_event(Event::Start(rels))?;
+
+        for offset in 0..worksheet.charts().len() {
+            self.write_relationship(
+                &mut writer,
+                offset + 1,
+                "http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart",
+                &format!(
+                    "../charts/chart{}.xml",
+                    self.first_chart_id(sheet_idx) + offset
+                ),
+            )?;
+        }
+
+        // Image relationships follow the chart relationships.
+        let chart_count = worksheet.charts().len();
+        for (offset, (_, _, image)) in worksheet.images().iter().enumerate() {
+            let image_id = self.first_image_id(sheet_idx) + offset;
+            let target = self.media_path(image_id, image).replacen("xl/", "../", 1);
+            self.write_relationship(
+                &mut writer,
+                chart_count + offset + 1,
+                "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image",
+                &target,
+            )?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("Relationships")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_chart_xml<W: Write + Seek>(
+        &self,
+        zip: &mut ZipWriter<W>,
+        options: FileOptions,
+        chart_id: usize,
+        chart: &Chart,
+    ) -> Result<()> {
+        zip.start_file(format!("xl/charts/chart{}.xml", chart_id), options)?;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+        let mut chart_space = BytesStart::new("c:chartSpace");
+        chart_space.push_attribute((
+            "xmlns:c",
+            "http://schemas.openxmlformats.org/drawingml/2006/chart",
+        ));
+        chart_space.push_attribute((
+            "xmlns:a",
+            "http://schemas.openxmlformats.org/drawingml/2006/main",
+        ));
+        chart_space.push_attribute((
+            "xmlns:r",
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+        ));
+        writer.write_event(Event::Start(chart_space))?;
+        writer.write_event(Event::Start(BytesStart::new("c:chart")))?;
+
+        match chart.get_title() {
+            Some(title) => self.write_chart_title(&mut writer, title)?,
+            None => self.write_val_element(&mut writer, "c:autoTitleDeleted", "1")?,
+        }
+
+        writer.write_event(Event::Start(BytesStart::new("c:plotArea")))?;
+        writer.write_event(Event::Empty(BytesStart::new("c:layout")))?;
+        self.write_plot(&mut writer, chart)?;
+        if chart.chart_type().has_axes() {
+            self.write_chart_axes(&mut writer, chart)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("c:plotArea")))?;
+
+        if let Some(position) = chart.get_legend().as_str() {
+            writer.write_event(Event::Start(BytesStart::new("c:legend")))?;
+            self.write_val_element(&mut writer, "c:legendPos", position)?;
+            self.write_val_element(&mut writer, "c:overlay", "0")?;
+            writer.write_event(Event::End(BytesEnd::new("c:legend")))?;
+        }
+
+        self.write_val_element(&mut writer, "c:plotVisOnly", "1")?;
+        writer.write_event(Event::End(BytesEnd::new("c:chart")))?;
+        writer.write_event(Event::End(BytesEnd::new("c:chartSpace")))?;
+
+        zip.write_all(writer.into_inner().into_inner().as_slice())?;
+        Ok(())
+    }
+
+    fn write_plot(&self, writer: &mut Writer<Cursor<Vec<u8>>>, chart: &Chart) -> Result<()> {
+        let chart_type = chart.chart_type();
+        let element = match chart_type {
+            ChartType::Column | ChartType::Bar => "c:barChart",
+            ChartType::Line => "c:lineChart",
+            ChartType::Pie => "c:pieChart",
+            ChartType::Scatter => "c:scatterChart",
+            ChartType::Area => "c:areaChart",
+        };
+        writer.write_event(Event::Start(BytesStart::new(element)))?;
+
+        match chart_type {
+            ChartType::Column => self.write_val_element(writer, "c:barDir", "col")?,
+            ChartType::Bar => self.write_val_element(writer, "c:barDir", "bar")?,
+            ChartType::Scatter => self.write_val_element(writer, "c:scatterStyle", "lineMarker")?,
+            ChartType::Pie => {}
+            ChartType::Line | ChartType::Area => {}
+        }
+        match chart_type {
+            ChartType::Column | ChartType::Bar => {
+                self.write_val_element(writer, "c:grouping", "clustered")?
+            }
+            ChartType::Line | ChartType::Area => {
+                self.write_val_element(writer, "c:grouping", "standard")?
+            }
+            ChartType::Pie | ChartType::Scatter => {}
+        }
+        let vary_colors = if chart_type == ChartType::Pie {
+            "1"
+        } else {
+            "0"
+        };
+        self.write_val_element(writer, "c:varyColors", vary_colors)?;
+
+        for (idx, series) in chart.series().iter().enumerate() {
+            writer.write_event(Event::Start(BytesStart::new("c:ser")))?;
+            self.write_val_element(writer, "c:idx", &idx.to_string())?;
+            self.write_val_element(writer, "c:order", &idx.to_string())?;
+
+            if let Some(name) = series.get_name() {
+                writer.write_event(Event::Start(BytesStart::new("c:tx")))?;
+                match name.strip_prefix('=') {
+                    Some(reference) => {
+                        writer.write_event(Event::Start(BytesStart::new("c:strRef")))?;
+                        self.write_text_element(writer, "c:f", reference)?;
+                        writer.write_event(Event::End(BytesEnd::new("c:strRef")))?;
+                    }
+                    None => self.write_text_element(writer, "c:v", name)?,
+                }
+                writer.write_event(Event::End(BytesEnd::new("c:tx")))?;
+            }
+
+            let line_only = matches!(chart_type, ChartType::Line);
+            if let Some(color) = series.get_color() {
+                writer.write_event(Event::Start(BytesStart::new("c:spPr")))?;
+                if line_only {
+                    writer.write_event(Event::Start(BytesStart::new("a:ln")))?;
+                }
+                writer.write_event(Event::Start(BytesStart::new("a:solidFill")))?;
+                let mut srgb = BytesStart::new("a:srgbClr");
+                srgb.push_attribute(("val", color.to_rgb_hex().as_str()));
+                writer.write_event(Event::Empty(srgb))?;
+                writer.write_event(Event::End(BytesEnd::new("a:solidFill")))?;
+                if line_only {
+                    writer.write_event(Event::End(BytesEnd::new("a:ln")))?;
+                }
+                writer.write_event(Event::End(BytesEnd::new("c:spPr")))?;
+            } else if chart_type == ChartType::Scatter {
+                // Scatter series are markers only unless a line is asked for.
+                writer.write_event(Event::Start(BytesStart::new("c:spPr")))?;
+                let mut ln = BytesStart::new("a:ln");
+                ln.push_attribute(("w", "28575"));
+                writer.write_event(Event::Start(ln))?;
+                writer.write_event(Event::Empty(BytesStart::new("a:noFill")))?;
+                writer.write_event(Event::End(BytesEnd::new("a:ln")))?;
+                writer.write_event(Event::End(BytesEnd::new("c:spPr")))?;
+            }
+
+            if matches!(chart_type, ChartType::Column | ChartType::Bar) {
+                self.write_val_element(writer, "c:invertIfNegative", "0")?;
+            }
+
+            let (cat_element, val_element, cat_ref) = if chart_type == ChartType::Scatter {
+                ("c:xVal", "c:yVal", "c:numRef")
+            } else {
+                ("c:cat", "c:val", "c:strRef")
+            };
+            if let Some(categories) = series.get_categories() {
+                writer.write_event(Event::Start(BytesStart::new(cat_element)))?;
+                writer.write_event(Event::Start(BytesStart::new(cat_ref)))?;
+                self.write_text_element(writer, "c:f", categories)?;
+                writer.write_event(Event::End(BytesEnd::new(cat_ref)))?;
+                writer.write_event(Event::End(BytesEnd::new(cat_element)))?;
+            }
+            writer.write_event(Event::Start(BytesStart::new(val_element)))?;
+            writer.write_event(Event::Start(BytesStart::new("c:numRef")))?;
+            self.write_text_element(writer, "c:f", series.get_values())?;
+            writer.write_event(Event::End(BytesEnd::new("c:numRef")))?;
+            writer.write_event(Event::End(BytesEnd::new(val_element)))?;
+
+            if matches!(chart_type, ChartType::Line | ChartType::Scatter) {
+                self.write_val_element(writer, "c:smooth", "0")?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("c:ser")))?;
+        }
+
+        match chart_type {
+            ChartType::Column | ChartType::Bar => {
+                self.write_val_element(writer, "c:gapWidth", "150")?
+            }
+            ChartType::Line => self.write_val_element(writer, "c:marker", "1")?,
+            ChartType::Pie => self.write_val_element(writer, "c:firstSliceAng", "0")?,
+            ChartType::Scatter | ChartType::Area => {}
+        }
+        if chart_type.has_axes() {
+            self.write_val_element(writer, "c:axId", &CATEGORY_AXIS_ID.to_string())?;
+            self.write_val_element(writer, "c:axId", &VALUE_AXIS_ID.to_string())?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new(element)))?;
+        Ok(())
+    }
+
+    fn write_chart_axes(&self, writer: &mut Writer<Cursor<Vec<u8>>>, chart: &Chart) -> Result<()> {
+        let chart_type = chart.chart_type();
+        // Bar charts swap the axes so categories run down the left side.
+        let (x_pos, y_pos) = if chart_type == ChartType::Bar {
+            ("l", "b")
+        } else {
+            ("b", "l")
+        };
+        let x_element = if chart_type == ChartType::Scatter {
+            "c:valAx"
+        } else {
+            "c:catAx"
+        };
+
+        self.write_chart_axis(
+            writer,
+            x_element,
+            chart.get_x_axis(),
+            CATEGORY_AXIS_ID,
+            VALUE_AXIS_ID,
+            x_pos,
+        )?;
+        self.write_chart_axis(
+            writer,
+            "c:valAx",
+            chart.get_y_axis(),
+            VALUE_AXIS_ID,
+            CATEGORY_AXIS_ID,
+            y_pos,
+        )?;
+        Ok(())
+    }
+
+    fn write_chart_axis(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        element: &str,
+        axis: &ChartAxis,
+        id: u32,
+        cross_id: u32,
+        position: &str,
+    ) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new(element)))?;
+        self.write_val_element(writer, "c:axId", &id.to_string())?;
+
+        writer.write_event(Event::Start(BytesStart::new("c:scaling")))?;
+        self.write_val_element(writer, "c:orientation", "minMax")?;
+        if let Some(max) = axis.get_max() {
+            self.write_val_element(writer, "c:max", &max.to_string())?;
+        }
+        if let Some(min) = axis.get_min() {
+            self.write_val_element(writer, "c:min", &min.to_string())?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("c:scaling")))?;
+
+        self.write_val_element(writer, "c:delete", "0")?;
+        self.write_val_element(writer, "c:axPos", position)?;
+        if axis.has_major_gridlines() {
+            writer.write_event(Event::Empty(BytesStart::new("c:majorGridlines")))?;
+        }
+        if let Some(title) = axis.get_title() {
+            self.write_chart_title(writer, title)?;
+        }
+        let mut num_fmt = BytesStart::new("c:numFmt");
+        num_fmt.push_attribute(("formatCode", "General"));
+        num_fmt.push_attribute(("sourceLinked", "1"));
+        writer.write_event(Event::Empty(num_fmt))?;
+        self.write_val_element(writer, "c:tickLblPos", "nextTo")?;
+        self.write_val_element(writer, "c:crossAx", &cross_id.to_string())?;
+        self.write_val_element(writer, "c:crosses", "autoZero")?;
+
+        if element == "c:catAx" {
+            self.write_val_element(writer, "c:auto", "1")?;
+            self.write_val_element(writer, "c:lblAlgn", "ctr")?;
+            self.write_val_element(writer, "c:lblOffset", "100")?;
+        } else {
+            self.write_val_element(writer, "c:crossBetween", "between")?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new(element)))?;
+        Ok(())
+    }
+
+    fn write_chart_title(&self, writer: &mut Writer<Cursor<Vec<u8>>>, title: &str) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new("c:title")))?;
+        writer.write_event(Event::Start(BytesStart::new("c:tx")))?;
+        writer.write_event(Event::Start(BytesStart::new("c:rich")))?;
+        writer.write_event(Event::Empty(BytesStart::new("a:bodyPr")))?;
+        writer.write_event(Event::Start(BytesStart::new("a:p")))?;
+        writer.write_event(Event::Start(BytesStart::new("a:r")))?;
+        self.write_text_element(writer, "a:t", title)?;
+        writer.write_event(Event::End(BytesEnd::new("a:r")))?;
+        writer.write_event(Event::End(BytesEnd::new("a:p")))?;
+        writer.write_event(Event::End(BytesEnd::new("c:rich")))?;
+        writer.write_event(Event::End(BytesEnd::new("c:tx")))?;
+        self.write_val_element(writer, "c:overlay", "0")?;
+        writer.write_event(Event::End(BytesEnd::new("c:title")))?;
+        Ok(())
+    }
+
+    fn write_conditional_formats(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        for (idx, (bounds, rule)) in worksheet.conditional_formats().iter().enumerate() {
+            let mut elem = BytesStart::new("conditionalFormatting");
+            elem.push_attribute(("sqref", range_reference(*bounds).as_str()));
+            writer.write_event(Event::Start(elem))?;
+            self.write_conditional_rule(writer, rule, idx + 1)?;
+            writer.write_event(Event::End(BytesEnd::new("conditionalFormatting")))?;
+        }
+        Ok(())
+    }
+
+    fn write_conditional_rule(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        rule: &ConditionalFormat,
+        priority: usize,
+    ) -> Result<()> {
+        let mut elem = BytesStart::new("cfRule");
+        elem.push_attribute(("type", rule.rule_type()));
+        if let Some(dxf_id) = rule.format().and_then(|f| self.styles.dxf_id(f)) {
+            elem.push_attribute(("dxfId", dxf_id.to_string().as_str()));
+        }
+        elem.push_attribute(("priority", priority.to_string().as_str()));
+
+        match rule {
+            ConditionalFormat::CellValue { criteria, .. } => {
+                elem.push_attribute(("operator", criteria.operator()));
+                writer.write_event(Event::Start(elem))?;
+                for formula in criteria.formulas() {
+                    self.write_text_element(writer, "formula", formula)?;
+                }
+            }
+            ConditionalFormat::Formula { formula, .. } => {
+                writer.write_event(Event::Start(elem))?;
+                let formula = formula.strip_prefix('=').unwrap_or(formula);
+                self.write_text_element(writer, "formula", formula)?;
+            }
+            ConditionalFormat::TopBottom {
+                rank,
+                percent,
+                bottom,
+                ..
+            } => {
+                if *percent {
+                    elem.push_attribute(("percent", "1"));
+                }
+                if *bottom {
+                    elem.push_attribute(("bottom", "1"));
+                }
+                elem.push_attribute(("rank", rank.to_string().as_str()));
+                writer.write_event(Event::Empty(elem))?;
+                return Ok(());
+            }
+            ConditionalFormat::Average { below, .. } => {
+                if *below {
+                    elem.push_attribute(("aboveAverage", "0"));
+                }
+                writer.write_event(Event::Empty(elem))?;
+                return Ok(());
+            }
+            ConditionalFormat::Duplicates { .. } => {
+                writer.write_event(Event::Empty(elem))?;
+                return Ok(());
+            }
+            ConditionalFormat::ColorScale2 {
+                min_color,
+                max_color,
+            } => {
+                writer.write_event(Event::Start(elem))?;
+                writer.write_event(Event::Start(BytesStart::new("colorScale")))?;
+                self.write_cfvo(writer, "min", None)?;
+                self.write_cfvo(writer, "max", None)?;
+                self.write_color(writer, "color", *min_color)?;
+                self.write_color(writer, "color", *max_color)?;
+                writer.write_event(Event::End(BytesEnd::new("colorScale")))?;
+            }
+            ConditionalFormat::ColorScale3 {
+                min_color,
+                mid_color,
+                max_color,
+            } => {
+                writer.write_event(Event::Start(elem))?;
+                writer.write_event(Event::Start(BytesStart::new("colorScale")))?;
+                self.write_cfvo(writer, "min", None)?;
+                self.write_cfvo(writer, "percentile", Some("50"))?;
+                self.write_cfvo(writer, "max", None)?;
+                self.write_color(writer, "color", *min_color)?;
+                self.write_color(writer, "color", *mid_color)?;
+                self.write_color(writer, "color", *max_color)?;
+                writer.write_event(Event::End(BytesEnd::new("colorScale")))?;
+            }
+            ConditionalFormat::DataBar { color } => {
+                writer.write_event(Event::Start(elem))?;
+                writer.write_event(Event::Start(BytesStart::new("dataBar")))?;
+                self.write_cfvo(writer, "min", None)?;
+                self.write_cfvo(writer, "max", None)?;
+                self.write_color(writer, "color", *color)?;
+                writer.write_event(Event::End(BytesEnd::new("dataBar")))?;
+            }
+            ConditionalFormat::IconSet {
+                style,
+                reverse,
+                show_value,
+            } => {
+                writer.write_event(Event::Start(elem))?;
+                let mut icon_set = BytesStart::new("iconSet");
+                icon_set.push_attribute(("iconSet", style.as_str()));
+                if !*show_value {
+                    icon_set.push_attribute(("showValue", "0"));
+                }
+                if *reverse {
+                    icon_set.push_attribute(("reverse", "1"));
+                }
+                writer.write_event(Event::Start(icon_set))?;
+                // Split the range into equal percentage bands, one per icon.
+                let count = style.icon_count();
+                for band in 0..count {
+                    let threshold = ((band * 100 + count / 2) / count).to_string();
+                    self.write_cfvo(writer, "percent", Some(&threshold))?;
+                }
+                writer.write_event(Event::End(BytesEnd::new("iconSet")))?;
+            }
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("cfRule")))?;
+        Ok(())
+    }
+
+    fn write_cfvo(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        cfvo_type: &str,
+        val: Option<&str>,
+    ) -> Result<()> {
+        let mut cfvo = BytesStart::new("cfvo");
+        cfvo.push_attribute(("type", cfvo_type));
+        if let Some(val) = val {
+            cfvo.push_attribute(("val", val));
+        }
+        writer.write_event(Event::Empty(cfvo))?;
+        Ok(())
+    }
+
+    fn write_data_validations(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        worksheet: &Worksheet,
+    ) -> Result<()> {
+        let validations = worksheet.data_validations();
+        if validations.is_empty() {
+            return Ok(());
+        }
+
+        let mut elem = BytesStart::new("dataValidations");
+        elem.push_attribute(("count", validations.len().to_string().as_str()));
+        writer.write_event(Event::Start(elem))?;
+
+        for (bounds, validation) in validations {
+            self.write_data_validation(writer, *bounds, validation)?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("dataValidations")))?;
+        Ok(())
+    }
+
+    fn write_data_validation(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        bounds: RangeBounds,
+        validation: &DataValidation,
+    ) -> Result<()> {
+        let rule = validation.rule();
+
+        let mut elem = BytesStart::new("dataValidation");
+        elem.push_attribute(("type", rule.validation_type()));
+        if let Some(criteria) = rule.criteria() {
+            elem.push_attribute(("operator", criteria.operator()));
+        }
+        if validation.error_style() != ValidationErrorStyle::Stop {
+            elem.push_attribute(("errorStyle", validation.error_style().as_str()));
+        }
+        if validation.is_ignore_blank() {
+            elem.push_attribute(("allowBlank", "1"));
+        }
+        // Despite its name, showDropDown="1" hides the in-cell dropdown arrow.
+        if !validation.is_show_dropdown() {
+            elem.push_attribute(("showDropDown", "1"));
+        }
+        if validation.input_title().is_some() || validation.input_message().is_some() {
+            elem.push_attribute(("showInputMessage", "1"));
+        }
+        elem.push_attribute(("showErrorMessage", "1"));
+        if let Some(title) = validation.error_title() {
+            elem.push_attribute(("errorTitle", title));
+        }
+        if let Some(message) = validation.error_message() {
+            elem.push_attribute(("error", message));
+        }
+        if let Some(title) = validation.input_title() {
+            elem.push_attribute(("promptTitle", title));
+        }
+        if let Some(message) = validation.input_message() {
+            elem.push_attribute(("prompt", message));
+        }
+        elem.push_attribute(("sqref", range_reference(bounds).as_str()));
+        writer.write_event(Event::Start(elem))?;
+
+        let (formula1, formula2) = rule.formulas();
+        self.write_text_element(writer, "formula1", &formula1)?;
+        if let Some(formula2) = formula2 {
+            self.write_text_element(writer, "formula2", &formula2)?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("dataValidation")))?;
+        Ok(())
+    }
+
+    fn write_text_element(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        name: &str,
+        text: &str,
+    ) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new(name)))?;
+        writer.write_event(Event::Text(BytesText::new(text)))?;
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+
+    /// Writes the `<cols>` element for column styles, given as
+    /// `(first_col, last_col, style)` spans.
+    fn write_cols(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        columns: &[(u32, u32, usize)],
+    ) -> Result<()> {
+        if columns.is_empty() {
+            return Ok(());
+        }
+        writer.write_event(Event::Start(BytesStart::new("cols")))?;
+        for &(first, last, style) in columns {
+            write!(
+                writer.get_mut(),
+                "<col min=\"{}\" max=\"{}\" width=\"{}\" style=\"{}\"/>",
+                first + 1,
+                last + 1,
+                DEFAULT_COL_WIDTH_CHARS,
+                style
+            )?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("cols")))?;
+        Ok(())
+    }
+
+    /// Writes `<sheetData>` in a single pass over the stored rows. Rows and
+    /// cells go straight into the output buffer, reusing one buffer for cell
+    /// references, since this is where almost all of a large file's time
+    /// goes. Cells without a format of their own get the style of their
+    /// column in `columns`, and rows whose cells are all skipped by
+    /// `writes_cell` are left out.
+    fn write_sheet_data(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        data: &SheetData,
+        columns: &[(u32, u32, usize)],
+    ) -> Result<()> {
+        writer.write_event(Event::Start(BytesStart::new("sheetData")))?;
+
+        let mut scratch = String::new();
+        let mut block_spans = None;
+        for (row, cells) in data.rows() {
+            if !cells
+                .iter()
+                .any(|(col, cell)| self.writes_cell(columns, *col, cell))
+            {
+                continue;
+            }
+            let block = row / SPAN_BLOCK_ROWS;
+            let spans = match block_spans {
+                Some((cached, spans)) if cached == block => spans,
+                _ => {
+                    let spans = data.spans(row, |col, cell| self.writes_cell(columns, col, cell));
+                    block_spans = Some((block, spans));
+                    spans
+                }
+            };
+            let out = writer.get_mut();
+            write!(out, "<row r=\"{}\"", row + 1)?;
+            if let Some((first, last)) = spans {
+                write!(out, " spans=\"{}:{}\"", first, last)?;
+            }
+            out.write_all(b">")?;
+
+            for (col, cell) in cells {
+                if self.writes_cell(columns, *col, cell) {
+                    let style = self.cell_style(columns, *col, cell);
+                    self.write_cell(writer, &mut scratch, row, *col, cell.value(), style)?;
+                }
+            }
+
+            writer.get_mut().write_all(b"</row>")?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("sheetData")))?;
+        Ok(())
+    }
+
+    /// The style index of a cell, falling back to its column's style.
+    fn cell_style(&self, columns: &[(u32, u32, usize)], col: u32, cell: &Cell) -> usize {
+        cell.format().map_or_else(
+            || column_style(columns, col),
+            |format| self.styles.xf_id(format).unwrap_or(0),
+        )
+    }
+
+    /// Whether a cell is written to `<sheetData>`. A formatted empty cell
+    /// styled like its column adds nothing, so it's skipped, and `spans` and
+    /// `<dimension>` leave it out too.
+    fn writes_cell(&self, columns: &[(u32, u32, usize)], col: u32, cell: &Cell) -> bool {
+        cell.has_value() || self.cell_style(columns, col, cell) != column_style(columns, col)
+    }
+
+    fn write_cell(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        scratch: &mut String,
+        row: u32,
+        col: u32,
+        value: &CellValue,
+        style: usize,
+    ) -> Result<()> {
+        scratch.clear();
+        push_cell_reference(scratch, row, col);
+        let out = writer.get_mut();
+        write!(out, "<c r=\"{}\"", scratch)?;
+        if style != 0 {
+            write!(out, " s=\"{}\"", style)?;
+        }
+        if let Some(cell_type) = value.xlsx_type() {
+            write!(out, " t=\"{}\"", cell_type)?;
+        }
+
+        match value {
+            CellValue::Empty => out.write_all(b"/>")?,
+            CellValue::String(s) => {
+                out.write_all(b"><is>")?;
+                self.write_string_text(writer, s)?;
+                writer.get_mut().write_all(b"</is></c>")?;
+            }
+            CellValue::RichString(rich) => {
+                out.write_all(b"><is>")?;
+                for run in rich.runs() {
+                    writer.write_event(Event::Start(BytesStart::new("r")))?;
+                    if let Some(font) = run.font() {
+                        self.write_font(writer, "rPr", font)?;
+                    }
+                    self.write_string_text(writer, run.text())?;
+                    writer.write_event(Event::End(BytesEnd::new("r")))?;
+                }
+                writer.get_mut().write_all(b"</is></c>")?;
+            }
+            CellValue::Number(n) if !n.is_finite() => {
+                write!(out, "><v>{}</v></c>", CellError::Num.as_str())?;
+            }
+            CellValue::Number(n) => {
+                // The reference was already written, so the buffer can be
+                // reused for the number text.
+                scratch.clear();
+                write_number(scratch, *n).expect("writing to a String can't fail");
+                out.write_all(b"><v>")?;
+                out.write_all(scratch.as_bytes())?;
+                out.write_all(b"</v></c>")?;
+            }
+            CellValue::Error(e) => {
+                write!(out, "><v>{}</v></c>", e.as_str())?;
+            }
+            CellValue::Boolean(b) => {
+                write!(out, "><v>{}</v></c>", if *b { "1" } else { "0" })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a `<t>` element for cell text, escaping characters XML can't
+    /// hold and keeping leading or trailing whitespace.
+    fn write_string_text(&self, writer: &mut Writer<Cursor<Vec<u8>>>, text: &str) -> Result<()> {
+        let out = writer.get_mut();
+        if needs_space_preserve(text) {
+            out.write_all(br#"<t xml:space="preserve">"#)?;
+        } else {
+            out.write_all(b"<t>")?;
+        }
+        out.write_all(escape(&escape_xstring(text)).as_bytes())?;
+        out.write_all(b"</t>")?;
+        Ok(())
+    }
+}
+
+fn absolute_cell_reference(row: u32, col: u32) -> String {
+    format!("${}${}", col_to_letter(col), row + 1)
+}
+
+/// The style of `col` among ordered `(first_col, last_col, style)` spans.
+fn column_style(columns: &[(u32, u32, usize)], col: u32) -> usize {
+    let index = columns.partition_point(|&(_, last, _)| last < col);
+    match columns.get(index) {
+        Some(&(first, _, style)) if first <= col => style,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::cell::{CellError, NonFinitePolicy};
+    use crate::chart::{ChartSeries, LegendPosition};
+    use crate::conditional_format::{ConditionalCriteria, IconSetStyle};
+    use crate::defined_name::DefinedName;
+    use crate::error::ExcelifyError;
+    use crate::header_footer::HeaderFooter;
+    use crate::page_setup::{Orientation, PageMargins};
+    use crate::properties::{CustomProperty, DocumentProperties};
+    use crate::protection::SheetProtection;
+    use crate::rich_text::RichString;
+    use crate::sheet_view::{SheetView, SheetVisibility};
+    use crate::validation::{ValidationCriteria, ValidationRule};
+
+    fn read_part(workbook: &Workbook, name: &str) -> String {
+        let buffer = XlsxWriter::new(workbook)
+            .write_to(Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(buffer).unwrap();
+        let mut part = archive.by_name(name).unwrap();
+        let mut xml = String::new();
+        part.read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    #[test]
+    fn test_worksheet_xml_cells() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.write(0, 0, 0, "Name").unwrap();
+        wb.write(0, 1, 1, 42.5).unwrap();
+        wb.write(0, 1, 2, true).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<c r="A1" t="inlineStr"><is><t>Name</t></is></c>"#));
+        assert!(xml.contains(r#"<c r="B2"><v>42.5</v></c>"#));
+        assert!(xml.contains(r#"<c r="C2" t="b"><v>1</v></c>"#));
+    }
+
+    #[test]
+    fn test_worksheet_xml_strings() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.write(0, 0, 0, "null\u{0}byte").unwrap();
+        wb.write(0, 0, 1, "  indented").unwrap();
+        wb.write(0, 0, 2, "a < b & \"c\"\r\n").unwrap();
+        wb.write(0, 0, 3, "_x0041_").unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<c r="A1" t="inlineStr"><is><t>null_x0000_byte</t></is></c>"#));
+        assert!(xml.contains(r#"<is><t xml:space="preserve">  indented</t></is>"#));
+        assert!(xml.contains(
+            r#"<is><t xml:space="preserve">a &lt; b &amp; &quot;c&quot;_x000D_
+</t></is>"#
+        ));
+        assert!(xml.contains(r#"<is><t>_x005F_x0041_</t></is>"#));
+        assert!(!xml.contains('\u{0}'));
+    }
+
+    #[test]
+    fn test_worksheet_xml_rich_strings() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let text = RichString::new().text("Status: ").formatted(
+            "overdue",
+            Format::new().bold().font_color(Color::rgb(0xC00000)),
+        );
+        wb.write(0, 0, 0, text).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(concat!(
+            r#"<c r="A1" t="inlineStr"><is>"#,
+            r#"<r><t xml:space="preserve">Status: </t></r>"#,
+            r#"<r><rPr><b/><sz val="11"/><color rgb="FFC00000"/><rFont val="Calibri"/><family val="2"/></rPr>"#,
+            r#"<t>overdue</t></r></is></c>"#
+        )));
+    }
+
+    #[test]
+    fn test_worksheet_xml_numbers() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.set_non_finite_policy(NonFinitePolicy::NumError);
+        wb.write(0, 0, 0, -0.0).unwrap();
+        wb.write(0, 0, 1, 1e308).unwrap();
+        wb.write(0, 0, 2, 5e-324).unwrap();
+        wb.write(0, 0, 3, f64::NAN).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<c r="A1"><v>0</v></c>"#));
+        assert!(xml.contains(r#"<c r="B1"><v>1E+308</v></c>"#));
+        assert!(xml.contains(r#"<c r="C1"><v>5E-324</v></c>"#));
+        assert!(xml.contains(r#"<c r="D1" t="e"><v>#NUM!</v></c>"#));
+        assert!(!xml.contains("NaN"));
+    }
+
+    #[test]
+    fn test_worksheet_xml_errors() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.write(0, 0, 0, CellError::NA).unwrap();
+        wb.write(0, 1, 0, CellError::Div0).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<c r="A1" t="e"><v>#N/A</v></c>"#));
+        assert!(xml.contains(r#"<c r="A2" t="e"><v>#DIV/0!</v></c>"#));
+    }
+
+    #[test]
+    fn test_data_validations_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        let list = DataValidation::new(ValidationRule::List(vec!["Yes".into(), "No".into()]))
+            .input_prompt("Answer", "Pick one");
+        ws.add_data_validation(1, 0, 10, 0, list).unwrap();
+        let rule = ValidationRule::WholeNumber(ValidationCriteria::Between(1.0, 5.0));
+        let bounds =
+            DataValidation::new(rule).error_alert(ValidationErrorStyle::Warning, "Range", "1 to 5");
+        ws.add_data_validation(0, 1, 0, 1, bounds).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<dataValidations count="2">"#));
+        assert!(xml.contains(
+            r#"<dataValidation type="list" allowBlank="1" showInputMessage="1" showErrorMessage="1" promptTitle="Answer" prompt="Pick one" sqref="A2:A11"><formula1>&quot;Yes,No&quot;</formula1></dataValidation>"#
+        ));
+        assert!(xml.contains(
+            r#"<dataValidation type="whole" operator="between" errorStyle="warning" allowBlank="1" showErrorMessage="1" errorTitle="Range" error="1 to 5" sqref="B1"><formula1>1</formula1><formula2>5</formula2></dataValidation>"#
+        ));
+        assert!(xml.find("</sheetData>").unwrap() < xml.find("<dataValidations").unwrap());
+    }
+
+    #[test]
+    fn test_dimension_element() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Empty").unwrap();
+        wb.add_worksheet("Data").unwrap();
+        let ws = wb.get_worksheet_mut(1).unwrap();
+        ws.write_number(2, 1, 1.0).unwrap();
+        ws.write_string(4, 3, "x").unwrap();
+        ws.set_format(1, 5, Format::new().bold()).unwrap();
+
+        let empty = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(empty.contains(r#"<dimension ref="A1"/><sheetViews>"#));
+        let data = read_part(&wb, "xl/worksheets/sheet2.xml");
+        assert!(data.contains(r#"<dimension ref="B2:F5"/>"#));
+    }
+
+    #[test]
+    fn test_save_options() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.write(0, 0, 0, "value").unwrap();
+        let save = |options: SaveOptions| {
+            XlsxWriter::new(&wb)
+                .options(options)
+                .write_to(Cursor::new(Vec::new()))
+                .unwrap()
+                .into_inner()
+        };
+
+        let timestamp = Timestamp::parse("2024-03-01T09:30:00Z").unwrap();
+        let fixed = SaveOptions::new().timestamp(timestamp);
+        let first = save(fixed);
+        assert_eq!(first, save(fixed));
+
+        let mut archive = ZipArchive::new(Cursor::new(first)).unwrap();
+        let mut core = String::new();
+        archive
+            .by_name("docProps/core.xml")
+            .unwrap()
+            .read_to_string(&mut core)
+            .unwrap();
+        assert!(core.contains(
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">2024-03-01T09:30:00Z</dcterms:modified>"#
+        ));
+        let sheet = archive.by_name("xl/worksheets/sheet1.xml").unwrap();
+        assert_eq!(sheet.compression(), zip::CompressionMethod::Deflated);
+        assert_eq!(sheet.last_modified().hour(), 9);
+        drop(sheet);
+
+        let stored = save(fixed.compression_level(0).zip64(true));
+        let mut archive = ZipArchive::new(Cursor::new(stored)).unwrap();
+        let sheet = archive.by_name("xl/worksheets/sheet1.xml").unwrap();
+        assert_eq!(sheet.compression(), zip::CompressionMethod::Stored);
+
+        let invalid = XlsxWriter::new(&wb)
+            .options(SaveOptions::new().compression_level(12))
+            .write_to(Cursor::new(Vec::new()));
+        assert!(matches!(invalid, Err(ExcelifyError::InvalidSaveOptions(_))));
+    }
+
+    #[test]
+    fn test_styles_part_is_registered() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+
+        let content_types = read_part(&wb, "[Content_Types].xml");
+        assert!(content_types.contains(r#"PartName="/xl/styles.xml""#));
+        let rels = read_part(&wb, "xl/_rels/workbook.xml.rels");
+        assert!(rels.contains(r#"Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml""#));
+        let styles = read_part(&wb, "xl/styles.xml");
+        assert!(styles.contains(r#"<dxfs count="0"/>"#));
+    }
+
+    #[test]
+    fn test_conditional_formats_xml() {
+        let highlight = Format::new()
+            .bold()
+            .font_color(Color::rgb(0x9C0006))
+            .fill_color(Color::rgb(0xFFC7CE));
+
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        let rules = [
+            ConditionalFormat::CellValue {
+                criteria: ConditionalCriteria::GreaterThan("100".into()),
+                format: highlight.clone(),
+            },
+            ConditionalFormat::TopBottom {
+                rank: 10,
+                percent: true,
+                bottom: true,
+                format: highlight.clone(),
+            },
+            ConditionalFormat::ColorScale3 {
+                min_color: Color::rgb(0xF8696B),
+                mid_color: Color::rgb(0xFFEB84),
+                max_color: Color::rgb(0x63BE7B),
+            },
+            ConditionalFormat::IconSet {
+                style: IconSetStyle::ThreeArrows,
+                reverse: false,
+                show_value: true,
+            },
+        ];
+        for rule in rules {
+            ws.add_conditional_format(0, 0, 9, 0, rule).unwrap();
+        }
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"<conditionalFormatting sqref="A1:A10"><cfRule type="cellIs" dxfId="0" priority="1" operator="greaterThan"><formula>100</formula></cfRule></conditionalFormatting>"#
+        ));
+        assert!(xml.contains(
+            r#"<cfRule type="top10" dxfId="0" priority="2" percent="1" bottom="1" rank="10"/>"#
+        ));
+        assert!(xml.contains(
+            r#"<cfRule type="colorScale" priority="3"><colorScale><cfvo type="min"/><cfvo type="percentile" val="50"/><cfvo type="max"/><color rgb="FFF8696B"/><color rgb="FFFFEB84"/><color rgb="FF63BE7B"/></colorScale></cfRule>"#
+        ));
+        assert!(xml.contains(
+            r#"<iconSet iconSet="3Arrows"><cfvo type="percent" val="0"/><cfvo type="percent" val="33"/><cfvo type="percent" val="67"/></iconSet>"#
+        ));
+
+        let styles = read_part(&wb, "xl/styles.xml");
+        assert!(styles.contains(
+            r#"<dxfs count="1"><dxf><font><b/><color rgb="FF9C0006"/></font><fill><patternFill><bgColor rgb="FFFFC7CE"/></patternFill></fill></dxf></dxfs>"#
+        ));
+    }
+
+    #[test]
+    fn test_chart_parts() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.add_worksheet("Dashboard").unwrap();
+        let chart = Chart::new(ChartType::Column)
+            .title("Revenue")
+            .add_series(
+                ChartSeries::new("=Data!$B$2:$B$5")
+                    .categories("Data!$A$2:$A$5")
+                    .name("2024")
+                    .color(Color::rgb(0x4472C4)),
+            )
+            .legend(LegendPosition::Bottom);
+        let ws = wb.get_worksheet_mut(1).unwrap();
+        ws.insert_chart(1, 1, chart.clone()).unwrap();
+        ws.insert_chart(20, 1, chart.legend(LegendPosition::None))
+            .unwrap();
+
+        let content_types = read_part(&wb, "[Content_Types].xml");
+        assert!(content_types.contains(r#"PartName="/xl/drawings/drawing1.xml""#));
+        assert!(content_types.contains(r#"PartName="/xl/charts/chart2.xml""#));
+
+        let sheet = read_part(&wb, "xl/worksheets/sheet2.xml");
+        assert!(sheet.contains(r#"<drawing r:id="rId1"/>"#));
+        assert!(!read_part(&wb, "xl/worksheets/sheet1.xml").contains("<drawing"));
+
+        let sheet_rels = read_part(&wb, "xl/worksheets/_rels/sheet2.xml.rels");
+        assert!(sheet_rels.contains(r#"Target="../drawings/drawing1.xml""#));
+        let drawing_rels = read_part(&wb, "xl/drawings/_rels/drawing1.xml.rels");
+        assert!(drawing_rels.contains(r#"Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="../charts/chart2.xml""#));
+
+        let drawing = read_part(&wb, "xl/drawings/drawing1.xml");
+        assert!(drawing.contains(
+            "<xdr:from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>1</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>"
+        ));
+        assert!(drawing.contains(r#"<c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId2"/>"#));
+
+        let chart_xml = read_part(&wb, "xl/charts/chart1.xml");
+        assert!(chart_xml.contains("<a:t>Revenue</a:t>"));
+        assert!(chart_xml.contains(r#"<c:barDir val="col"/>"#));
+        assert!(chart_xml.contains(r#"<c:tx><c:v>2024</c:v></c:tx>"#));
+        assert!(chart_xml.contains(r#"<a:srgbClr val="4472C4"/>"#));
+        assert!(chart_xml.contains(
+            "<c:cat><c:strRef><c:f>Data!$A$2:$A$5</c:f></c:strRef></c:cat><c:val><c:numRef><c:f>Data!$B$2:$B$5</c:f></c:numRef></c:val>"
+        ));
+        assert!(chart_xml.contains(r#"<c:legendPos val="b"/>"#));
+        assert!(!read_part(&wb, "xl/charts/chart2.xml").contains("<c:legend>"));
+    }
+
+    #[test]
+    fn test_pie_and_scatter_charts() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        let series = ChartSeries::new("Sheet1!$B$1:$B$3").categories("Sheet1!$A$1:$A$3");
+        ws.insert_chart(0, 3, Chart::new(ChartType::Pie).add_series(series.clone()))
+            .unwrap();
+        ws.insert_chart(0, 10, Chart::new(ChartType::Scatter).add_series(series))
+            .unwrap();
+
+        let pie = read_part(&wb, "xl/charts/chart1.xml");
+        assert!(pie.contains(r#"<c:pieChart><c:varyColors val="1"/>"#));
+        assert!(!pie.contains("<c:catAx>"));
+
+        let scatter = read_part(&wb, "xl/charts/chart2.xml");
+        assert!(
+            scatter.contains("<c:xVal><c:numRef><c:f>Sheet1!$A$1:$A$3</c:f></c:numRef></c:xVal>")
+        );
+        assert_eq!(scatter.matches("<c:valAx>").count(), 2);
+    }
+
+    fn tiny_png() -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend_from_slice(&13u32.to_be_bytes());
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&64u32.to_be_bytes());
+        data.extend_from_slice(&32u32.to_be_bytes());
+        data.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_image_parts() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.insert_chart(
+            10,
+            0,
+            Chart::new(ChartType::Line).add_series(ChartSeries::new("Sheet1!$A$1:$A$3")),
+        )
+        .unwrap();
+        let logo = Image::from_buffer(tiny_png())
+            .unwrap()
+            .scale(2.0, 2.0)
+            .offset(10, 4)
+            .alt_text("Company logo");
+        ws.insert_image(0, 0, logo).unwrap();
+
+        let content_types = read_part(&wb, "[Content_Types].xml");
+        assert!(content_types.contains(r#"<Default Extension="png" ContentType="image/png"/>"#));
+        assert!(!content_types.contains(r#"Extension="gif""#));
+
+        let buffer = XlsxWriter::new(&wb)
+            .write_to(Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(buffer).unwrap();
+        let mut media = Vec::new();
+        archive
+            .by_name("xl/media/image1.png")
+            .unwrap()
+            .read_to_end(&mut media)
+            .unwrap();
+        assert_eq!(media, tiny_png());
+
+        let rels = read_part(&wb, "xl/drawings/_rels/drawing1.xml.rels");
+        assert!(rels.contains(r#"Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image1.png""#));
+
+        let drawing = read_part(&wb, "xl/drawings/drawing1.xml");
+        assert!(drawing.contains(r#"<xdr:cNvPr id="3" name="Picture 1" descr="Company logo"/>"#));
+        assert!(drawing.contains(r#"r:embed="rId2""#));
+        // 128x64 px after scaling, starting 10 px right and 4 px down from A1.
+        assert!(
+            drawing.contains(r#"<a:off x="95250" y="38100"/><a:ext cx="1219200" cy="609600"/>"#)
+        );
+        assert!(drawing.contains(
+            "<xdr:to><xdr:col>2</xdr:col><xdr:colOff>95250</xdr:colOff><xdr:row>3</xdr:row><xdr:rowOff>76200</xdr:rowOff></xdr:to>"
+        ));
+    }
+
+    #[test]
+    fn test_defined_names_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.define_name(DefinedName::new("Regions", "=Data!$A$2:$A$20"))
+            .unwrap();
+        wb.define_name(DefinedName::new("Rate", "Data!$B$1").scope(0).hidden(true))
+            .unwrap();
+
+        let xml = read_part(&wb, "xl/workbook.xml");
+        assert!(xml.contains(
+            r#"</sheets><definedNames><definedName name="Regions">Data!$A$2:$A$20</definedName><definedName name="Rate" localSheetId="0" hidden="1">Data!$B$1</definedName></definedNames>"#
+        ));
+    }
+
+    #[test]
+    fn test_page_setup_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Data").unwrap();
+        wb.add_worksheet("Q1 Report").unwrap();
+        let margins = PageMargins {
+            left: 0.25,
+            right: 0.25,
+            ..PageMargins::default()
+        };
+        wb.get_worksheet_mut(1)
+            .unwrap()
+            .set_page_setup(
+                PageSetup::new()
+                    .orientation(Orientation::Landscape)
+                    .paper_size(9)
+                    .fit_to_pages(1, 0)
+                    .margins(margins)
+                    .center_horizontally(true)
+                    .print_gridlines(true)
+                    .print_area(0, 0, 19, 3)
+                    .repeat_rows(0, 1)
+                    .repeat_cols(0, 0),
+            )
+            .unwrap();
+
+        let plain = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(plain.contains(
+            r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>"#
+        ));
+        assert!(!plain.contains("<pageSetup"));
+        assert!(!plain.contains("<sheetPr>"));
+
+        let xml = read_part(&wb, "xl/worksheets/sheet2.xml");
+        assert!(xml.contains(
+            r#"<sheetPr><pageSetUpPr fitToPage="1"/></sheetPr><dimension ref="A1"/><sheetViews>"#
+        ));
+        assert!(xml.contains(
+            r#"<printOptions horizontalCentered="1" gridLines="1"/><pageMargins left="0.25" right="0.25" top="0.75" bottom="0.75" header="0.3" footer="0.3"/><pageSetup paperSize="9" fitToWidth="1" fitToHeight="0" orientation="landscape"/>"#
+        ));
+
+        let workbook_xml = read_part(&wb, "xl/workbook.xml");
+        assert!(workbook_xml.contains(
+            r#"<definedName name="_xlnm.Print_Area" localSheetId="1">'Q1 Report'!$A$1:$D$20</definedName>"#
+        ));
+        assert!(workbook_xml.contains(
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="1">'Q1 Report'!$A:$A,'Q1 Report'!$1:$2</definedName>"#
+        ));
+    }
+
+    #[test]
+    fn test_print_names_quote_reference_like_sheets() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Q1").unwrap();
+        wb.add_worksheet("R2C3").unwrap();
+        for idx in 0..2 {
+            wb.get_worksheet_mut(idx)
+                .unwrap()
+                .set_page_setup(PageSetup::new().print_area(0, 0, 3, 1).repeat_rows(0, 0))
+                .unwrap();
+        }
+
+        let workbook_xml = read_part(&wb, "xl/workbook.xml");
+        assert!(workbook_xml.contains(
+            r#"<definedName name="_xlnm.Print_Area" localSheetId="0">'Q1'!$A$1:$B$4</definedName>"#
+        ));
+        assert!(workbook_xml.contains(
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="1">'R2C3'!$1:$1</definedName>"#
+        ));
+    }
+
+    #[test]
+    fn test_header_footer_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Invoice").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.set_header(HeaderFooter::new("&LAcme && Co&RPage &P of &N").first_page("&CInvoice"))
+            .unwrap();
+        ws.set_footer(HeaderFooter::new("&C&F")).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"<headerFooter differentFirst="1"><oddHeader>&amp;LAcme &amp;&amp; Co&amp;RPage &amp;P of &amp;N</oddHeader><oddFooter>&amp;C&amp;F</oddFooter><firstHeader>&amp;CInvoice</firstHeader><firstFooter>&amp;C&amp;F</firstFooter></headerFooter>"#
+        ));
+        assert!(xml.find("<pageMargins").unwrap() < xml.find("<headerFooter").unwrap());
+    }
+
+    #[test]
+    fn test_page_breaks_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Invoices").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.set_h_page_breaks(&[30, 15]).unwrap();
+        ws.set_v_page_breaks(&[8]).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"<rowBreaks count="2" manualBreakCount="2"><brk id="15" max="16383" man="1"/><brk id="30" max="16383" man="1"/></rowBreaks><colBreaks count="1" manualBreakCount="1"><brk id="8" max="1048575" man="1"/></colBreaks>"#
+        ));
+    }
+
+    #[test]
+    fn test_cell_formats_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.write_string(0, 0, "Total").unwrap();
+        ws.write_number(0, 1, 12.5).unwrap();
+        ws.set_format(0, 0, Format::new().bold().fill_color(Color::rgb(0xDDEBF7)))
+            .unwrap();
+        ws.set_format(0, 1, Format::new().num_format("0.00").hidden(true))
+            .unwrap();
+        ws.set_format(1, 1, Format::new().locked(false).border_color(Color::BLACK))
+            .unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"<row r="1" spans="1:2"><c r="A1" s="1" t="inlineStr"><is><t>Total</t></is></c><c r="B1" s="2"><v>12.5</v></c></row><row r="2" spans="1:2"><c r="B2" s="3"/></row>"#
+        ));
+
+        let styles = read_part(&wb, "xl/styles.xml");
+        assert!(styles.contains(r#"<fonts count="2">"#));
+        assert!(styles.contains(
+            r#"<fill><patternFill patternType="solid"><fgColor rgb="FFDDEBF7"/><bgColor indexed="64"/></patternFill></fill>"#
+        ));
+        assert!(styles.contains(
+            r#"<cellXfs count="4"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="2" borderId="0" xfId="0" applyFont="1" applyFill="1"/><xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1" applyProtection="1"><protection hidden="1"/></xf><xf numFmtId="0" fontId="0" fillId="0" borderId="1" xfId="0" applyBorder="1" applyProtection="1"><protection locked="0"/></xf></cellXfs>"#
+        ));
+    }
+
+    #[test]
+    fn test_column_formats_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        ws.set_range_format(0, 1, MAX_ROWS - 1, 1, Format::new().locked(false))
+            .unwrap();
+        ws.write_number(1, 0, 1.0).unwrap();
+        ws.write_number(1, 1, 5.0).unwrap();
+        ws.clear_range_formats(0, 1, 0, 1).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"<cols><col min="2" max="2" width="9.140625" style="1"/></cols><sheetData><row r="1" spans="1:2"><c r="B1"/></row><row r="2" spans="1:2"><c r="A2"><v>1</v></c><c r="B2" s="1"><v>5</v></c></row></sheetData>"#
+        ));
+    }
+
+    #[test]
+    fn test_skipped_cells_leave_spans_and_dimension() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        let ws = wb.get_worksheet_mut(0).unwrap();
+        let unlocked = Format::new().locked(false);
+        ws.set_range_format(0, 1, MAX_ROWS - 1, 1, unlocked.clone())
+            .unwrap();
+        ws.write_number(1, 0, 1.0).unwrap();
+        ws.set_format(1, 3, Format::new().bold()).unwrap();
+        ws.clear_range_formats(1, 3, 1, 3).unwrap();
+        ws.set_format(4, 1, unlocked).unwrap();
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<dimension ref="A2"/>"#));
+        assert!(xml.contains(
+            r#"<sheetData><row r="2" spans="1:1"><c r="A2"><v>1</v></c></row></sheetData>"#
+        ));
+    }
+
+    #[test]
+    fn test_protection_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.get_worksheet_mut(0).unwrap().protect(
+            SheetProtection::new()
+                .password(PasswordHash::legacy("password"))
+                .sort(true)
+                .autofilter(true),
+        );
+        wb.protect_structure(Some(PasswordHash::sha512_with_salt("secret", &[1; 16], 10)));
+
+        let xml = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(
+            r#"</sheetData><sheetProtection password="83AF" sheet="1" objects="1" scenarios="1" sort="0" autoFilter="0"/>"#
+        ));
+
+        let workbook_xml = read_part(&wb, "xl/workbook.xml");
+        assert!(workbook_xml.contains(
+            r#"<workbookProtection workbookAlgorithmName="SHA-512" workbookHashValue=""#
+        ));
+        assert!(workbook_xml.contains(
+            r#"workbookSaltValue="AQEBAQEBAQEBAQEBAQEBAQ==" workbookSpinCount="10" lockStructure="1"/><bookViews>"#
+        ));
+    }
+
+    #[test]
+    fn test_document_properties_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Summary").unwrap();
+        wb.add_worksheet("Data").unwrap();
+        wb.set_properties(
+            DocumentProperties::new()
+                .title("Q1 Report")
+                .author("Finance Team")
+                .company("Acme & Co")
+                .created(Timestamp::parse("2024-01-15T08:00:00Z").unwrap()),
+        );
+
+        let core = read_part(&wb, "docProps/core.xml");
+        assert!(core.contains("<dc:title>Q1 Report</dc:title>"));
+        assert!(core.contains("<dc:creator>Finance Team</dc:creator>"));
+        assert!(core.contains(
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">2024-01-15T08:00:00Z</dcterms:created>"#
+        ));
+
+        let app = read_part(&wb, "docProps/app.xml");
+        assert!(app.contains(
+            r#"<TitlesOfParts><vt:vector size="2" baseType="lpstr"><vt:lpstr>Summary</vt:lpstr><vt:lpstr>Data</vt:lpstr></vt:vector></TitlesOfParts>"#
+        ));
+        assert!(app.contains("<Company>Acme &amp; Co</Company>"));
+
+        let rels = read_part(&wb, "_rels/.rels");
+        assert!(rels.contains(r#"Target="docProps/core.xml""#));
+        assert!(!rels.contains("custom.xml"));
+    }
+
+    #[test]
+    fn test_custom_properties_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Sheet1").unwrap();
+        wb.set_custom_property("Department", CustomProperty::Text("Finance".into()))
+            .unwrap();
+        wb.set_custom_property("Revision", CustomProperty::Number(3.0))
+            .unwrap();
+        wb.set_custom_property("Approved", CustomProperty::Boolean(true))
+            .unwrap();
+        wb.set_custom_property(
+            "Due",
+            CustomProperty::Date(Timestamp::parse("2024-06-30").unwrap()),
+        )
+        .unwrap();
+
+        let custom = read_part(&wb, "docProps/custom.xml");
+        assert!(custom.contains(
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Department"><vt:lpwstr>Finance</vt:lpwstr></property>"#
+        ));
+        assert!(custom.contains(r#"pid="3" name="Revision"><vt:i4>3</vt:i4>"#));
+        assert!(custom.contains(r#"pid="4" name="Approved"><vt:bool>true</vt:bool>"#));
+        assert!(custom
+            .contains(r#"pid="5" name="Due"><vt:filetime>2024-06-30T00:00:00Z</vt:filetime>"#));
+
+        let content_types = read_part(&wb, "[Content_Types].xml");
+        assert!(content_types.contains(r#"PartName="/docProps/custom.xml""#));
+    }
+
+    #[test]
+    fn test_sheet_views_xml() {
+        let mut wb = Workbook::new();
+        wb.add_worksheet("Lookup").unwrap();
+        wb.add_worksheet("Report").unwrap();
+        wb.add_worksheet("Notes").unwrap();
+        wb.set_active_sheet(1).unwrap();
+
+        let lookup = wb.get_worksheet_mut(0).unwrap();
+        lookup.set_visibility(SheetVisibility::VeryHidden);
+        let report = wb.get_worksheet_mut(1).unwrap();
+        report.set_tab_color(Color::rgb(0x00B050));
+        report
+            .set_view(
+                SheetView::new()
+                    .zoom(125)
+                    .show_gridlines(false)
+                    .right_to_left(true)
+                    .selection(1, 1, 3, 2),
+            )
+            .unwrap();
+        wb.get_worksheet_mut(2).unwrap().set_selected(true);
+
+        let workbook_xml = read_part(&wb, "xl/workbook.xml");
+        assert!(workbook_xml.contains(
+            r#"<bookViews><workbookView activeTab="1"/></bookViews><sheets><sheet name="Lookup" sheetId="1" state="veryHidden" r:id="rId1"/>"#
+        ));
+
+        let report = read_part(&wb, "xl/worksheets/sheet2.xml");
+        assert!(report.contains(
+            r#"<sheetPr><tabColor rgb="FF00B050"/></sheetPr><dimension ref="A1"/><sheetViews><sheetView showGridLines="0" rightToLeft="1" tabSelected="1" zoomScale="125" zoomScaleNormal="125" workbookViewId="0"><selection activeCell="B2" sqref="B2:C4"/></sheetView></sheetViews><sheetData"#
+        ));
+
+        let lookup = read_part(&wb, "xl/worksheets/sheet1.xml");
+        assert!(lookup.contains(r#"<sheetViews><sheetView workbookViewId="0"/></sheetViews>"#));
+        let notes = read_part(&wb, "xl/worksheets/sheet3.xml");
+        assert!(notes.contains(r#"<sheetView tabSelected="1" workbookViewId="0"/>"#));
+    }
 }
@@ -0,0 +1,263 @@
+//! Golden-file tests: workbooks are saved with a fixed timestamp and every
+//! part of the package is compared with the copy checked in under
+//! `tests/golden/<case>/`. `parts.txt` lists the parts in archive order,
+//! and `<case>.xlsx` is the whole file saved without compression.
+//!
+//! After an intended change to the output, regenerate the fixtures with
+//! `UPDATE_GOLDEN=1 cargo test --test golden` and review the diff.
+
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use zip::ZipArchive;
+
+use excelifyjs::cell::{CellError, CellValue};
+use excelifyjs::chart::{Chart, ChartSeries, ChartType};
+use excelifyjs::conditional_format::{ConditionalCriteria, ConditionalFormat};
+use excelifyjs::defined_name::DefinedName;
+use excelifyjs::format::{Color, Format};
+use excelifyjs::header_footer::HeaderFooter;
+use excelifyjs::image::Image;
+use excelifyjs::page_setup::{Orientation, PageSetup};
+use excelifyjs::properties::{CustomProperty, DocumentProperties, Timestamp};
+use excelifyjs::protection::{PasswordHash, SheetProtection};
+use excelifyjs::rich_text::RichString;
+use excelifyjs::save_options::SaveOptions;
+use excelifyjs::sheet_view::{SheetView, SheetVisibility};
+use excelifyjs::validation::{DataValidation, ValidationCriteria, ValidationRule};
+use excelifyjs::workbook::Workbook;
+use excelifyjs::writer::XlsxWriter;
+
+fn save_with(workbook: &Workbook, options: SaveOptions) -> Vec<u8> {
+    let timestamp = Timestamp::parse("2024-03-01T09:30:00Z").unwrap();
+    XlsxWriter::new(workbook)
+        .options(options.timestamp(timestamp))
+        .write_to(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner()
+}
+
+fn save(workbook: &Workbook) -> Vec<u8> {
+    save_with(workbook, SaveOptions::new())
+}
+
+fn check_golden(case: &str, workbook: &Workbook) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut archive = ZipArchive::new(Cursor::new(save(workbook))).unwrap();
+    let names: Vec<String> = (0..archive.len())
+        .map(|index| archive.by_index(index).unwrap().name().to_string())
+        .collect();
+
+    // Golden files as paths relative to `root`, with their expected content.
+    let mut files = vec![(format!("{}/parts.txt", case), names.join("\n").into_bytes())];
+    for name in &names {
+        let mut data = Vec::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        files.push((format!("{}/{}", case, name), data));
+    }
+    // Stored entries pin the ZIP layout and metadata too, independently of
+    // the Deflate implementation.
+    let stored = save_with(workbook, SaveOptions::new().compression_level(0));
+    files.push((format!("{}.xlsx", case), stored));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let _ = fs::remove_dir_all(root.join(case));
+        for (name, data) in &files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+        return;
+    }
+
+    for (name, data) in &files {
+        let expected = read_fixture(&root.join(name));
+        assert!(
+            *data == expected,
+            "{} differs from the golden file:\n{}",
+            name,
+            String::from_utf8_lossy(data)
+        );
+    }
+}
+
+fn read_fixture(path: &Path) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden file {} ({}); run with UPDATE_GOLDEN=1 to create it",
+            path.display(),
+            e
+        )
+    })
+}
+
+fn tiny_png() -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    data.extend_from_slice(&13u32.to_be_bytes());
+    data.extend_from_slice(b"IHDR");
+    data.extend_from_slice(&64u32.to_be_bytes());
+    data.extend_from_slice(&32u32.to_be_bytes());
+    data.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    data
+}
+
+/// Values of every type, formats and document properties on two sheets.
+/// Cells are written out of order to show that it doesn't matter.
+fn cells_workbook(reversed: bool) -> Workbook {
+    let mut wb = Workbook::new();
+    wb.add_worksheet("Sales").unwrap();
+    wb.add_worksheet("Notes").unwrap();
+
+    let header = Format::new().bold().fill_color(Color::rgb(0xDDEBF7));
+    let money = Format::new().num_format("#,##0.00");
+    let mut cells = vec![
+        (0, 0, CellValue::String("Region".into())),
+        (0, 1, CellValue::String("Total".into())),
+        (0, 2, CellValue::String("Closed".into())),
+        (1, 0, CellValue::String(" North ".into())),
+        (1, 1, CellValue::Number(1250.5)),
+        (1, 2, CellValue::Boolean(true)),
+        (2, 0, CellValue::String("South & <East>".into())),
+        (2, 1, CellValue::Error(CellError::NA)),
+        (2, 2, CellValue::Boolean(false)),
+        (20, 3, CellValue::Number(-1e-9)),
+    ];
+    if reversed {
+        cells.reverse();
+    }
+    let ws = wb.get_worksheet_mut(0).unwrap();
+    for (row, col, value) in cells {
+        ws.write(row, col, value).unwrap();
+    }
+    ws.set_range_format(0, 0, 0, 2, header).unwrap();
+    ws.set_range_format(1, 1, 2, 1, money).unwrap();
+    ws.set_format(5, 5, Format::new().locked(false)).unwrap();
+
+    let note = RichString::new()
+        .formatted(
+            "Important: ",
+            Format::new().bold().font_color(Color::rgb(0xC00000)),
+        )
+        .text("figures are provisional");
+    let notes = wb.get_worksheet_mut(1).unwrap();
+    notes.write(0, 0, note).unwrap();
+    notes.set_tab_color(Color::rgb(0x00B050));
+
+    wb.set_properties(
+        DocumentProperties::new()
+            .title("Quarterly sales")
+            .author("Finance")
+            .created(Timestamp::parse("2024-01-15T08:00:00Z").unwrap()),
+    );
+    wb.set_custom_property("Reviewed", CustomProperty::Boolean(true))
+        .unwrap();
+    wb.define_name(DefinedName::new("Totals", "=Sales!$B$2:$B$3"))
+        .unwrap();
+    wb
+}
+
+/// Validations, conditional formats, a chart, an image and print settings.
+fn objects_workbook() -> Workbook {
+    let mut wb = Workbook::new();
+    wb.add_worksheet("Data").unwrap();
+    wb.add_worksheet("Lookup").unwrap();
+
+    let ws = wb.get_worksheet_mut(0).unwrap();
+    for row in 0..4 {
+        ws.write(row, 0, format!("Item {}", row + 1)).unwrap();
+        ws.write(row, 1, f64::from(row * 10 + 5)).unwrap();
+    }
+    ws.add_data_validation(
+        0,
+        1,
+        3,
+        1,
+        DataValidation::new(ValidationRule::WholeNumber(ValidationCriteria::Between(
+            0.0, 100.0,
+        ))),
+    )
+    .unwrap();
+    ws.add_conditional_format(
+        0,
+        1,
+        3,
+        1,
+        ConditionalFormat::CellValue {
+            criteria: ConditionalCriteria::GreaterThan("20".into()),
+            format: Format::new().font_color(Color::rgb(0x9C0006)),
+        },
+    )
+    .unwrap();
+    ws.add_conditional_format(
+        0,
+        1,
+        3,
+        1,
+        ConditionalFormat::DataBar {
+            color: Color::rgb(0x638EC6),
+        },
+    )
+    .unwrap();
+    ws.insert_chart(
+        6,
+        3,
+        Chart::new(ChartType::Column)
+            .title("Quantities")
+            .add_series(
+                ChartSeries::new("Data!$B$1:$B$4")
+                    .categories("Data!$A$1:$A$4")
+                    .name("Qty"),
+            ),
+    )
+    .unwrap();
+    ws.insert_image(
+        0,
+        3,
+        Image::from_buffer(tiny_png()).unwrap().alt_text("Logo"),
+    )
+    .unwrap();
+    ws.set_page_setup(
+        PageSetup::new()
+            .orientation(Orientation::Landscape)
+            .print_area(0, 0, 3, 1)
+            .repeat_rows(0, 0),
+    )
+    .unwrap();
+    ws.set_header(HeaderFooter::new("&CInventory")).unwrap();
+    ws.set_view(SheetView::new().zoom(120).selection(1, 1, 1, 1))
+        .unwrap();
+    ws.protect(
+        SheetProtection::new()
+            .password(PasswordHash::sha512_with_salt("secret", &[7; 16], 1000))
+            .format_cells(true),
+    );
+
+    let lookup = wb.get_worksheet_mut(1).unwrap();
+    lookup.write(0, 0, "Yes").unwrap();
+    lookup.write(1, 0, "No").unwrap();
+    lookup.set_visibility(SheetVisibility::Hidden);
+    wb
+}
+
+#[test]
+fn test_cells_golden() {
+    check_golden("cells", &cells_workbook(false));
+}
+
+#[test]
+fn test_objects_golden() {
+    check_golden("objects", &objects_workbook());
+}
+
+#[test]
+fn test_identical_input_gives_identical_bytes() {
+    let first = save(&cells_workbook(false));
+    assert_eq!(first, save(&cells_workbook(false)));
+    assert_eq!(first, save(&cells_workbook(true)));
+    assert_eq!(save(&objects_workbook()), save(&objects_workbook()));
+}